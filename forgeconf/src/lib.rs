@@ -49,6 +49,101 @@ pub trait ForgeconfClap {
         out: &mut std::collections::BTreeMap<String, String>,
     );
 }
+
+/// An optional section takes the flags of the section itself.
+#[cfg(feature = "clap")]
+impl<T: ForgeconfClap> ForgeconfClap for Option<T> {
+    fn augment_clap_with_prefix(cmd: clap::Command, prefix: Option<&str>) -> clap::Command {
+        T::augment_clap_with_prefix(cmd, prefix)
+    }
+
+    fn extract_clap_with_prefix(
+        matches: &clap::ArgMatches,
+        prefix: Option<&str>,
+        out: &mut std::collections::BTreeMap<String, String>,
+    ) {
+        T::extract_clap_with_prefix(matches, prefix, out)
+    }
+}
+
+/// Items used by the code `#[forgeconf]` generates; not part of the public
+/// API.
+#[doc(hidden)]
+pub mod __private {
+    pub use forgeconf_core::__private::*;
+
+    /// Calls the `ForgeconfClap` impl of a nested section's type when it has
+    /// one, the same way `NodeProbe` reaches `ToNode`:
+    /// `(&ClapProbe::<T>::new()).augment(..)` resolves to [`ViaForgeconfClap`]
+    /// when `T: ForgeconfClap` and to [`WithoutForgeconfClap`], which adds no
+    /// flags, otherwise. Only used for concrete types: a field type naming a
+    /// type parameter gets a `ForgeconfClap` bound instead.
+    #[cfg(feature = "clap")]
+    pub struct ClapProbe<T>(std::marker::PhantomData<T>);
+
+    #[cfg(feature = "clap")]
+    impl<T> ClapProbe<T> {
+        #[allow(clippy::new_without_default)]
+        pub fn new() -> Self {
+            Self(std::marker::PhantomData)
+        }
+    }
+
+    #[cfg(feature = "clap")]
+    pub trait ViaForgeconfClap {
+        fn augment(&self, cmd: clap::Command, prefix: &str) -> clap::Command;
+
+        fn extract(
+            &self,
+            matches: &clap::ArgMatches,
+            prefix: &str,
+            out: &mut std::collections::BTreeMap<String, String>,
+        );
+    }
+
+    #[cfg(feature = "clap")]
+    impl<T: crate::ForgeconfClap> ViaForgeconfClap for ClapProbe<T> {
+        fn augment(&self, cmd: clap::Command, prefix: &str) -> clap::Command {
+            T::augment_clap_with_prefix(cmd, Some(prefix))
+        }
+
+        fn extract(
+            &self,
+            matches: &clap::ArgMatches,
+            prefix: &str,
+            out: &mut std::collections::BTreeMap<String, String>,
+        ) {
+            T::extract_clap_with_prefix(matches, Some(prefix), out)
+        }
+    }
+
+    #[cfg(feature = "clap")]
+    pub trait WithoutForgeconfClap {
+        fn augment(&self, cmd: clap::Command, prefix: &str) -> clap::Command;
+
+        fn extract(
+            &self,
+            matches: &clap::ArgMatches,
+            prefix: &str,
+            out: &mut std::collections::BTreeMap<String, String>,
+        );
+    }
+
+    #[cfg(feature = "clap")]
+    impl<T> WithoutForgeconfClap for &ClapProbe<T> {
+        fn augment(&self, cmd: clap::Command, _prefix: &str) -> clap::Command {
+            cmd
+        }
+
+        fn extract(
+            &self,
+            _matches: &clap::ArgMatches,
+            _prefix: &str,
+            _out: &mut std::collections::BTreeMap<String, String>,
+        ) {
+        }
+    }
+}
 #[cfg(all(feature = "parse", feature = "json"))]
 pub use forgeconf_core::parse_json;
 #[cfg(feature = "parse")]
@@ -59,17 +154,38 @@ pub use forgeconf_core::parse_toml;
 pub use forgeconf_core::parse_yaml;
 #[cfg(feature = "validators")]
 pub use forgeconf_core::validators;
-#[cfg(feature = "cli")]
-pub use forgeconf_core::{CliArgsSource, CliArguments};
 pub use forgeconf_core::{
+    BuildFn,
+    ByteSize,
+    Coercion,
     ConfigBuilder,
+    ConfigDir,
     ConfigError,
     ConfigFile,
     ConfigNode,
     ConfigSource,
+    EnvSource,
+    Explained,
     FileFormat,
     FromNode,
+    Layers,
+    LoadContext,
+    MergeStrategy,
+    Origin,
+    PathOptions,
+    Provenance,
+    ReloadEvent,
+    ReloadHandle,
+    Reloadable,
+    Secret,
+    SourceDocument,
+    SourceMap,
+    ToNode,
     load_from_path,
     merge_nodes,
+    merge_nodes_with,
+    schema,
 };
+#[cfg(feature = "cli")]
+pub use forgeconf_core::{CliArgsSource, CliArguments};
 pub use forgeconf_macros::forgeconf;
@@ -0,0 +1,137 @@
+#![cfg(feature = "clap")]
+
+use std::time::Duration;
+
+use forgeconf::clap::Command;
+use forgeconf::clap::error::ErrorKind;
+use forgeconf::{ConfigError, forgeconf, validators};
+
+#[forgeconf]
+struct ServerSettings {
+    #[field(default = 8080, validate = validators::range(1024, 65535))]
+    port: u16,
+    #[field(default = Duration::from_secs(30))]
+    timeout: Duration,
+}
+
+#[forgeconf]
+struct ClapConfig {
+    #[field(default = false)]
+    debug: bool,
+    #[field(default = 0, count, short = 'v')]
+    verbose: u8,
+    #[field(default = "info".to_string(), validate = validators::one_of(["info".to_string(), "warn".to_string()]))]
+    level: String,
+    #[field(default = 3, validate = validators::range(1, 10))]
+    retries: u64,
+    #[field(default = Vec::new(), separator = ";")]
+    ports: Vec<u8>,
+    #[field(nested)]
+    server: ServerSettings,
+}
+
+fn command() -> Command {
+    ClapConfig::augment_clap(Command::new("app").no_binary_name(true))
+}
+
+fn load(args: &[&str]) -> Result<ClapConfig, ConfigError> {
+    let matches = command().try_get_matches_from(args).unwrap();
+    ClapConfig::loader()
+        .add_source(ClapConfig::from_clap(&matches))
+        .load()
+}
+
+#[test]
+fn typed_values_reach_the_config() -> Result<(), ConfigError> {
+    let cfg = load(&[
+        "--debug",
+        "-vvv",
+        "--level",
+        "warn",
+        "--retries=7",
+        "--ports=1;2",
+        "--server-port=9000",
+        "--server-timeout=2m",
+    ])?;
+
+    assert!(cfg.debug);
+    assert_eq!(cfg.verbose, 3);
+    assert_eq!(cfg.level, "warn");
+    assert_eq!(cfg.retries, 7);
+    assert_eq!(cfg.ports, vec![1, 2]);
+    assert_eq!(cfg.server.port, 9000);
+    assert_eq!(cfg.server.timeout, Duration::from_secs(120));
+    Ok(())
+}
+
+#[test]
+fn absent_args_keep_defaults() -> Result<(), ConfigError> {
+    let cfg = load(&[])?;
+
+    assert!(!cfg.debug);
+    assert_eq!(cfg.verbose, 0);
+    assert_eq!(cfg.server.port, 8080);
+    Ok(())
+}
+
+#[test]
+fn no_flags_turn_switches_off() {
+    let matches = command()
+        .try_get_matches_from(["--debug", "--no-debug"])
+        .unwrap();
+    let args = ClapConfig::from_clap(&matches);
+
+    assert_eq!(
+        forgeconf::ConfigSource::load(&args)
+            .unwrap()
+            .get_path("debug"),
+        Some(&forgeconf::ConfigNode::Scalar("false".into()))
+    );
+}
+
+#[test]
+fn clap_rejects_invalid_values_up_front() {
+    let cases: [&[&str]; 6] = [
+        &["--server-port=abc"],
+        &["--server-port=80"],
+        &["--retries=11"],
+        &["--ports=1;300"],
+        &["--level=debug"],
+        &["--server-timeout=soon"],
+    ];
+    for args in cases {
+        let err = command().try_get_matches_from(args).unwrap_err();
+        assert!(
+            matches!(err.kind(), ErrorKind::ValueValidation | ErrorKind::InvalidValue),
+            "{args:?}: {err}"
+        );
+    }
+
+    let err = command()
+        .try_get_matches_from(["--debug=true"])
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TooManyValues);
+}
+
+#[forgeconf]
+struct Service<S> {
+    #[field(nested)]
+    settings: S,
+    #[field(nested)]
+    fallback: Option<S>,
+}
+
+#[test]
+fn generic_sections_get_their_flags() -> Result<(), ConfigError> {
+    let command = Service::<ServerSettings>::augment_clap(Command::new("app").no_binary_name(true));
+    let matches = command
+        .try_get_matches_from(["--settings-port=9000", "--fallback-port=9001"])
+        .unwrap();
+    let cfg = Service::<ServerSettings>::loader()
+        .add_source(Service::<ServerSettings>::from_clap(&matches))
+        .load()?;
+
+    assert_eq!(cfg.settings.port, 9000);
+    assert_eq!(cfg.fallback.map(|fallback| fallback.port), Some(9001));
+    Ok(())
+}
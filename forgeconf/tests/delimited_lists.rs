@@ -0,0 +1,84 @@
+#![cfg(feature = "cli")]
+
+use std::collections::BTreeSet;
+
+use forgeconf::{CliArguments, ConfigError, EnvSource, forgeconf};
+
+#[forgeconf(config(path = "tests/fixtures/basic.toml"))]
+struct ListConfig {
+    #[field(default = Vec::new())]
+    hosts: Vec<String>,
+    #[field(default = BTreeSet::new())]
+    ports: BTreeSet<u16>,
+    #[field(env = "FORGECONF_SEARCH_PATH", separator = ":", default = Vec::new())]
+    search_path: Vec<String>,
+    #[field(separator = "x", default = [0, 0])]
+    size: [u32; 2],
+    #[field(separator = ":", default = (String::new(), 0))]
+    upstream: (String, u16),
+}
+
+#[test]
+fn env_values_split_on_commas() -> Result<(), ConfigError> {
+    let cfg =
+        ListConfig::loader()
+            .add_source(EnvSource::new("APP_").with_vars([
+                ("APP_HOSTS", " a.example, b.example "),
+                ("APP_PORTS", "[8080, 8443]"),
+            ]))
+            .load()?;
+
+    assert_eq!(cfg.hosts, ["a.example", "b.example"]);
+    assert_eq!(cfg.ports, BTreeSet::from([8080, 8443]));
+    Ok(())
+}
+
+#[test]
+fn cli_values_accept_json_arrays() -> Result<(), ConfigError> {
+    let cfg = ListConfig::loader()
+        .add_source(CliArguments::new().with_args([r#"--hosts=["a,b", "c"]"#, "--ports="]))
+        .load()?;
+
+    assert_eq!(cfg.hosts, ["a,b", "c"]);
+    assert!(cfg.ports.is_empty());
+    Ok(())
+}
+
+#[test]
+fn field_separator_applies_to_env_overrides() -> Result<(), ConfigError> {
+    unsafe {
+        std::env::set_var("FORGECONF_SEARCH_PATH", "/usr/lib: /opt/lib");
+    };
+    let cfg = ListConfig::loader().load();
+    unsafe {
+        std::env::remove_var("FORGECONF_SEARCH_PATH");
+    };
+
+    assert_eq!(cfg?.search_path, ["/usr/lib", "/opt/lib"]);
+    Ok(())
+}
+
+#[test]
+fn field_separator_applies_to_arrays_and_tuples() -> Result<(), ConfigError> {
+    let cfg = ListConfig::loader()
+        .add_source(
+            EnvSource::new("APP_")
+                .with_vars([("APP_SIZE", "1280x720"), ("APP_UPSTREAM", "db.internal:5432")]),
+        )
+        .load()?;
+
+    assert_eq!(cfg.size, [1280, 720]);
+    assert_eq!(cfg.upstream, ("db.internal".to_string(), 5432));
+    Ok(())
+}
+
+#[test]
+fn invalid_items_report_their_index() {
+    let err = ListConfig::loader()
+        .add_source(EnvSource::new("APP_").with_vars([("APP_PORTS", "80, http")]))
+        .load()
+        .err()
+        .unwrap();
+
+    assert!(err.to_string().contains("ports[1]"), "{err}");
+}
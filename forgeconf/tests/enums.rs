@@ -0,0 +1,165 @@
+#![cfg(all(feature = "parse", feature = "toml"))]
+
+use forgeconf::{ConfigError, forgeconf};
+
+#[derive(Debug, PartialEq)]
+#[forgeconf(insensitive = true)]
+enum LogLevel {
+    Debug,
+    Info,
+    #[variant(name = "warning")]
+    Warn,
+}
+
+#[derive(Debug, PartialEq)]
+#[forgeconf]
+struct Bucket {
+    bucket: String,
+}
+
+#[derive(Debug, PartialEq)]
+#[forgeconf(tag = "kind")]
+enum Storage {
+    Memory,
+    Disk {
+        path: String,
+        #[field(default = 64)]
+        max_mb: u32,
+    },
+    S3(Bucket),
+}
+
+#[derive(Debug, PartialEq)]
+#[forgeconf]
+enum Sink {
+    Stdout,
+    File { path: String },
+}
+
+#[derive(Debug)]
+#[forgeconf]
+struct ServiceConfig {
+    log_level: LogLevel,
+    storage: Storage,
+    #[field(default = Sink::Stdout)]
+    sink: Sink,
+}
+
+#[test]
+fn unit_variants_load_from_strings() -> Result<(), ConfigError> {
+    let cfg = ServiceConfig::parse_toml(
+        r#"
+        log_level = "DEBUG"
+        storage = "memory"
+        "#,
+    )?;
+
+    assert_eq!(cfg.log_level, LogLevel::Debug);
+    assert_eq!(cfg.storage, Storage::Memory);
+    assert_eq!(cfg.sink, Sink::Stdout);
+    Ok(())
+}
+
+#[test]
+fn renamed_variants_use_their_name() -> Result<(), ConfigError> {
+    let cfg = ServiceConfig::parse_toml(
+        r#"
+        log_level = "warning"
+        storage = { kind = "memory" }
+        "#,
+    )?;
+
+    assert_eq!(cfg.log_level, LogLevel::Warn);
+    Ok(())
+}
+
+#[test]
+fn internally_tagged_variants_load_payload() -> Result<(), ConfigError> {
+    let cfg = ServiceConfig::parse_toml(
+        r#"
+        log_level = "info"
+        storage = { kind = "s3", bucket = "assets" }
+        "#,
+    )?;
+    assert_eq!(cfg.storage, Storage::S3(Bucket { bucket: "assets".into() }));
+
+    let cfg = ServiceConfig::parse_toml(
+        r#"
+        log_level = "info"
+        storage = { kind = "disk", path = "/var/lib/app" }
+        "#,
+    )?;
+    assert_eq!(cfg.storage, Storage::Disk { path: "/var/lib/app".into(), max_mb: 64 });
+    Ok(())
+}
+
+#[test]
+fn externally_tagged_variants_load_payload() -> Result<(), ConfigError> {
+    let cfg = ServiceConfig::parse_toml(
+        r#"
+        log_level = "info"
+        storage = "memory"
+        sink = { file = { path = "/tmp/out.log" } }
+        "#,
+    )?;
+
+    assert_eq!(cfg.sink, Sink::File { path: "/tmp/out.log".into() });
+    Ok(())
+}
+
+#[test]
+fn unknown_variant_lists_valid_names() {
+    let err = ServiceConfig::parse_toml(
+        r#"
+        log_level = "trace"
+        storage = "memory"
+        "#,
+    )
+    .unwrap_err();
+
+    assert!(matches!(
+        err,
+        ConfigError::TypeMismatch { field, expected, found, .. }
+            if field == "log_level"
+                && expected == "one of [debug, info, warning]"
+                && found == "trace"
+    ));
+}
+
+#[test]
+fn missing_tag_is_reported_with_path() {
+    let err = ServiceConfig::parse_toml(
+        r#"
+        log_level = "info"
+        storage = { bucket = "assets" }
+        "#,
+    )
+    .unwrap_err();
+
+    assert!(matches!(
+        err,
+        ConfigError::MissingValue { field, .. } if field == "storage.kind"
+    ));
+}
+
+#[derive(Debug, PartialEq)]
+#[forgeconf]
+enum Listener {
+    HTTPServer,
+    GrpcTLS,
+}
+
+#[forgeconf]
+struct ListenerConfig {
+    listener: Listener,
+}
+
+#[test]
+fn variant_names_keep_acronyms_together() -> Result<(), ConfigError> {
+    let cfg = ListenerConfig::parse_toml(r#"listener = "http_server""#)?;
+    assert_eq!(cfg.listener, Listener::HTTPServer);
+
+    let cfg = ListenerConfig::parse_toml(r#"listener = "grpc_tls""#)?;
+    assert_eq!(cfg.listener, Listener::GrpcTLS);
+    Ok(())
+}
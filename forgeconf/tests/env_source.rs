@@ -0,0 +1,30 @@
+use forgeconf::{ConfigError, EnvSource, forgeconf};
+
+#[forgeconf]
+struct DatabaseSettings {
+    host: String,
+    port: u16,
+}
+
+#[forgeconf(config(path = "tests/fixtures/basic.toml"))]
+struct EnvConfig {
+    port: u16,
+    #[field(nested)]
+    database: DatabaseSettings,
+}
+
+#[test]
+fn env_source_fills_nested_sections() -> Result<(), ConfigError> {
+    let cfg = EnvConfig::loader()
+        .add_source(EnvSource::new("APP_").with_vars([
+            ("APP_PORT", "4100"),
+            ("APP_DATABASE__HOST", "db.internal"),
+            ("APP_DATABASE__PORT", "6432"),
+        ]))
+        .load()?;
+
+    assert_eq!(cfg.port, 4100);
+    assert_eq!(cfg.database.host, "db.internal");
+    assert_eq!(cfg.database.port, 6432);
+    Ok(())
+}
@@ -16,3 +16,143 @@ fn missing_fields_raise_useful_errors() {
         other => panic!("unexpected error: {other:?}"),
     }
 }
+
+#[derive(Debug)]
+#[forgeconf]
+#[allow(dead_code)]
+struct Database {
+    host: String,
+    port: u16,
+}
+
+#[derive(Debug)]
+#[forgeconf]
+#[allow(dead_code)]
+struct ServiceConfig {
+    name: String,
+    #[field(validate = unprivileged)]
+    port: u16,
+    #[field(nested)]
+    database: Database,
+}
+
+fn unprivileged(value: &u16, key: &str) -> Result<(), ConfigError> {
+    if *value >= 1024 {
+        Ok(())
+    } else {
+        Err(ConfigError::mismatch(key, "port >= 1024", value.to_string()))
+    }
+}
+
+fn field_names(errors: &[ConfigError]) -> Vec<String> {
+    errors
+        .iter()
+        .map(|err| match err {
+            ConfigError::MissingValue { field, .. } | ConfigError::TypeMismatch { field, .. } => {
+                field.clone()
+            },
+            ConfigError::Nested { section, .. } => section.clone(),
+            other => panic!("unexpected error: {other:?}"),
+        })
+        .collect()
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn collect_errors_reports_every_failure() {
+    let err = ServiceConfig::loader()
+        .add_source(forgeconf::ConfigFile::new("tests/fixtures/broken-service.toml"))
+        .collect_errors()
+        .load()
+        .unwrap_err();
+
+    let ConfigError::Multiple { errors } = err else {
+        panic!("expected multiple errors, got {err:?}");
+    };
+    assert_eq!(field_names(&errors), ["name", "port", "database"]);
+
+    let ConfigError::Nested { source, .. } = &errors[2] else {
+        panic!("expected nested error");
+    };
+    let source: &dyn std::error::Error = &**source;
+    let Some(ConfigError::Multiple { errors: nested }) = source.downcast_ref::<ConfigError>()
+    else {
+        panic!("expected multiple nested errors");
+    };
+    assert_eq!(field_names(nested), ["host", "port"]);
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn load_stops_at_first_error_by_default() {
+    let err = ServiceConfig::loader()
+        .add_source(forgeconf::ConfigFile::new("tests/fixtures/broken-service.toml"))
+        .load()
+        .unwrap_err();
+
+    assert!(matches!(err, ConfigError::MissingValue { field, .. } if field == "name"));
+}
+
+#[derive(Debug)]
+#[forgeconf]
+#[allow(dead_code)]
+struct Ports {
+    ports: Vec<u16>,
+    limits: std::collections::BTreeMap<String, u8>,
+}
+
+#[cfg(feature = "toml")]
+fn ports_node() -> forgeconf::ConfigNode {
+    forgeconf::parse_toml(
+        r#"
+        ports = ["x", 80, "y"]
+        limits = { a = "big", b = 1, c = "huge" }
+        "#,
+    )
+    .unwrap()
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn collect_from_reports_every_bad_collection_item() {
+    let err = Ports::collect_from(&ports_node()).unwrap_err();
+
+    let ConfigError::Multiple { errors } = err else {
+        panic!("expected multiple errors, got {err:?}");
+    };
+    assert_eq!(field_names(&errors), ["ports[0]", "ports[2]", "limits.a", "limits.c"]);
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn load_from_stops_at_the_first_bad_collection_item() {
+    let err = Ports::load_from(&ports_node()).unwrap_err();
+
+    assert!(matches!(err, ConfigError::TypeMismatch { field, .. } if field == "ports[0]"));
+}
+
+#[derive(Debug)]
+#[forgeconf]
+#[allow(dead_code)]
+struct Replicas {
+    replicas: [Database; 2],
+    primary: (String, Database),
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn collect_from_reports_every_bad_fixed_size_item() {
+    let node = forgeconf::parse_toml(
+        r#"
+        replicas = [{ port = 1 }, { port = 2 }]
+        primary = ["main", { port = 3 }]
+        "#,
+    )
+    .unwrap();
+    let err = Replicas::collect_from(&node).unwrap_err();
+
+    let ConfigError::Multiple { errors } = err else {
+        panic!("expected multiple errors, got {err:?}");
+    };
+    assert_eq!(field_names(&errors), ["replicas[0]", "replicas[1]", "primary[1]"]);
+}
@@ -0,0 +1,66 @@
+#![cfg(all(feature = "parse", feature = "toml"))]
+
+use forgeconf::{ConfigError, FileFormat, forgeconf, parse_toml};
+
+#[allow(dead_code)]
+#[derive(Debug)]
+#[forgeconf]
+struct Database {
+    #[field(help = "Connection string")]
+    url: String,
+    #[field(default = 10, help = "Pool size")]
+    pool: u32,
+}
+
+#[allow(dead_code)]
+#[derive(Debug)]
+#[forgeconf]
+struct AppConfig {
+    #[field(help = "Service name shown in logs")]
+    name: String,
+    #[field(default = 8080)]
+    port: u16,
+    #[field(default = vec!["api".to_string()])]
+    tags: Vec<String>,
+    #[field(optional = true)]
+    motd: Option<String>,
+    #[field(nested)]
+    database: Database,
+}
+
+#[test]
+fn toml_example_is_annotated() {
+    let example = AppConfig::example_config(FileFormat::Toml).unwrap();
+
+    assert!(example.contains("# Service name shown in logs\n# required\nname = \"<name>\"\n"));
+    assert!(example.contains("port = 8080\n"));
+    assert!(example.contains("tags = [\"api\"]\n"));
+    assert!(example.contains("# motd = \"<motd>\"\n"));
+    assert!(example.contains("[database]\n# Pool size\npool = 10\n"));
+    assert!(example.contains("# Connection string\n# required\nurl = \"<url>\"\n"));
+}
+
+#[test]
+fn toml_example_loads_back() -> Result<(), ConfigError> {
+    let example = AppConfig::example_config(FileFormat::Toml)?;
+    let config = AppConfig::load_from(&parse_toml(&example)?)?;
+
+    assert_eq!(config.name, "<name>");
+    assert_eq!(config.port, 8080);
+    assert_eq!(config.database.pool, 10);
+    assert!(config.motd.is_none());
+    Ok(())
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn yaml_example_nests_sections() -> Result<(), ConfigError> {
+    let example = AppConfig::example_config(FileFormat::Yaml)?;
+
+    assert!(example.contains("tags:\n  - api\n"));
+    assert!(example.contains("database:\n  # Pool size\n  pool: 10\n"));
+
+    let config = AppConfig::load_from(&forgeconf::parse_yaml(&example)?)?;
+    assert_eq!(config.database.url, "<url>");
+    Ok(())
+}
@@ -0,0 +1,98 @@
+#![cfg(all(feature = "parse", feature = "toml"))]
+
+use std::fmt::Debug;
+
+use forgeconf::{ConfigError, ConfigNode, FromNode, forgeconf};
+
+#[forgeconf]
+struct Pool<T: Debug> {
+    inner: T,
+    #[field(default = 4)]
+    size: u32,
+}
+
+#[forgeconf(to_node)]
+struct Limits<K, V>
+where
+    V: Clone,
+{
+    #[field(optional = true)]
+    soft: Option<K>,
+    hard: Vec<V>,
+}
+
+#[forgeconf]
+struct AppConfig {
+    #[field(nested)]
+    pool: Pool<String>,
+    #[field(nested)]
+    limits: Limits<u16, u64>,
+}
+
+fn load<T: FromNode>(input: &str) -> Result<T, ConfigError> {
+    T::from_node(&forgeconf::parse_toml(input)?, "root")
+}
+
+#[test]
+fn generic_structs_load_through_from_node() -> Result<(), ConfigError> {
+    let pool: Pool<u16> = load("inner = 8080\n")?;
+    assert_eq!(pool.inner, 8080);
+    assert_eq!(pool.size, 4);
+
+    let pool = Pool::<String>::parse_toml("inner = \"db\"\nsize = 8\n")?;
+    assert_eq!(pool.inner, "db");
+    assert_eq!(pool.size, 8);
+    Ok(())
+}
+
+#[test]
+fn concrete_instantiations_nest_and_round_trip() -> Result<(), ConfigError> {
+    let input = "[pool]\ninner = \"db\"\n\n[limits]\nsoft = 10\nhard = [1, 2]\n";
+    let cfg = AppConfig::parse_toml(input)?;
+
+    assert_eq!(cfg.pool.inner, "db");
+    assert_eq!(cfg.limits.soft, Some(10));
+    assert_eq!(cfg.limits.hard, [1, 2]);
+    assert_eq!(cfg.limits.to_node().get_path("hard[1]"), Some(&ConfigNode::Integer(2)));
+    Ok(())
+}
+
+#[test]
+fn type_errors_name_the_generic_field() {
+    let err = Pool::<u16>::parse_toml("inner = \"abc\"\n")
+        .map(|pool| pool.inner)
+        .unwrap_err();
+
+    assert!(matches!(err, ConfigError::TypeMismatch { field, .. } if field == "inner"));
+}
+
+#[test]
+fn generic_loaders_use_the_same_sources() -> Result<(), ConfigError> {
+    let pool = Pool::<bool>::loader()
+        .add_source(forgeconf::EnvSource::new("POOL_").with_vars([("POOL_INNER", "true")]))
+        .load_explained()?;
+
+    assert!(pool.inner);
+    assert!(pool.explain().contains("size = 4  # default"));
+    Ok(())
+}
+
+#[forgeconf]
+struct Named<S, F> {
+    source: S,
+    fallback: F,
+}
+
+#[test]
+fn type_parameters_may_share_names_with_loader_internals() -> Result<(), ConfigError> {
+    let named = Named::<String, u8>::loader()
+        .add_source(
+            forgeconf::EnvSource::new("NAMED_")
+                .with_vars([("NAMED_SOURCE", "env"), ("NAMED_FALLBACK", "1")]),
+        )
+        .load()?;
+
+    assert_eq!(named.source, "env");
+    assert_eq!(named.fallback, 1);
+    Ok(())
+}
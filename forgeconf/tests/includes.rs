@@ -0,0 +1,95 @@
+#![cfg(all(feature = "toml", feature = "yaml"))]
+
+use forgeconf::{ConfigDir, ConfigError, ConfigFile, ConfigSource, EnvSource, forgeconf};
+
+#[forgeconf]
+struct Http {
+    host: String,
+    port: u16,
+}
+
+#[forgeconf]
+struct Database {
+    host: String,
+    pool: u32,
+}
+
+#[forgeconf(config(path = "tests/fixtures/includes/app.toml", includes = true))]
+struct AppConfig {
+    name: String,
+    #[field(nested)]
+    http: Http,
+    #[field(nested)]
+    database: Database,
+}
+
+#[forgeconf]
+struct Fragments {
+    #[field(nested)]
+    http: Http,
+    #[field(nested)]
+    database: Database,
+}
+
+#[forgeconf]
+struct Patterns {
+    name: String,
+    include: String,
+}
+
+#[test]
+fn include_is_an_ordinary_key_by_default() -> Result<(), ConfigError> {
+    let cfg = Patterns::loader()
+        .add_source(ConfigFile::new("tests/fixtures/includes/app.toml"))
+        .load()?;
+
+    assert_eq!(cfg.name, "billing");
+    assert_eq!(cfg.include, "conf.d/*");
+    Ok(())
+}
+
+#[test]
+fn included_fragments_override_the_including_file() -> Result<(), ConfigError> {
+    let cfg = AppConfig::loader().load()?;
+
+    assert_eq!(cfg.name, "billing");
+    assert_eq!(cfg.http.host, "127.0.0.1");
+    assert_eq!(cfg.http.port, 9000);
+    assert_eq!(cfg.database.host, "db.internal");
+    assert_eq!(cfg.database.pool, 8);
+    Ok(())
+}
+
+#[test]
+fn provenance_names_the_fragment() -> Result<(), ConfigError> {
+    let cfg = AppConfig::loader().load_explained()?;
+
+    let origin = cfg.sources().get("database.pool").unwrap();
+    assert!(origin.source.ends_with("10-database.yaml"));
+    assert_eq!(origin.location, Some((3, 9)));
+    Ok(())
+}
+
+#[test]
+fn config_dir_loads_every_file_in_order() -> Result<(), ConfigError> {
+    let source = ConfigDir::new("tests/fixtures/includes/conf.d");
+    let node = source.load()?;
+
+    assert!(node.get_path("http.host").is_none());
+    assert_eq!(node.get_path("http.port").unwrap().to_string(), "9000");
+    assert_eq!(source.watched_paths().len(), 3);
+    Ok(())
+}
+
+#[test]
+fn config_dir_layers_with_other_sources() -> Result<(), ConfigError> {
+    let cfg = Fragments::loader()
+        .add_source(ConfigDir::new("tests/fixtures/includes/conf.d"))
+        .add_source(EnvSource::new("APP_").with_vars([("APP_HTTP__HOST", "0.0.0.0")]))
+        .load()?;
+
+    assert_eq!(cfg.http.host, "0.0.0.0");
+    assert_eq!(cfg.http.port, 9000);
+    assert_eq!(cfg.database.pool, 8);
+    Ok(())
+}
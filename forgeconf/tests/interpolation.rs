@@ -0,0 +1,75 @@
+#![cfg(feature = "toml")]
+
+use forgeconf::{ConfigError, ConfigFile, forgeconf};
+
+#[forgeconf]
+struct Database {
+    port: u16,
+    url: String,
+}
+
+#[forgeconf(config(path = "tests/fixtures/interpolated.toml"))]
+struct AppConfig {
+    data_dir: String,
+    #[field(nested)]
+    database: Database,
+}
+
+#[test]
+fn references_resolve_against_keys_and_fallbacks() -> Result<(), ConfigError> {
+    let cfg = AppConfig::loader()
+        .interpolate()
+        .env_lookup(|_| None)
+        .load()?;
+
+    assert_eq!(cfg.database.url, "postgres://db.internal:5432/billing");
+    assert_eq!(cfg.database.port, 5432);
+    assert_eq!(cfg.data_dir, "/var/lib/billing");
+    Ok(())
+}
+
+#[test]
+fn references_read_the_injected_environment() -> Result<(), ConfigError> {
+    let cfg = AppConfig::loader()
+        .interpolate()
+        .env_lookup(|name| (name == "FORGECONF_TEST_DATA_DIR").then(|| "/srv".to_string()))
+        .load()?;
+
+    assert_eq!(cfg.data_dir, "/srv/billing");
+    Ok(())
+}
+
+#[test]
+fn values_are_literal_by_default() -> Result<(), ConfigError> {
+    let cfg = AppConfig::loader().load()?;
+
+    assert_eq!(cfg.database.url, "postgres://${database.host}:${database.port}/${name}");
+    Ok(())
+}
+
+#[derive(Debug)]
+#[forgeconf]
+#[allow(dead_code)]
+struct Unresolved {
+    url: String,
+}
+
+#[test]
+fn unresolved_references_point_at_the_value() {
+    let err = Unresolved::loader()
+        .add_source(ConfigFile::new("tests/fixtures/unresolved.toml"))
+        .interpolate()
+        .env_lookup(|_| None)
+        .load()
+        .unwrap_err();
+
+    let ConfigError::Interpolation { field, span: Some(span), .. } = err else {
+        panic!("expected a located interpolation error, got {err:?}");
+    };
+    assert_eq!(field, "url");
+    let contents = std::fs::read_to_string("tests/fixtures/unresolved.toml").unwrap();
+    assert_eq!(
+        &contents[span.offset()..span.offset() + span.len()],
+        r#""postgres://${DB_HOST_FORGECONF_UNSET}/app""#
+    );
+}
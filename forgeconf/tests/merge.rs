@@ -0,0 +1,95 @@
+#![cfg(feature = "toml")]
+
+use forgeconf::{ConfigError, ConfigFile, MergeStrategy, forgeconf};
+
+#[forgeconf]
+struct Listener {
+    name: String,
+    port: u16,
+}
+
+#[forgeconf]
+struct Server {
+    #[field(merge = "merge_by_key(name)")]
+    listeners: Vec<Listener>,
+}
+
+#[forgeconf(
+    config(path = "tests/fixtures/merge-base.toml", priority = 5),
+    config(path = "tests/fixtures/merge-overlay.toml", priority = 10)
+)]
+struct AppConfig {
+    #[field(merge = "append")]
+    plugins: Vec<String>,
+    hosts: Vec<String>,
+    #[field(nested)]
+    server: Server,
+}
+
+#[test]
+fn field_strategies_are_applied() -> Result<(), ConfigError> {
+    let cfg = AppConfig::loader().load()?;
+
+    assert_eq!(cfg.plugins, ["auth", "metrics"]);
+    assert_eq!(cfg.hosts, ["b.internal"]);
+    let listeners: Vec<_> = cfg
+        .server
+        .listeners
+        .iter()
+        .map(|listener| (listener.name.as_str(), listener.port))
+        .collect();
+    assert_eq!(listeners, [("http", 8080), ("https", 443), ("admin", 9000)]);
+    Ok(())
+}
+
+#[forgeconf]
+#[allow(dead_code)]
+struct Pool {
+    #[field(merge = "append")]
+    hosts: Vec<String>,
+}
+
+#[forgeconf]
+#[allow(dead_code)]
+struct PoolsConfig {
+    #[field(nested)]
+    pools: Vec<Pool>,
+    #[field(nested)]
+    spare: Option<Pool>,
+}
+
+#[test]
+fn strategies_of_optional_and_listed_sections_are_prefixed() {
+    assert_eq!(
+        PoolsConfig::merge_strategies(),
+        [
+            ("pools.hosts".to_string(), MergeStrategy::Append),
+            ("spare.hosts".to_string(), MergeStrategy::Append),
+        ]
+    );
+}
+
+#[test]
+fn nested_strategies_are_prefixed() {
+    let strategies = AppConfig::merge_strategies();
+
+    assert_eq!(
+        strategies,
+        [
+            ("plugins".to_string(), MergeStrategy::Append),
+            ("server.listeners".to_string(), MergeStrategy::MergeByKey("name".into())),
+        ]
+    );
+}
+
+#[test]
+fn loader_strategies_extend_declared_ones() -> Result<(), ConfigError> {
+    let cfg = AppConfig::loader()
+        .merge_strategy("hosts", MergeStrategy::Prepend)
+        .add_source(ConfigFile::new("tests/fixtures/merge-overlay.toml").with_priority(20))
+        .load()?;
+
+    assert_eq!(cfg.hosts, ["b.internal", "b.internal", "a.internal"]);
+    assert_eq!(cfg.plugins, ["auth", "metrics", "metrics"]);
+    Ok(())
+}
@@ -30,3 +30,45 @@ fn nested_structs_load_from_sections() -> Result<(), ConfigError> {
     assert_eq!(cfg.database.pool, 16);
     Ok(())
 }
+
+#[cfg(all(feature = "parse", feature = "toml"))]
+mod maps {
+    use std::collections::{BTreeMap, HashMap};
+
+    use super::*;
+
+    #[forgeconf]
+    struct Upstream {
+        host: String,
+        port: u16,
+    }
+
+    #[forgeconf]
+    struct GatewayConfig {
+        labels: BTreeMap<String, String>,
+        upstreams: HashMap<String, Upstream>,
+    }
+
+    #[test]
+    fn dynamic_sections_load_into_maps() -> Result<(), ConfigError> {
+        let cfg = GatewayConfig::parse_toml(
+            r#"
+            labels = { team = "edge", tier = "gold" }
+
+            [upstreams.api]
+            host = "10.0.0.1"
+            port = 8080
+
+            [upstreams.web]
+            host = "10.0.0.2"
+            port = 8081
+            "#,
+        )?;
+
+        assert_eq!(cfg.labels["team"], "edge");
+        assert_eq!(cfg.upstreams.len(), 2);
+        assert_eq!(cfg.upstreams["api"].host, "10.0.0.1");
+        assert_eq!(cfg.upstreams["web"].port, 8081);
+        Ok(())
+    }
+}
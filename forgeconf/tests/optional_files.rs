@@ -0,0 +1,33 @@
+use forgeconf::{ConfigError, forgeconf};
+
+#[forgeconf(
+    config(path = "tests/fixtures/basic.toml"),
+    config(path = "tests/fixtures/does-not-exist.toml", optional = true, priority = 50)
+)]
+struct LayeredConfig {
+    port: u16,
+}
+
+#[forgeconf(config(path = "tests/fixtures/does-not-exist.toml"))]
+struct RequiredConfig {
+    #[field(default = 1)]
+    port: u16,
+}
+
+#[test]
+fn missing_optional_files_are_skipped() -> Result<(), ConfigError> {
+    let cfg = LayeredConfig::loader().load()?;
+
+    assert_eq!(cfg.port, 3000);
+    Ok(())
+}
+
+#[test]
+fn missing_required_files_still_fail() {
+    let err = RequiredConfig::loader()
+        .load()
+        .map(|cfg| cfg.port)
+        .unwrap_err();
+
+    assert!(matches!(err, ConfigError::Io(_)));
+}
@@ -0,0 +1,144 @@
+#![cfg(feature = "toml")]
+
+use std::path::{Path, PathBuf};
+
+use forgeconf::{ConfigError, ConfigFile, ConfigNode, FromNode, PathOptions, forgeconf};
+
+#[forgeconf]
+struct Tls {
+    #[field(relative_to_config)]
+    cert: PathBuf,
+    key: PathBuf,
+}
+
+#[forgeconf(config(path = "tests/fixtures/paths/app.toml"))]
+struct AppConfig {
+    #[field(relative_to_config)]
+    data_dir: PathBuf,
+    #[field(relative_to_config, expand_path)]
+    cache_dir: PathBuf,
+    #[field(relative_to_config)]
+    plugins: Vec<PathBuf>,
+    #[field(nested)]
+    tls: Tls,
+}
+
+fn fixtures() -> &'static Path {
+    Path::new("tests/fixtures/paths")
+}
+
+#[test]
+fn relative_paths_resolve_against_the_config_file() -> Result<(), ConfigError> {
+    let cfg = AppConfig::loader().load()?;
+
+    assert_eq!(cfg.tls.cert, fixtures().join("certs/server.pem"));
+    assert_eq!(cfg.plugins, [fixtures().join("plugins/auth.so"), "/opt/plugins/log.so".into()]);
+    assert_eq!(cfg.data_dir, PathBuf::from("/var/lib/app"));
+    Ok(())
+}
+
+#[test]
+fn unmarked_paths_stay_as_written() -> Result<(), ConfigError> {
+    let cfg = AppConfig::loader().load()?;
+    assert_eq!(cfg.tls.key, PathBuf::from("certs/server.key"));
+    Ok(())
+}
+
+#[test]
+fn expand_path_replaces_the_home_directory() -> Result<(), ConfigError> {
+    let Ok(home) = std::env::var("HOME") else {
+        return Ok(());
+    };
+    let cfg = AppConfig::loader().load()?;
+    assert_eq!(cfg.cache_dir, Path::new(&home).join("cache"));
+    Ok(())
+}
+
+#[test]
+fn path_options_cover_nested_sections() {
+    let options = AppConfig::path_options();
+    assert!(options.contains(&(
+        "tls.cert".to_string(),
+        PathOptions { relative_to_config: true, expand: false }
+    )));
+    assert!(!options.iter().any(|(path, _)| path == "tls.key"));
+}
+
+#[test]
+fn builder_resolves_registered_paths() -> Result<(), ConfigError> {
+    let node = forgeconf::ConfigBuilder::new()
+        .add_source(ConfigFile::new(fixtures().join("app.toml")))
+        .resolve_path("tls.key", PathOptions { relative_to_config: true, expand: false })
+        .load()?;
+
+    let key = fixtures().join("certs/server.key");
+    assert_eq!(node.get_path("tls.key").unwrap().to_string(), key.display().to_string());
+    Ok(())
+}
+
+/// A section with a hand-written `FromNode` that declares its path field.
+struct Bundle {
+    ca: PathBuf,
+}
+
+impl FromNode for Bundle {
+    fn from_node(node: &ConfigNode, key: &str) -> Result<Self, ConfigError> {
+        let ca = node
+            .get_path("ca")
+            .ok_or_else(|| ConfigError::missing(format!("{key}.ca")))?;
+        Ok(Bundle { ca: PathBuf::from_node(ca, key)? })
+    }
+
+    fn path_options() -> Vec<(String, PathOptions)> {
+        vec![("ca".into(), PathOptions { relative_to_config: true, expand: false })]
+    }
+}
+
+#[forgeconf(config(path = "tests/fixtures/paths/proxy.toml"))]
+struct ProxyConfig {
+    #[field(nested)]
+    upstreams: Vec<Tls>,
+    #[field(nested)]
+    fallback: Option<Tls>,
+    #[field(nested)]
+    bundle: Bundle,
+}
+
+#[test]
+fn paths_resolve_inside_lists_options_and_custom_sections() -> Result<(), ConfigError> {
+    let cfg = ProxyConfig::loader().load()?;
+
+    let certs: Vec<_> = cfg.upstreams.iter().map(|tls| tls.cert.clone()).collect();
+    assert_eq!(certs, [fixtures().join("certs/a.pem"), "/etc/ssl/b.pem".into()]);
+    assert_eq!(cfg.fallback.unwrap().cert, fixtures().join("certs/fallback.pem"));
+    assert_eq!(cfg.bundle.ca, fixtures().join("certs/ca.pem"));
+    Ok(())
+}
+
+#[forgeconf]
+struct Plugins {
+    #[field(relative_to_config, merge = "append")]
+    plugins: Vec<PathBuf>,
+    #[field(nested, merge = "append")]
+    upstreams: Vec<Tls>,
+}
+
+#[test]
+fn appended_items_resolve_against_their_own_file() -> Result<(), ConfigError> {
+    let (base, extra) = (tempfile::tempdir()?, tempfile::tempdir()?);
+    let item = |dir: &str| {
+        format!("plugins = [\"{dir}.so\"]\n[[upstreams]]\ncert = \"{dir}.pem\"\nkey = \"k\"\n")
+    };
+    std::fs::write(base.path().join("app.toml"), item("base"))?;
+    std::fs::write(extra.path().join("extra.toml"), item("extra"))?;
+
+    let cfg = Plugins::loader()
+        .add_source(ConfigFile::new(base.path().join("app.toml")))
+        .add_source(ConfigFile::new(extra.path().join("extra.toml")).with_priority(20))
+        .load()?;
+
+    assert_eq!(cfg.plugins, [base.path().join("base.so"), extra.path().join("extra.so")]);
+    let certs: Vec<_> = cfg.upstreams.iter().map(|tls| tls.cert.clone()).collect();
+    assert_eq!(certs, [base.path().join("base.pem"), extra.path().join("extra.pem")]);
+    Ok(())
+}
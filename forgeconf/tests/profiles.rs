@@ -0,0 +1,71 @@
+#![cfg(feature = "toml")]
+
+use forgeconf::{CliArguments, ConfigError, EnvSource, forgeconf};
+
+#[forgeconf(config(path = "tests/fixtures/profiles.toml"))]
+struct AppConfig {
+    host: String,
+    port: u16,
+    debug: bool,
+}
+
+#[test]
+fn selected_profile_overrides_the_default() -> Result<(), ConfigError> {
+    let cfg = AppConfig::loader().profile("prod").load()?;
+
+    assert_eq!(cfg.host, "0.0.0.0");
+    assert_eq!(cfg.port, 80);
+    assert!(!cfg.debug);
+    Ok(())
+}
+
+#[test]
+fn unset_profile_env_uses_the_default() -> Result<(), ConfigError> {
+    let cfg = AppConfig::loader()
+        .profile_env("FORGECONF_TEST_UNSET_PROFILE")
+        .load()?;
+
+    assert_eq!(cfg.port, 8080);
+    assert!(!cfg.debug);
+    Ok(())
+}
+
+#[test]
+fn cli_flag_selects_the_profile() -> Result<(), ConfigError> {
+    let cfg = AppConfig::loader()
+        .profile_env("FORGECONF_TEST_UNSET_PROFILE")
+        .add_source(CliArguments::new().with_args(["--profile=dev"]))
+        .load()?;
+
+    assert!(cfg.debug);
+    assert_eq!(cfg.port, 8080);
+    Ok(())
+}
+
+#[test]
+fn higher_priority_sources_still_beat_the_profile() -> Result<(), ConfigError> {
+    let cfg = AppConfig::loader()
+        .profile("prod")
+        .add_source(EnvSource::new("APP_").with_vars([("APP_PORT", "8443")]))
+        .load_explained()?;
+
+    assert_eq!(cfg.port, 8443);
+    assert_eq!(cfg.host, "0.0.0.0");
+    let origin = cfg.sources().get("host").unwrap();
+    assert_eq!(origin.location, Some((10, 8)));
+    Ok(())
+}
+
+#[test]
+fn unknown_profiles_are_rejected() {
+    let err = AppConfig::loader()
+        .profile("prdo")
+        .load()
+        .map(|cfg| cfg.port)
+        .unwrap_err();
+
+    assert!(matches!(
+        err,
+        ConfigError::UnknownProfile { profile, known, .. } if profile == "prdo" && known == ["dev", "prod"]
+    ));
+}
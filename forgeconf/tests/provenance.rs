@@ -0,0 +1,103 @@
+#![cfg(feature = "toml")]
+
+use forgeconf::{ConfigError, EnvSource, forgeconf};
+
+#[forgeconf]
+#[allow(dead_code)]
+struct Http {
+    host: String,
+    port: u16,
+}
+
+#[forgeconf]
+#[allow(dead_code)]
+struct Database {
+    url: String,
+    #[field(default = 4)]
+    pool: u32,
+    #[field(default = 30)]
+    timeout: u32,
+}
+
+#[forgeconf(config(path = "tests/fixtures/nested.toml"))]
+#[allow(dead_code)]
+struct ServiceConfig {
+    #[field(nested)]
+    http: Http,
+    #[field(nested)]
+    database: Database,
+    #[field(default = "info".to_string())]
+    log_level: String,
+}
+
+#[test]
+fn load_explained_reports_the_source_of_each_value() -> Result<(), ConfigError> {
+    let cfg = ServiceConfig::loader()
+        .add_source(EnvSource::new("APP_").with_vars([("APP_HTTP__PORT", "9000")]))
+        .load_explained()?;
+
+    assert_eq!(cfg.http.port, 9000);
+
+    let sources = cfg.sources();
+    let host = sources.get("http.host").unwrap();
+    assert_eq!(host.source, "tests/fixtures/nested.toml");
+    assert_eq!(host.location, Some((2, 8)));
+    assert_eq!(host.priority, Some(10));
+
+    let port = sources.get("http.port").unwrap();
+    assert_eq!(port.source, "env APP_*");
+    assert_eq!(port.location, None);
+
+    assert_eq!(sources.get("database.pool").unwrap().value.as_deref(), Some("16"));
+    assert_eq!(sources.get("database.timeout").unwrap().to_string(), "default");
+    assert_eq!(sources.get("log_level").unwrap().value.as_deref(), Some("info"));
+    Ok(())
+}
+
+#[test]
+fn explain_renders_one_line_per_value() -> Result<(), ConfigError> {
+    let cfg = ServiceConfig::loader().load_explained()?;
+    let report = cfg.explain();
+
+    assert_eq!(report.lines().count(), 6);
+    assert!(report.contains("http.port = 8080  # tests/fixtures/nested.toml:3:8 (priority 10)"));
+    assert!(report.contains("database.timeout = 30  # default"));
+    Ok(())
+}
+
+#[forgeconf]
+#[allow(dead_code)]
+struct Store {
+    #[field(env = "FORGECONF_PROVENANCE_STORE_URL")]
+    url: String,
+    #[field(default = 8)]
+    pool_max: u32,
+}
+
+#[forgeconf(config(path = "tests/fixtures/nested.toml"))]
+#[allow(dead_code)]
+struct StoreConfig {
+    #[field(nested, name = "database")]
+    store: Store,
+}
+
+#[test]
+fn load_explained_records_the_override_that_was_used() -> Result<(), ConfigError> {
+    unsafe {
+        std::env::set_var("FORGECONF_PROVENANCE_STORE_URL", "postgres://env");
+    }
+    let cfg = StoreConfig::loader().load_explained();
+    unsafe {
+        std::env::remove_var("FORGECONF_PROVENANCE_STORE_URL");
+    }
+    let cfg = cfg?;
+
+    assert_eq!(cfg.store.url, "postgres://env");
+    let sources = cfg.sources();
+    let url = sources.get("database.url").unwrap();
+    assert_eq!(url.source, "env FORGECONF_PROVENANCE_STORE_URL");
+    assert_eq!(url.value.as_deref(), Some("postgres://env"));
+    assert_eq!(sources.get("database.pool_max").unwrap().value.as_deref(), Some("8"));
+    assert_eq!(sources.len(), 2);
+    Ok(())
+}
@@ -0,0 +1,61 @@
+#![cfg(feature = "toml")]
+
+use std::fs;
+use std::time::Duration;
+
+use forgeconf::{ConfigError, ConfigFile, ReloadEvent, ReloadHandle, forgeconf};
+use tempfile::NamedTempFile;
+
+#[forgeconf]
+struct ServerConfig {
+    #[field(validate = non_zero)]
+    workers: u16,
+    #[field(default = "info".to_string())]
+    log_level: String,
+}
+
+fn non_zero(value: &u16, key: &str) -> Result<(), ConfigError> {
+    if *value > 0 { Ok(()) } else { Err(ConfigError::mismatch(key, "at least 1", "0")) }
+}
+
+/// A `.toml` file holding `contents`, removed when dropped.
+fn config_file(contents: &str) -> NamedTempFile {
+    let file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    fs::write(file.path(), contents).unwrap();
+    file
+}
+
+#[test]
+fn watch_publishes_reloaded_config() -> Result<(), ConfigError> {
+    let file = config_file("workers = 4\n");
+    let path = file.path();
+
+    let loader = ServerConfig::loader().add_source(ConfigFile::new(path));
+    let handle = ReloadHandle::watch(loader, Duration::from_millis(10))?;
+    let events = handle.subscribe();
+    assert_eq!(handle.current().workers, 4);
+
+    fs::write(path, "workers = 16\nlog_level = \"debug\"\n")?;
+    let event = events.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(matches!(event, ReloadEvent::Reloaded(_)));
+    assert_eq!(handle.current().workers, 16);
+    assert_eq!(handle.current().log_level, "debug");
+    Ok(())
+}
+
+#[test]
+fn invalid_reload_keeps_previous_config() -> Result<(), ConfigError> {
+    let file = config_file("workers = 4\n");
+    let path = file.path();
+
+    let loader = ServerConfig::loader().add_source(ConfigFile::new(path));
+    let handle = ReloadHandle::watch(loader, Duration::from_secs(3600))?;
+
+    fs::write(path, "workers = 0\n")?;
+    let ReloadEvent::Failed(err) = handle.reload() else {
+        panic!("expected the reload to fail");
+    };
+    assert!(matches!(&*err, ConfigError::TypeMismatch { field, .. } if field == "workers"));
+    assert_eq!(handle.current().workers, 4);
+    Ok(())
+}
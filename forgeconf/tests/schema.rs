@@ -0,0 +1,148 @@
+#![cfg(all(feature = "parse", feature = "toml"))]
+
+use std::collections::BTreeMap;
+
+use forgeconf::{ConfigNode, FromNode, Secret, forgeconf, validators};
+
+#[derive(Debug)]
+#[forgeconf]
+#[allow(dead_code)]
+struct Bucket {
+    bucket: String,
+}
+
+#[derive(Debug)]
+#[forgeconf(tag = "kind")]
+#[allow(dead_code)]
+enum Storage {
+    Memory,
+    S3(Bucket),
+}
+
+#[derive(Debug)]
+#[forgeconf]
+enum Level {
+    Debug,
+    #[variant(name = "warning")]
+    Warn,
+}
+
+#[allow(dead_code)]
+#[derive(Debug)]
+#[forgeconf]
+struct Http {
+    #[field(name = "listen_port", default = 8080, validate = validators::range(1024, 65535))]
+    port: u16,
+    #[field(help = "Address to bind", validate = validators::non_empty())]
+    host: String,
+}
+
+#[allow(dead_code)]
+#[derive(Debug)]
+#[forgeconf(deny_unknown_fields)]
+struct AppConfig {
+    #[field(nested)]
+    http: Http,
+    storage: Storage,
+    level: Level,
+    #[field(validate = validators::one_of(["blue".to_string(), "green".to_string()]))]
+    color: String,
+    tags: BTreeMap<String, Vec<String>>,
+    #[field(optional = true)]
+    motd: Option<String>,
+    #[field(env = "APP_TOKEN")]
+    token: Secret<String>,
+}
+
+fn text(value: &str) -> ConfigNode {
+    ConfigNode::String(value.to_string())
+}
+
+#[test]
+fn document_describes_fields_and_nested_sections() {
+    let schema = AppConfig::json_schema();
+
+    assert_eq!(schema.get_path("$schema"), Some(&text(forgeconf::schema::DIALECT)));
+    assert_eq!(schema.get_path("title"), Some(&text("AppConfig")));
+    assert_eq!(schema.get_path("additionalProperties"), Some(&ConfigNode::Boolean(false)));
+    assert_eq!(
+        schema.get_path("required"),
+        Some(&ConfigNode::Array(vec![text("storage"), text("level"), text("color"), text("tags")]))
+    );
+
+    assert_eq!(schema.get_path("properties.http.type"), Some(&text("object")));
+    assert_eq!(
+        schema.get_path("properties.http.required"),
+        Some(&ConfigNode::Array(vec![text("host")]))
+    );
+    assert_eq!(
+        schema.get_path("properties.tags.additionalProperties.items.type"),
+        Some(&text("string"))
+    );
+    assert_eq!(schema.get_path("properties.motd.anyOf[1].type"), Some(&text("null")));
+    assert_eq!(schema.get_path("properties.token.writeOnly"), Some(&ConfigNode::Boolean(true)));
+}
+
+#[test]
+fn options_of_any_value_accept_null_once() {
+    let schema = <Option<ConfigNode> as FromNode>::json_schema();
+
+    assert_eq!(schema.get_path("oneOf"), None);
+    assert_eq!(
+        schema.get_path("anyOf"),
+        Some(&ConfigNode::Array(vec![
+            ConfigNode::empty_table(),
+            forgeconf::schema::of_type("null")
+        ]))
+    );
+}
+
+#[test]
+fn field_options_and_validators_map_to_keywords() {
+    let schema = AppConfig::json_schema();
+
+    let port = schema
+        .get_path("properties.http.properties.listen_port")
+        .unwrap();
+    assert_eq!(port.get_path("type"), Some(&text("integer")));
+    assert_eq!(port.get_path("minimum"), Some(&ConfigNode::Integer(1024)));
+    assert_eq!(port.get_path("maximum"), Some(&ConfigNode::Integer(65535)));
+    assert_eq!(port.get_path("default"), Some(&ConfigNode::Integer(8080)));
+
+    let host = schema.get_path("properties.http.properties.host").unwrap();
+    assert_eq!(host.get_path("description"), Some(&text("Address to bind")));
+    assert_eq!(host.get_path("minLength"), Some(&ConfigNode::Integer(1)));
+
+    assert_eq!(
+        schema.get_path("properties.color.enum"),
+        Some(&ConfigNode::Array(vec![text("blue"), text("green")]))
+    );
+}
+
+#[test]
+fn enums_list_their_accepted_forms() {
+    let schema = AppConfig::json_schema();
+
+    assert_eq!(
+        schema.get_path("properties.level.enum"),
+        Some(&ConfigNode::Array(vec![text("debug"), text("warning")]))
+    );
+
+    let storage = schema.get_path("properties.storage.oneOf").unwrap();
+    assert_eq!(storage.get_path("[0].enum[0]"), Some(&text("memory")));
+    assert_eq!(storage.get_path("[2].properties.kind.const"), Some(&text("s3")));
+    assert_eq!(
+        storage.get_path("[2].required"),
+        Some(&ConfigNode::Array(vec![text("kind"), text("bucket")]))
+    );
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn schema_renders_as_json() {
+    let rendered = AppConfig::json_schema()
+        .to_string_as(forgeconf::FileFormat::Json)
+        .unwrap();
+    assert!(rendered.contains("\"$schema\""));
+    assert!(rendered.contains("\"listen_port\""));
+}
@@ -0,0 +1,86 @@
+#![cfg(all(feature = "parse", feature = "toml"))]
+
+use forgeconf::{ConfigError, EnvSource, FileFormat, Secret, forgeconf};
+
+#[derive(Debug)]
+#[forgeconf(to_node)]
+struct Database {
+    url: String,
+    password: Secret<String>,
+    #[field(secret)]
+    pin: Secret<u32>,
+}
+
+#[derive(Debug)]
+#[forgeconf(to_node)]
+struct AppConfig {
+    #[field(nested)]
+    database: Database,
+    #[field(optional = true)]
+    api_token: Option<Secret<String>>,
+}
+
+const INPUT: &str = r#"
+api_token = "tok-123"
+
+[database]
+url = "postgres://db"
+password = "hunter2"
+pin = 4321
+"#;
+
+#[test]
+fn debug_output_hides_secret_values() -> Result<(), ConfigError> {
+    let cfg = AppConfig::parse_toml(INPUT)?;
+    let printed = format!("{cfg:?}");
+
+    assert!(printed.contains("postgres://db"));
+    assert!(!printed.contains("hunter2"));
+    assert!(!printed.contains("tok-123"));
+    assert!(!printed.contains("4321"));
+    assert_eq!(cfg.database.password.expose(), "hunter2");
+    assert_eq!(*cfg.database.pin.expose(), 4321);
+    Ok(())
+}
+
+#[test]
+fn dumps_redact_secret_fields() -> Result<(), ConfigError> {
+    let text = AppConfig::parse_toml(INPUT)?
+        .to_node()
+        .to_string_as(FileFormat::Toml)?;
+
+    assert!(text.contains(r#"url = "postgres://db""#));
+    assert!(text.contains(r#"password = "[redacted]""#));
+    assert!(text.contains(r#"pin = "[redacted]""#));
+    assert!(text.contains(r#"api_token = "[redacted]""#));
+    Ok(())
+}
+
+#[test]
+fn errors_do_not_echo_secret_values() {
+    let err = Database::parse_toml("url = \"x\"\npassword = \"p\"\npin = \"12ab\"\n").unwrap_err();
+
+    assert!(matches!(
+        err,
+        ConfigError::TypeMismatch { field, found, span: None, .. }
+            if field == "pin" && found == "[redacted]"
+    ));
+}
+
+#[test]
+fn provenance_reports_redact_secret_values() -> Result<(), ConfigError> {
+    let cfg = AppConfig::loader()
+        .add_source(EnvSource::new("APP_").with_vars([
+            ("APP_DATABASE__URL", "postgres://db"),
+            ("APP_DATABASE__PASSWORD", "hunter2"),
+            ("APP_DATABASE__PIN", "4321"),
+        ]))
+        .load_explained()?;
+
+    let report = cfg.explain();
+    assert!(report.contains("database.url = postgres://db"));
+    assert!(report.contains("database.password = [redacted]"));
+    assert!(report.contains("database.pin = [redacted]"));
+    assert!(!report.contains("hunter2"));
+    Ok(())
+}
@@ -0,0 +1,135 @@
+#![cfg(all(feature = "parse", feature = "toml"))]
+
+use std::collections::BTreeMap;
+
+use forgeconf::{ConfigError, ConfigNode, FileFormat, FromNode, forgeconf};
+
+#[derive(Debug, PartialEq)]
+#[forgeconf(to_node)]
+struct Bucket {
+    bucket: String,
+}
+
+#[derive(Debug, PartialEq)]
+#[forgeconf(tag = "kind", to_node)]
+enum Storage {
+    Memory,
+    Disk { path: String, max_mb: u32 },
+    S3(Bucket),
+}
+
+#[derive(Debug, PartialEq)]
+#[forgeconf(to_node)]
+enum Sink {
+    Stdout,
+    File { path: String },
+}
+
+#[derive(Debug, PartialEq)]
+#[forgeconf(to_node)]
+struct Http {
+    host: String,
+    #[field(name = "listen_port")]
+    port: u16,
+}
+
+#[derive(Debug, PartialEq)]
+#[forgeconf(to_node)]
+struct AppConfig {
+    #[field(nested)]
+    http: Http,
+    storage: Storage,
+    sinks: Vec<Sink>,
+    ratio: f64,
+    tags: BTreeMap<String, String>,
+    #[field(optional = true)]
+    motd: Option<String>,
+}
+
+fn sample() -> AppConfig {
+    AppConfig {
+        http: Http { host: "0.0.0.0".into(), port: 8080 },
+        storage: Storage::Disk { path: "/var/data".into(), max_mb: 512 },
+        sinks: vec![Sink::Stdout, Sink::File { path: "out.log".into() }],
+        ratio: 0.5,
+        tags: BTreeMap::from([("team".to_string(), "infra".to_string())]),
+        motd: None,
+    }
+}
+
+#[test]
+fn to_node_uses_field_keys_and_enum_tags() {
+    let node = sample().to_node();
+
+    assert_eq!(node.get_path("http.listen_port"), Some(&ConfigNode::Integer(8080)));
+    assert_eq!(node.get_path("storage.kind"), Some(&ConfigNode::String("disk".into())));
+    assert_eq!(node.get_path("sinks[0]"), Some(&ConfigNode::String("stdout".into())));
+    assert_eq!(node.get_path("sinks[1].file.path"), Some(&ConfigNode::String("out.log".into())));
+    assert_eq!(node.get_path("motd"), Some(&ConfigNode::Null));
+}
+
+#[test]
+fn toml_output_loads_back_into_the_same_struct() -> Result<(), ConfigError> {
+    let text = sample().to_node().to_string_as(FileFormat::Toml)?;
+
+    assert!(text.contains("[http]"));
+    assert!(!text.contains("motd"));
+    assert_eq!(AppConfig::parse_toml(&text)?, sample());
+    Ok(())
+}
+
+#[test]
+fn newtype_variants_merge_into_tagged_table() -> Result<(), ConfigError> {
+    let mut cfg = sample();
+    cfg.storage = Storage::S3(Bucket { bucket: "assets".into() });
+
+    let text = cfg.to_node().to_string_as(FileFormat::Toml)?;
+    assert_eq!(AppConfig::parse_toml(&text)?.storage, cfg.storage);
+    Ok(())
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn yaml_output_loads_back_into_the_same_struct() -> Result<(), ConfigError> {
+    let text = sample().to_node().to_string_as(FileFormat::Yaml)?;
+    assert_eq!(AppConfig::parse_yaml(&text)?, sample());
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn json_output_loads_back_into_the_same_struct() -> Result<(), ConfigError> {
+    let text = sample().to_node().to_string_as(FileFormat::Json)?;
+    assert_eq!(AppConfig::parse_json(&text)?, sample());
+    Ok(())
+}
+
+/// Loads through `FromNode` only; it has no `ToNode` impl.
+#[derive(Debug, PartialEq)]
+struct Region(String);
+
+impl FromNode for Region {
+    fn from_node(node: &ConfigNode, key: &str) -> Result<Self, ConfigError> {
+        String::from_node(node, key).map(Region)
+    }
+}
+
+#[derive(Debug)]
+#[forgeconf]
+struct Placement {
+    zone: Region,
+    #[field(default = Region("eu".into()))]
+    region: Region,
+}
+
+#[test]
+fn structs_without_to_node_accept_from_node_only_fields() -> Result<(), ConfigError> {
+    let cfg = Placement::parse_toml(r#"zone = "a""#)?;
+    assert_eq!(cfg.zone, Region("a".into()));
+    assert_eq!(cfg.region, Region("eu".into()));
+
+    let schema = Placement::json_schema();
+    assert!(schema.get_path("properties.region").is_some());
+    assert!(schema.get_path("properties.region.default").is_none());
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use forgeconf::{ConfigError, ConfigFile, forgeconf};
+
+#[derive(Debug)]
+#[forgeconf]
+#[allow(dead_code)]
+struct Server {
+    host: String,
+    port: u16,
+}
+
+#[derive(Debug)]
+#[forgeconf]
+#[allow(dead_code)]
+struct AppConfig {
+    name: String,
+    #[field(nested)]
+    server: Server,
+}
+
+/// Unwraps `Nested` layers down to the error that caused them.
+fn innermost(err: ConfigError) -> ConfigError {
+    match err {
+        ConfigError::Nested { source, .. } => {
+            let source: Box<dyn std::error::Error + Send + Sync> = source;
+            innermost(*source.downcast::<ConfigError>().unwrap())
+        },
+        other => other,
+    }
+}
+
+fn snippet(contents: &str, offset: usize, len: usize) -> &str {
+    &contents[offset..offset + len]
+}
+
+#[test]
+fn loader_points_mismatch_at_file_value() {
+    let err = AppConfig::loader()
+        .add_source(ConfigFile::new("tests/fixtures/bad-port.toml"))
+        .load()
+        .unwrap_err();
+
+    let contents = std::fs::read_to_string("tests/fixtures/bad-port.toml").unwrap();
+    match innermost(err) {
+        ConfigError::TypeMismatch {
+            field, span: Some(span), src: Some(src), ..
+        } => {
+            assert_eq!(field, "port");
+            assert_eq!(snippet(&contents, span.offset(), span.len()), "\"abc\"");
+            assert_eq!(src.name(), "tests/fixtures/bad-port.toml");
+        },
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[test]
+fn loader_leaves_values_from_other_sources_unlocated() {
+    let err = AppConfig::loader()
+        .add_source(ConfigFile::new("tests/fixtures/bad-port.toml"))
+        .add_source(forgeconf::EnvSource::new("APP_").with_vars([("APP_SERVER__PORT", "nope")]))
+        .load()
+        .unwrap_err();
+
+    assert!(matches!(innermost(err), ConfigError::TypeMismatch { span: None, src: None, .. }));
+}
+
+#[cfg(all(feature = "parse", feature = "toml"))]
+#[test]
+fn parse_toml_reports_parse_error_with_source() {
+    let err = AppConfig::parse_toml("name = \"api\"\n[server\n").unwrap_err();
+
+    assert!(matches!(err, ConfigError::Toml { span: Some(_), src: Some(_), .. }));
+}
+
+#[cfg(all(feature = "parse", feature = "yaml"))]
+#[test]
+fn parse_yaml_points_missing_value_at_section() {
+    let input = "name: api\nserver:\n  host: localhost\n";
+    let err = innermost(AppConfig::parse_yaml(input).unwrap_err());
+
+    match err {
+        ConfigError::MissingValue { field, span: Some(span), .. } => {
+            assert_eq!(field, "port");
+            assert_eq!(snippet(input, span.offset(), span.len()), "server");
+        },
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[cfg(all(feature = "parse", feature = "json"))]
+#[test]
+fn parse_json_points_mismatch_at_value() {
+    let input = r#"{"name": "api", "server": {"host": "h", "port": [1]}}"#;
+    let err = innermost(AppConfig::parse_json(input).unwrap_err());
+
+    match err {
+        ConfigError::TypeMismatch { span: Some(span), .. } => {
+            assert_eq!(snippet(input, span.offset(), span.len()), "[1]");
+        },
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
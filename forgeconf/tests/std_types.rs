@@ -0,0 +1,92 @@
+#![cfg(all(feature = "parse", feature = "toml"))]
+
+use std::collections::{BTreeSet, HashSet};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::num::NonZeroU32;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use forgeconf::{ConfigError, forgeconf};
+
+#[derive(Debug)]
+#[forgeconf(to_node)]
+struct Server {
+    listen: SocketAddr,
+    public_ip: IpAddr,
+    #[field(default = Ipv4Addr::LOCALHOST)]
+    admin_ip: Ipv4Addr,
+    data_dir: PathBuf,
+    workers: NonZeroU32,
+    roles: BTreeSet<String>,
+    #[field(default = HashSet::new())]
+    blocked: HashSet<u16>,
+    color: [u8; 3],
+    window: (u32, u32),
+    limit: Box<u64>,
+    name: Arc<String>,
+}
+
+const CONFIG: &str = r#"
+listen = "0.0.0.0:8080"
+public_ip = "::1"
+data_dir = "/var/lib/app"
+workers = 4
+roles = ["api", "admin"]
+color = [255, 128, 0]
+window = [1280, 720]
+limit = 10
+name = "app"
+"#;
+
+#[test]
+fn std_types_load_directly() -> Result<(), ConfigError> {
+    let cfg = Server::parse_toml(CONFIG)?;
+
+    assert_eq!(cfg.listen, "0.0.0.0:8080".parse::<SocketAddr>().unwrap());
+    assert!(cfg.public_ip.is_loopback());
+    assert_eq!(cfg.admin_ip, Ipv4Addr::LOCALHOST);
+    assert_eq!(cfg.data_dir, PathBuf::from("/var/lib/app"));
+    assert_eq!(cfg.workers.get(), 4);
+    assert_eq!(cfg.roles, BTreeSet::from(["admin".to_string(), "api".to_string()]));
+    assert!(cfg.blocked.is_empty());
+    assert_eq!(cfg.color, [255, 128, 0]);
+    assert_eq!(cfg.window, (1280, 720));
+    assert_eq!(*cfg.limit, 10);
+    assert_eq!(*cfg.name, "app");
+    Ok(())
+}
+
+#[test]
+fn std_types_round_trip() -> Result<(), ConfigError> {
+    let cfg = Server::parse_toml(CONFIG)?;
+    let node = cfg.to_node();
+
+    assert_eq!(node.get_path("listen").unwrap().to_string(), "0.0.0.0:8080");
+    assert_eq!(node.get_path("window").unwrap().to_string(), "[1280, 720]");
+    assert_eq!(Server::load_from(&node)?.roles, cfg.roles);
+    Ok(())
+}
+
+#[test]
+fn duplicate_set_items_are_rejected() {
+    let config = CONFIG.replace(r#"["api", "admin"]"#, r#"["api", "admin", "api"]"#);
+    let err = Server::parse_toml(&config).unwrap_err();
+
+    assert!(matches!(
+        err,
+        ConfigError::TypeMismatch { field, found, .. }
+            if field == "roles[2]" && found == "duplicate of roles[0]"
+    ));
+}
+
+#[test]
+fn fixed_arrays_check_their_length() {
+    let config = CONFIG.replace("[255, 128, 0]", "[255, 128]");
+    let err = Server::parse_toml(&config).unwrap_err();
+
+    assert!(matches!(
+        err,
+        ConfigError::TypeMismatch { field, expected, .. }
+            if field == "color" && expected == "array of 3 items"
+    ));
+}
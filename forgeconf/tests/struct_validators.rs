@@ -0,0 +1,98 @@
+#![cfg(all(feature = "validators", feature = "parse", feature = "toml"))]
+
+use forgeconf::validators::{at_least_one_of, mutually_exclusive, required_if};
+use forgeconf::{ConfigError, forgeconf};
+
+#[derive(Debug)]
+#[forgeconf(
+    validate = required_if("tls_cert", "tls", true),
+    validate = mutually_exclusive(["password", "key_file"]),
+    validate = at_least_one_of(["password", "key_file"]),
+    validate = pool_bounds,
+    to_node,
+)]
+struct Server {
+    #[field(default = false)]
+    tls: bool,
+    #[field(optional = true)]
+    tls_cert: Option<String>,
+    #[field(optional = true)]
+    password: Option<String>,
+    #[field(optional = true)]
+    key_file: Option<String>,
+    #[field(default = 1)]
+    min_connections: u32,
+    #[field(default = 10)]
+    max_connections: u32,
+}
+
+fn pool_bounds(server: &Server) -> Result<(), ConfigError> {
+    if server.min_connections <= server.max_connections {
+        Ok(())
+    } else {
+        Err(ConfigError::constraint(
+            ["min_connections", "max_connections"],
+            "`min_connections` must not exceed `max_connections`",
+        ))
+    }
+}
+
+#[derive(Debug)]
+#[forgeconf]
+#[allow(dead_code)]
+struct AppConfig {
+    #[field(nested)]
+    server: Server,
+}
+
+#[test]
+fn valid_combinations_load() -> Result<(), ConfigError> {
+    let server = Server::parse_toml("tls = true\ntls_cert = \"cert.pem\"\npassword = \"p\"\n")?;
+
+    assert_eq!(server.tls_cert.as_deref(), Some("cert.pem"));
+    Ok(())
+}
+
+#[test]
+fn required_if_names_both_fields() {
+    let err = Server::parse_toml("tls = true\npassword = \"p\"\n").unwrap_err();
+
+    assert!(matches!(
+        err,
+        ConfigError::Constraint { fields, span: Some(_), .. } if fields == ["tls_cert", "tls"]
+    ));
+}
+
+#[test]
+fn custom_validators_run_after_fields_are_built() {
+    let err = Server::parse_toml("password = \"p\"\nmin_connections = 20\n").unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "invalid combination of min_connections, max_connections: `min_connections` must not \
+         exceed `max_connections`"
+    );
+}
+
+#[test]
+fn collected_errors_include_every_violated_rule() {
+    let err = AppConfig::loader()
+        .add_source(forgeconf::EnvSource::new("APP_").with_vars([
+            ("APP_SERVER__TLS", "true"),
+            ("APP_SERVER__PASSWORD", "p"),
+            ("APP_SERVER__KEY_FILE", "id_rsa"),
+        ]))
+        .collect_errors()
+        .load()
+        .unwrap_err();
+
+    let ConfigError::Nested { section, source, .. } = err else {
+        panic!("expected a nested error, got {err:?}");
+    };
+    assert_eq!(section, "server");
+    let source: &dyn std::error::Error = &*source;
+    let Some(ConfigError::Multiple { errors }) = source.downcast_ref::<ConfigError>() else {
+        panic!("expected multiple errors");
+    };
+    assert_eq!(errors.len(), 2);
+}
@@ -0,0 +1,47 @@
+#![cfg(all(feature = "parse", feature = "toml"))]
+
+use forgeconf::{ConfigError, forgeconf};
+
+#[forgeconf]
+struct LenientConfig {
+    port: u16,
+    name: String,
+}
+
+#[derive(Debug)]
+#[forgeconf]
+struct StrictConfig {
+    #[field(strict)]
+    port: u16,
+    #[field(strict, default = false)]
+    debug: bool,
+}
+
+#[test]
+fn lenient_fields_coerce_quoted_values() -> Result<(), ConfigError> {
+    let cfg = LenientConfig::parse_toml("port = \"8080\"\nname = 42")?;
+
+    assert_eq!(cfg.port, 8080);
+    assert_eq!(cfg.name, "42");
+    Ok(())
+}
+
+#[test]
+fn strict_fields_accept_matching_types() -> Result<(), ConfigError> {
+    let cfg = StrictConfig::parse_toml("port = 8080\ndebug = true")?;
+
+    assert_eq!(cfg.port, 8080);
+    assert!(cfg.debug);
+    Ok(())
+}
+
+#[test]
+fn strict_fields_reject_quoted_values() {
+    let err = StrictConfig::parse_toml("port = \"8080\"").unwrap_err();
+
+    assert!(matches!(
+        err,
+        ConfigError::TypeMismatch { field, expected, found, .. }
+            if field == "port" && expected == "integer" && found == "string"
+    ));
+}
@@ -0,0 +1,70 @@
+#![cfg(all(feature = "parse", feature = "toml"))]
+
+use std::time::Duration;
+
+use forgeconf::validators::{duration_range, size_range};
+use forgeconf::{ByteSize, ConfigError, forgeconf};
+
+#[derive(Debug)]
+#[forgeconf(to_node)]
+struct Limits {
+    #[field(validate = duration_range("100ms", "5m"))]
+    timeout: Duration,
+    #[field(default = Duration::from_secs(30))]
+    idle: Duration,
+    #[field(validate = size_range("4KiB", "1GiB"))]
+    buffer: ByteSize,
+    #[field(default = ByteSize::mib(64))]
+    cache: ByteSize,
+}
+
+#[test]
+fn units_load_from_strings_and_numbers() -> Result<(), ConfigError> {
+    let cfg =
+        Limits::parse_toml("timeout = \"1m30s\"\nidle = 45\nbuffer = \"512MiB\"\ncache = 1024")?;
+
+    assert_eq!(cfg.timeout, Duration::from_secs(90));
+    assert_eq!(cfg.idle, Duration::from_secs(45));
+    assert_eq!(cfg.buffer, ByteSize::mib(512));
+    assert_eq!(cfg.cache.as_u64(), 1024);
+    Ok(())
+}
+
+#[test]
+fn units_use_defaults_and_round_trip() -> Result<(), ConfigError> {
+    let cfg = Limits::parse_toml("timeout = \"250ms\"\nbuffer = \"1.5MB\"")?;
+
+    assert_eq!(cfg.idle, Duration::from_secs(30));
+    assert_eq!(cfg.cache, ByteSize::mib(64));
+
+    let node = cfg.to_node();
+    assert_eq!(node.get_path("timeout").unwrap().to_string(), "250ms");
+    assert_eq!(node.get_path("buffer").unwrap().to_string(), "1500kB");
+    Ok(())
+}
+
+#[test]
+fn malformed_units_are_type_mismatches() {
+    let err = Limits::parse_toml("timeout = \"soon\"\nbuffer = \"1MiB\"").unwrap_err();
+    assert!(matches!(
+        err,
+        ConfigError::TypeMismatch { field, expected, found, .. }
+            if field == "timeout" && expected.starts_with("duration like") && found == "soon"
+    ));
+
+    let err = Limits::parse_toml("timeout = \"1s\"\nbuffer = \"12 bananas\"").unwrap_err();
+    assert!(matches!(
+        err,
+        ConfigError::TypeMismatch { field, found, .. } if field == "buffer" && found == "12 bananas"
+    ));
+}
+
+#[test]
+fn unit_validators_report_bounds() {
+    let err = Limits::parse_toml("timeout = \"10m\"\nbuffer = \"1KiB\"").unwrap_err();
+    assert!(matches!(
+        err,
+        ConfigError::TypeMismatch { field, expected, found, .. }
+            if field == "timeout" && expected == "between 100ms and 5m" && found == "10m"
+    ));
+}
@@ -0,0 +1,122 @@
+#![cfg(all(feature = "parse", feature = "toml"))]
+
+use std::sync::Mutex;
+
+use forgeconf::{ConfigError, forgeconf};
+
+#[derive(Debug)]
+#[forgeconf(deny_unknown_fields)]
+#[allow(dead_code)]
+struct Database {
+    host: String,
+    #[field(default = 5432)]
+    port: u16,
+}
+
+#[derive(Debug)]
+#[forgeconf(deny_unknown_fields)]
+#[allow(dead_code)]
+struct StrictConfig {
+    #[field(default = "sqlite://memory".to_string())]
+    database_url: String,
+    #[field(nested)]
+    database: Database,
+}
+
+static WARNINGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+fn record(err: &ConfigError) {
+    WARNINGS.lock().unwrap().push(err.to_string());
+}
+
+#[derive(Debug)]
+#[forgeconf(warn_unknown_fields = record)]
+#[allow(dead_code)]
+struct LenientConfig {
+    name: String,
+}
+
+#[test]
+fn unknown_keys_are_rejected_with_suggestion() {
+    let err =
+        StrictConfig::parse_toml("datbase_url = \"postgres://db\"\n[database]\nhost = \"db\"\n")
+            .unwrap_err();
+
+    assert!(matches!(
+        err,
+        ConfigError::UnknownField { field, suggestion: Some(suggestion), span: Some(_), .. }
+            if field == "datbase_url" && suggestion == "database_url"
+    ));
+}
+
+#[test]
+fn unknown_keys_are_rejected_in_nested_sections() {
+    let err = StrictConfig::parse_toml("[database]\nhost = \"db\"\nprot = 1\n").unwrap_err();
+
+    let ConfigError::Nested { section, source, .. } = err else {
+        panic!("expected nested error");
+    };
+    assert_eq!(section, "database");
+    let source: &dyn std::error::Error = &*source;
+    assert!(matches!(
+        source.downcast_ref::<ConfigError>(),
+        Some(ConfigError::UnknownField { field, suggestion: Some(suggestion), .. })
+            if field == "prot" && suggestion == "port"
+    ));
+}
+
+#[test]
+fn known_keys_load_normally() -> Result<(), ConfigError> {
+    let cfg = StrictConfig::parse_toml("[database]\nhost = \"db\"\nport = 1\n")?;
+
+    assert_eq!(cfg.database.port, 1);
+    Ok(())
+}
+
+#[test]
+fn warn_callback_sees_unknown_keys_without_failing() -> Result<(), ConfigError> {
+    let cfg = LenientConfig::parse_toml("name = \"api\"\nnmae = \"typo\"\n")?;
+
+    assert_eq!(cfg.name, "api");
+    assert_eq!(*WARNINGS.lock().unwrap(), ["unknown field 'nmae'"]);
+    Ok(())
+}
+
+#[derive(Debug)]
+#[forgeconf(tag = "kind", deny_unknown_fields)]
+#[allow(dead_code)]
+enum Storage {
+    Memory,
+    Disk { path: String },
+}
+
+#[derive(Debug)]
+#[forgeconf]
+#[allow(dead_code)]
+struct StorageConfig {
+    storage: Storage,
+}
+
+#[test]
+fn unknown_keys_are_rejected_in_struct_variants() -> Result<(), ConfigError> {
+    let err = StorageConfig::parse_toml("[storage]\nkind = \"disk\"\npath = \"/srv\"\npaht = 1\n")
+        .unwrap_err();
+
+    let ConfigError::Nested { source, .. } = err else {
+        panic!("expected nested error, got {err:?}");
+    };
+    let source: &dyn std::error::Error = &*source;
+    assert!(matches!(
+        source.downcast_ref::<ConfigError>(),
+        Some(ConfigError::UnknownField { field, suggestion: Some(suggestion), .. })
+            if field == "paht" && suggestion == "path"
+    ));
+
+    let err =
+        StorageConfig::parse_toml("[storage]\nkind = \"memory\"\npath = \"/srv\"\n").unwrap_err();
+    assert!(matches!(err, ConfigError::Nested { .. }), "{err:?}");
+
+    let cfg = StorageConfig::parse_toml("[storage]\nkind = \"disk\"\npath = \"/srv\"\n")?;
+    assert!(matches!(cfg.storage, Storage::Disk { ref path } if path == "/srv"));
+    Ok(())
+}
@@ -0,0 +1,198 @@
+use std::fmt::{self, Debug, Formatter};
+
+use crate::{ConfigError, Origin, Provenance, SourceMap};
+
+/// Settings shared by everything built during one load, handed down through
+/// [`FromNode::from_node_in`](crate::FromNode::from_node_in).
+///
+/// A new context stops at the first error. [`LoadContext::collecting`]
+/// keeps going instead, so `#[forgeconf]` structs and collections report
+/// every failure they contain as one [`ConfigError::Multiple`].
+/// [`LoadContext::recording`] additionally notes where each value came from
+/// as it is loaded.
+#[derive(Debug, Default)]
+pub struct LoadContext<'s> {
+    collect_errors: bool,
+    recorder: Option<Recorder<'s>>,
+}
+
+/// The origins noted so far, with the path of the section being loaded.
+struct Recorder<'s> {
+    sources: &'s SourceMap,
+    provenance: Provenance,
+    prefix: String,
+    records: usize,
+}
+
+impl Debug for Recorder<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Recorder")
+            .field("provenance", &self.provenance)
+            .field("prefix", &self.prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'s> LoadContext<'s> {
+    /// A context that stops at the first error.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A context that reports every error.
+    pub fn collecting() -> Self {
+        Self { collect_errors: true, recorder: None }
+    }
+
+    /// Also record the [`Origin`] of every value loaded, looking up merged
+    /// values in `sources`. Read the result with
+    /// [`LoadContext::into_provenance`].
+    pub fn recording(mut self, sources: &'s SourceMap) -> Self {
+        self.recorder = Some(Recorder {
+            sources,
+            provenance: Provenance::new(),
+            prefix: String::new(),
+            records: 0,
+        });
+        self
+    }
+
+    /// Whether loading continues past the first error.
+    pub fn collects_errors(&self) -> bool {
+        self.collect_errors
+    }
+
+    /// Run `load` on each of `items`. Stops at the first error, unless the
+    /// context collects errors, in which case every item is tried and the
+    /// failures are returned together.
+    pub fn load_all<I, T, C>(
+        &mut self,
+        items: I,
+        mut load: impl FnMut(&mut Self, I::Item) -> Result<T, ConfigError>,
+    ) -> Result<C, ConfigError>
+    where
+        I: IntoIterator,
+        C: FromIterator<T>,
+    {
+        let mut values = Vec::new();
+        let mut errors = Vec::new();
+        for item in items {
+            match load(self, item) {
+                Ok(value) => values.push(value),
+                Err(err) if self.collect_errors => errors.push(err),
+                Err(err) => return Err(err),
+            }
+        }
+        if errors.is_empty() {
+            Ok(values.into_iter().collect())
+        } else {
+            Err(ConfigError::multiple(errors))
+        }
+    }
+
+    /// Run `load` with `key` appended to the path that recorded origins are
+    /// stored under, for a section loaded at `key`.
+    pub fn scoped<T>(&mut self, key: &str, load: impl FnOnce(&mut Self) -> T) -> T {
+        let Some(recorder) = &mut self.recorder else {
+            return load(self);
+        };
+        let outer = recorder.prefix.len();
+        if outer > 0 {
+            recorder.prefix.push('.');
+        }
+        recorder.prefix.push_str(key);
+
+        let value = load(self);
+        if let Some(recorder) = &mut self.recorder {
+            recorder.prefix.truncate(outer);
+        }
+        value
+    }
+
+    /// Where the merged sources supplied the value at `key` in the current
+    /// section, or `None` when not recording.
+    pub fn source_origin(&self, key: &str) -> Option<Origin> {
+        let recorder = self.recorder.as_ref()?;
+        recorder.sources.origin(&recorder.join(key))
+    }
+
+    /// Record the origin of the value at `key` in the current section. The
+    /// origin is only built when recording.
+    pub fn record(&mut self, key: &str, origin: impl FnOnce() -> Origin) {
+        if let Some(recorder) = &mut self.recorder {
+            let path = recorder.join(key);
+            recorder.provenance.insert(path, origin());
+            recorder.records += 1;
+        }
+    }
+
+    /// How many origins have been recorded so far, so that a field can tell
+    /// whether loading its value already recorded where its parts came from.
+    pub fn recorded(&self) -> usize {
+        self.recorder
+            .as_ref()
+            .map_or(0, |recorder| recorder.records)
+    }
+
+    /// The origins recorded during the load.
+    pub fn into_provenance(self) -> Provenance {
+        self.recorder
+            .map(|recorder| recorder.provenance)
+            .unwrap_or_default()
+    }
+}
+
+impl Recorder<'_> {
+    fn join(&self, key: &str) -> String {
+        if self.prefix.is_empty() { key.to_string() } else { format!("{}.{}", self.prefix, key) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(cx: &mut LoadContext, items: &[&str]) -> Result<Vec<u8>, ConfigError> {
+        cx.load_all(items, |_, item| {
+            item.parse()
+                .map_err(|_| ConfigError::mismatch(*item, "u8", *item))
+        })
+    }
+
+    #[test]
+    fn load_all_stops_at_the_first_error_by_default() {
+        let err = parse(&mut LoadContext::new(), &["1", "x", "y"]).unwrap_err();
+        assert!(matches!(err, ConfigError::TypeMismatch { field, .. } if field == "x"));
+    }
+
+    #[test]
+    fn load_all_reports_every_error_when_collecting() {
+        let err = parse(&mut LoadContext::collecting(), &["1", "x", "y"]).unwrap_err();
+        assert!(matches!(err, ConfigError::Multiple { errors } if errors.len() == 2));
+    }
+
+    #[test]
+    fn record_stores_origins_under_the_scoped_path() {
+        let sources = SourceMap::new();
+        let mut cx = LoadContext::new().recording(&sources);
+        cx.scoped("http", |cx| {
+            cx.scoped("tls", |cx| cx.record("cert", Origin::default_value));
+            cx.record("port", Origin::default_value);
+        });
+        cx.record("name", Origin::default_value);
+
+        assert_eq!(cx.recorded(), 3);
+        let provenance = cx.into_provenance();
+        let paths: Vec<_> = provenance.iter().map(|(path, _)| path).collect();
+        assert_eq!(paths, ["http.port", "http.tls.cert", "name"]);
+    }
+
+    #[test]
+    fn record_is_a_no_op_unless_recording() {
+        let mut cx = LoadContext::new();
+        cx.record("name", || unreachable!());
+
+        assert_eq!(cx.recorded(), 0);
+        assert!(cx.into_provenance().is_empty());
+    }
+}
@@ -5,9 +5,15 @@
 
 #![allow(unused_assignments)] // miette Diagnostic derive macro triggers false positive unused_assignments warnings
 
+use std::error::Error;
 use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use miette::{Diagnostic, SourceSpan};
+use miette::{Diagnostic, NamedSource, SourceSpan};
+
+use crate::FileFormat;
+use crate::secret::REDACTED;
 
 /// Unified error returned by the runtime components.
 #[derive(Diagnostic, thiserror::Error)]
@@ -45,6 +51,9 @@ pub enum ConfigError {
         /// Optional source span for where the error occurred
         #[label("expected '{field}' to be defined here")]
         span: Option<SourceSpan>,
+        /// The document `span` points into, when known
+        #[source_code]
+        src: Option<Arc<NamedSource<String>>>,
     },
 
     /// Raised when a value cannot be converted into the expected target type.
@@ -66,6 +75,13 @@ pub enum ConfigError {
         /// Optional source span highlighting the problematic value
         #[label("expected {expected}, but found {found}")]
         span: Option<SourceSpan>,
+        /// The document `span` points into, when known
+        #[source_code]
+        src: Option<Arc<NamedSource<String>>>,
+        /// Whether `found` was hidden by [`ConfigError::redact`]; such
+        /// errors are never given a span, since the snippet would show the
+        /// value
+        redacted: bool,
     },
 
     /// Used to thread contextual errors when deserializing nested structs.
@@ -84,6 +100,129 @@ pub enum ConfigError {
         /// Optional source span for the section
         #[label("error in this section")]
         span: Option<SourceSpan>,
+        /// The document `span` points into, when known
+        #[source_code]
+        src: Option<Arc<NamedSource<String>>>,
+    },
+
+    /// Raised for keys that do not match any field when unknown fields are
+    /// denied.
+    #[error("unknown field '{field}'")]
+    #[diagnostic(code(forgeconf::unknown_field))]
+    UnknownField {
+        /// The unexpected key
+        field: String,
+        /// The closest known field name, when one is similar enough
+        suggestion: Option<String>,
+        /// Rendered hint built from `suggestion`
+        #[help]
+        help: Option<String>,
+        /// Optional source span of the unexpected value
+        #[label("not a known field")]
+        span: Option<SourceSpan>,
+        /// The document `span` points into, when known
+        #[source_code]
+        src: Option<Arc<NamedSource<String>>>,
+    },
+
+    /// Raised when the selected profile has no `[profile.<name>]` section in
+    /// any source.
+    #[error("unknown profile '{profile}'")]
+    #[diagnostic(code(forgeconf::unknown_profile))]
+    UnknownProfile {
+        /// The selected profile
+        profile: String,
+        /// Profiles defined by the sources
+        known: Vec<String>,
+        /// Rendered list of `known`
+        #[help]
+        help: String,
+    },
+
+    /// Several independent errors, reported together when loading in
+    /// collecting mode.
+    #[error("found {} configuration errors", errors.len())]
+    #[diagnostic(
+        code(forgeconf::multiple_errors),
+        help("Each problem is listed below; fix them all before loading again.")
+    )]
+    Multiple {
+        /// The collected errors, in field order
+        #[related]
+        errors: Vec<ConfigError>,
+    },
+
+    /// Raised when a `${...}` reference in a value cannot be resolved.
+    #[error("cannot interpolate '{field}': {reason}")]
+    #[diagnostic(
+        code(forgeconf::interpolation),
+        help(
+            "References name another key (`${{database.port}}`) or an environment variable \
+             (`${{HOME}}`); add a fallback with `${{NAME:-value}}` or write `$${{` for a literal \
+             `${{`."
+        )
+    )]
+    Interpolation {
+        /// Path of the value containing the reference
+        field: String,
+        /// What went wrong
+        reason: String,
+        /// Optional source span of the value
+        #[label("{reason}")]
+        span: Option<SourceSpan>,
+        /// The document `span` points into, when known
+        #[source_code]
+        src: Option<Arc<NamedSource<String>>>,
+    },
+
+    /// Raised by struct-level validators when a rule spanning several fields
+    /// is violated, such as two mutually exclusive fields both being set.
+    #[error("invalid combination of {}: {reason}", fields.join(", "))]
+    #[diagnostic(code(forgeconf::constraint))]
+    Constraint {
+        /// Paths of the fields involved, relative to the validated struct
+        fields: Vec<String>,
+        /// The rule that was violated
+        reason: String,
+        /// Optional source span of the first field
+        #[label("{reason}")]
+        span: Option<SourceSpan>,
+        /// The document `span` points into, when known
+        #[source_code]
+        src: Option<Arc<NamedSource<String>>>,
+    },
+
+    /// Raised when an `include` directive cannot be followed, such as a
+    /// missing file or a cycle.
+    #[error("cannot include '{}': {reason}", path.display())]
+    #[diagnostic(
+        code(forgeconf::include_error),
+        help(
+            "Include paths are relative to the including file; wildcards (`*`, `?`) are only \
+             allowed in the file name."
+        )
+    )]
+    Include {
+        /// The file that could not be included
+        path: PathBuf,
+        /// What went wrong
+        reason: String,
+    },
+
+    /// Raised when a tree cannot be written in the requested format, such as
+    /// a `null` inside a TOML array.
+    #[error("cannot write '{path}' as {format}: {reason}")]
+    #[diagnostic(
+        code(forgeconf::serialize_error),
+        help("Pick a format that can represent this value, or remove it before writing.")
+    )]
+    Serialize {
+        /// The target format
+        format: String,
+        /// Path of the offending value (`root` for the document itself)
+        path: String,
+        /// Why the value cannot be written
+        reason: String,
     },
 
     /// IO errors propagated from the filesystem.
@@ -111,6 +250,9 @@ pub enum ConfigError {
         /// Optional source span for where the parse error occurred
         #[label("parse error here")]
         span: Option<SourceSpan>,
+        /// The document that failed to parse, when known
+        #[source_code]
+        src: Option<Arc<NamedSource<String>>>,
     },
 
     /// JSON parsing failure.
@@ -130,6 +272,9 @@ pub enum ConfigError {
         /// Optional source span for where the parse error occurred
         #[label("parse error here")]
         span: Option<SourceSpan>,
+        /// The document that failed to parse, when known
+        #[source_code]
+        src: Option<Arc<NamedSource<String>>>,
     },
 
     /// YAML parsing failure.
@@ -149,6 +294,9 @@ pub enum ConfigError {
         /// Optional source span for where the parse error occurred
         #[label("parse error here")]
         span: Option<SourceSpan>,
+        /// The document that failed to parse, when known
+        #[source_code]
+        src: Option<Arc<NamedSource<String>>>,
     },
 }
 
@@ -192,6 +340,91 @@ impl ConfigError {
             expected: expected.into(),
             found: found.into(),
             span: None,
+            src: None,
+            redacted: false,
+        }
+    }
+
+    /// Hide the values echoed by `err`, for errors about secret fields.
+    ///
+    /// Redacted mismatches are also never given a span, since the snippet
+    /// would show the value.
+    pub fn redact(self) -> Self {
+        match self {
+            ConfigError::TypeMismatch { field, expected, .. } => ConfigError::TypeMismatch {
+                field,
+                expected,
+                found: REDACTED.into(),
+                span: None,
+                src: None,
+                redacted: true,
+            },
+            ConfigError::Nested { section, source, span, src } => {
+                let source = match downcast_nested(source) {
+                    Ok(inner) => Box::new(inner.redact()),
+                    Err(source) => source,
+                };
+                ConfigError::Nested { section, source, span, src }
+            },
+            ConfigError::Multiple { errors } => ConfigError::Multiple {
+                errors: errors.into_iter().map(ConfigError::redact).collect(),
+            },
+            other => other,
+        }
+    }
+
+    /// Helper to produce an `Interpolation` error.
+    pub fn interpolation(field: impl Into<String>, reason: impl Into<String>) -> Self {
+        ConfigError::Interpolation {
+            field: field.into(),
+            reason: reason.into(),
+            span: None,
+            src: None,
+        }
+    }
+
+    /// Helper to produce a `Constraint` error for the fields at `paths`.
+    pub fn constraint<I, S>(paths: I, reason: impl Into<String>) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        ConfigError::Constraint {
+            fields: paths.into_iter().map(Into::into).collect(),
+            reason: reason.into(),
+            span: None,
+            src: None,
+        }
+    }
+
+    /// Helper to produce an `Include` error.
+    pub fn include(path: impl AsRef<Path>, reason: impl Into<String>) -> Self {
+        ConfigError::Include {
+            path: path.as_ref().to_path_buf(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Helper to produce an `UnknownProfile` error.
+    pub fn unknown_profile<'a>(
+        profile: impl Into<String>,
+        known: impl IntoIterator<Item = &'a str>,
+    ) -> Self {
+        let known: Vec<String> = known.into_iter().map(str::to_string).collect();
+        let help = format!("known profiles: {}", known.join(", "));
+        ConfigError::UnknownProfile { profile: profile.into(), known, help }
+    }
+
+    /// Helper to produce a `Serialize` error.
+    pub fn serialize(
+        format: FileFormat,
+        path: impl Into<String>,
+        reason: impl Into<String>,
+    ) -> Self {
+        ConfigError::Serialize {
+            format: format.label().to_string(),
+            path: path.into(),
+            reason: reason.into(),
         }
     }
 
@@ -207,6 +440,8 @@ impl ConfigError {
             expected: expected.into(),
             found: found.into(),
             span: Some(span),
+            src: None,
+            redacted: false,
         }
     }
 
@@ -216,6 +451,7 @@ impl ConfigError {
             section: section.into(),
             source: Box::new(source),
             span: None,
+            src: None,
         }
     }
 
@@ -225,18 +461,110 @@ impl ConfigError {
             section: section.into(),
             source: Box::new(source),
             span: Some(span),
+            src: None,
         }
     }
 
+    /// Helper to report a key that matches none of the `known` field names,
+    /// suggesting the closest one by edit distance.
+    pub fn unknown_field(key: impl Into<String>, known: &[&str]) -> Self {
+        let field = key.into();
+        let suggestion = closest_match(&field, known).map(str::to_string);
+        let help = suggestion
+            .as_ref()
+            .map(|name| format!("did you mean '{name}'?"));
+        ConfigError::UnknownField {
+            field,
+            suggestion,
+            help,
+            span: None,
+            src: None,
+        }
+    }
+
+    /// Helper to combine collected errors: a single error is returned as is,
+    /// several become [`ConfigError::Multiple`]. Nested `Multiple` errors are
+    /// flattened.
+    pub fn multiple(errors: impl IntoIterator<Item = ConfigError>) -> Self {
+        let mut flat = Vec::new();
+        for err in errors {
+            match err {
+                ConfigError::Multiple { errors } => flat.extend(errors),
+                other => flat.push(other),
+            }
+        }
+
+        if flat.len() == 1 { flat.remove(0) } else { ConfigError::Multiple { errors: flat } }
+    }
+
     /// Helper to surface missing values.
     pub fn missing(key: impl Into<String>) -> Self {
-        ConfigError::MissingValue { field: key.into(), span: None }
+        ConfigError::MissingValue { field: key.into(), span: None, src: None }
     }
 
     /// Helper to surface missing values with a source span.
     pub fn missing_at(key: impl Into<String>, span: SourceSpan) -> Self {
-        ConfigError::MissingValue { field: key.into(), span: Some(span) }
+        ConfigError::MissingValue {
+            field: key.into(),
+            span: Some(span),
+            src: None,
+        }
+    }
+}
+
+/// Pick the candidate closest to `name`, ignoring any that differ in more
+/// than a third of its characters.
+fn closest_match<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let limit = (name.chars().count() / 3).max(1);
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(name, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Edit distance between two strings, counted in chars, where swapping two
+/// adjacent chars counts as a single edit (optimal string alignment).
+fn edit_distance(left: &str, right: &str) -> usize {
+    let left: Vec<char> = left.chars().collect();
+    let right: Vec<char> = right.chars().collect();
+    let mut table = vec![vec![0; right.len() + 1]; left.len() + 1];
+    for (row, cells) in table.iter_mut().enumerate() {
+        cells[0] = row;
+    }
+    table[0] = (0..=right.len()).collect();
+
+    for row in 1..=left.len() {
+        for col in 1..=right.len() {
+            let cost = usize::from(left[row - 1] != right[col - 1]);
+            let mut best = (table[row - 1][col - 1] + cost)
+                .min(table[row - 1][col] + 1)
+                .min(table[row][col - 1] + 1);
+            if row > 1
+                && col > 1
+                && left[row - 1] == right[col - 2]
+                && left[row - 2] == right[col - 1]
+            {
+                best = best.min(table[row - 2][col - 2] + 1);
+            }
+            table[row][col] = best;
+        }
     }
+
+    table[left.len()][right.len()]
+}
+
+/// Recover the [`ConfigError`] wrapped by a `Nested` variant.
+pub(crate) fn downcast_nested(
+    source: Box<dyn Diagnostic + Send + Sync>,
+) -> Result<ConfigError, Box<dyn Diagnostic + Send + Sync>> {
+    if !(&*source as &dyn Error).is::<ConfigError>() {
+        return Err(source);
+    }
+
+    let source: Box<dyn Error + Send + Sync> = source;
+    Ok(*source.downcast::<ConfigError>().expect("checked above"))
 }
 
 #[cfg(test)]
@@ -253,6 +581,7 @@ mod tests {
                 expected,
                 found,
                 span: None,
+                ..
             } if field == "port" && expected == "u16" && found == "string"
         ));
     }
@@ -276,8 +605,47 @@ mod tests {
         let err = ConfigError::missing("database");
         assert!(matches!(
             err,
-            ConfigError::MissingValue { ref field, span: None }
+            ConfigError::MissingValue { ref field, span: None, .. }
                 if field == "database"
         ));
     }
+
+    #[test]
+    fn multiple_helper_unwraps_single_error() {
+        let err = ConfigError::multiple([ConfigError::missing("port")]);
+        assert!(matches!(err, ConfigError::MissingValue { .. }));
+    }
+
+    #[test]
+    fn multiple_helper_flattens_nested_lists() {
+        let inner = ConfigError::multiple([ConfigError::missing("a"), ConfigError::missing("b")]);
+        let err = ConfigError::multiple([inner, ConfigError::missing("c")]);
+        assert!(matches!(err, ConfigError::Multiple { ref errors } if errors.len() == 3));
+    }
+
+    #[test]
+    fn unknown_field_suggests_closest_name() {
+        let err = ConfigError::unknown_field("datbase_url", &["port", "database_url"]);
+        assert!(matches!(
+            err,
+            ConfigError::UnknownField { ref suggestion, .. }
+                if suggestion.as_deref() == Some("database_url")
+        ));
+    }
+
+    #[test]
+    fn unknown_field_skips_distant_names() {
+        let err = ConfigError::unknown_field("colour", &["port", "host"]);
+        assert!(matches!(err, ConfigError::UnknownField { suggestion: None, help: None, .. }));
+    }
+
+    #[test]
+    fn edit_distance_counts_single_edits() {
+        assert_eq!(edit_distance("port", "port"), 0);
+        assert_eq!(edit_distance("prot", "port"), 1);
+        assert_eq!(edit_distance("hots", "host"), 1);
+        assert_eq!(edit_distance("port", "pot"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("datbase", "database"), 1);
+    }
 }
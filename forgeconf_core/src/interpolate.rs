@@ -0,0 +1,262 @@
+//! `${...}` references inside string values.
+//!
+//! `${database.port}` refers to another key of the merged tree and `${HOME}`
+//! to an environment variable; keys win when both exist. `${NAME:-fallback}`
+//! uses `fallback` when neither does, and `$${` writes a literal `${`. A value
+//! that consists of a single reference takes the referenced value as is, so
+//! `port = "${base.port}"` stays an integer.
+
+use std::collections::BTreeMap;
+
+use crate::span::{index_path, join_path};
+use crate::{ConfigError, ConfigNode};
+
+/// Resolve every reference in `root`, reading environment variables through
+/// `env`.
+pub(crate) fn interpolate(
+    root: &ConfigNode,
+    env: &dyn Fn(&str) -> Option<String>,
+) -> Result<ConfigNode, ConfigError> {
+    let mut resolver = Resolver {
+        root,
+        env,
+        resolved: BTreeMap::new(),
+        stack: Vec::new(),
+    };
+    resolver.value_at("", root)
+}
+
+struct Resolver<'a> {
+    root: &'a ConfigNode,
+    env: &'a dyn Fn(&str) -> Option<String>,
+    /// Interpolated leaves, keyed by path.
+    resolved: BTreeMap<String, ConfigNode>,
+    /// Paths of the leaves currently being interpolated, for cycle detection.
+    stack: Vec<String>,
+}
+
+enum Segment<'t> {
+    Text(&'t str),
+    Reference { name: &'t str, fallback: Option<&'t str> },
+}
+
+impl Resolver<'_> {
+    fn value_at(&mut self, path: &str, node: &ConfigNode) -> Result<ConfigNode, ConfigError> {
+        Ok(match node {
+            ConfigNode::Table(map) => ConfigNode::Table(
+                map.iter()
+                    .map(|(key, value)| {
+                        Ok((key.clone(), self.value_at(&join_path(path, key), value)?))
+                    })
+                    .collect::<Result<_, ConfigError>>()?,
+            ),
+            ConfigNode::Array(items) => ConfigNode::Array(
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(index, item)| self.value_at(&index_path(path, index), item))
+                    .collect::<Result<_, _>>()?,
+            ),
+            ConfigNode::String(text) if text.contains('$') => {
+                self.leaf(path, text, ConfigNode::String)?
+            },
+            ConfigNode::Scalar(text) if text.contains('$') => {
+                self.leaf(path, text, ConfigNode::Scalar)?
+            },
+            other => other.clone(),
+        })
+    }
+
+    fn leaf(
+        &mut self,
+        path: &str,
+        text: &str,
+        wrap: fn(String) -> ConfigNode,
+    ) -> Result<ConfigNode, ConfigError> {
+        if let Some(done) = self.resolved.get(path) {
+            return Ok(done.clone());
+        }
+        if let Some(start) = self.stack.iter().position(|entry| entry == path) {
+            let mut cycle = self.stack[start..].to_vec();
+            cycle.push(path.to_string());
+            return Err(ConfigError::interpolation(
+                path,
+                format!("reference cycle {}", cycle.join(" -> ")),
+            ));
+        }
+
+        self.stack.push(path.to_string());
+        let value = self.expand(path, text, wrap);
+        self.stack.pop();
+
+        let value = value?;
+        self.resolved.insert(path.to_string(), value.clone());
+        Ok(value)
+    }
+
+    fn expand(
+        &mut self,
+        path: &str,
+        text: &str,
+        wrap: fn(String) -> ConfigNode,
+    ) -> Result<ConfigNode, ConfigError> {
+        let segments = parse(text).map_err(|reason| ConfigError::interpolation(path, reason))?;
+        if let [Segment::Reference { name, fallback }] = segments.as_slice() {
+            return self.lookup(path, name, *fallback);
+        }
+
+        let mut out = String::with_capacity(text.len());
+        for segment in segments {
+            match segment {
+                Segment::Text(text) => out.push_str(text),
+                Segment::Reference { name, fallback } => {
+                    let value = self.lookup(path, name, fallback)?;
+                    let Some(text) = value.as_text() else {
+                        return Err(ConfigError::interpolation(
+                            path,
+                            format!("`{name}` is {} and cannot be embedded in text", value.kind()),
+                        ));
+                    };
+                    out.push_str(&text);
+                },
+            }
+        }
+        Ok(wrap(out))
+    }
+
+    fn lookup(
+        &mut self,
+        path: &str,
+        name: &str,
+        fallback: Option<&str>,
+    ) -> Result<ConfigNode, ConfigError> {
+        if name.is_empty() {
+            return Err(ConfigError::interpolation(path, "empty reference `${}`"));
+        }
+        if let Some(node) = self.root.get_path(name) {
+            return self.value_at(name, node);
+        }
+        if let Some(value) = (self.env)(name) {
+            return Ok(ConfigNode::Scalar(value));
+        }
+        match fallback {
+            Some(fallback) => Ok(ConfigNode::Scalar(fallback.to_string())),
+            None => Err(ConfigError::interpolation(
+                path,
+                format!("`${{{name}}}` is neither a key nor an environment variable"),
+            )),
+        }
+    }
+}
+
+/// Split `text` into literal text and references.
+fn parse(text: &str) -> Result<Vec<Segment<'_>>, String> {
+    let mut segments = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('$') {
+        let after = &rest[start + 1..];
+        if let Some(escaped) = after.strip_prefix("${") {
+            // `$${` -> literal `${`
+            segments.push(Segment::Text(&rest[..start + 1]));
+            segments.push(Segment::Text("{"));
+            rest = escaped;
+        } else if let Some(body) = after.strip_prefix('{') {
+            let end = body.find('}').ok_or("unterminated `${` reference")?;
+            segments.push(Segment::Text(&rest[..start]));
+            let inner = &body[..end];
+            let (name, fallback) = match inner.split_once(":-") {
+                Some((name, fallback)) => (name, Some(fallback)),
+                None => (inner, None),
+            };
+            segments.push(Segment::Reference { name: name.trim(), fallback });
+            rest = &body[end + 1..];
+        } else {
+            segments.push(Segment::Text(&rest[..start + 1]));
+            rest = after;
+        }
+    }
+    segments.push(Segment::Text(rest));
+    segments.retain(|segment| !matches!(segment, Segment::Text("")));
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(entries: &[(&str, ConfigNode)]) -> ConfigNode {
+        ConfigNode::Table(
+            entries
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect(),
+        )
+    }
+
+    fn text(value: &str) -> ConfigNode {
+        ConfigNode::String(value.into())
+    }
+
+    fn env(name: &str) -> Option<String> {
+        (name == "DB_HOST").then(|| "db.internal".to_string())
+    }
+
+    #[test]
+    fn interpolate_should_resolve_keys_and_env_vars() {
+        let root = table(&[
+            ("database", table(&[("port", ConfigNode::Integer(5432))])),
+            ("url", text("postgres://${DB_HOST}:${database.port}/app")),
+            ("port", text("${database.port}")),
+        ]);
+
+        let resolved = interpolate(&root, &env).unwrap();
+        assert_eq!(resolved.get_path("url"), Some(&text("postgres://db.internal:5432/app")));
+        assert_eq!(resolved.get_path("port"), Some(&ConfigNode::Integer(5432)));
+    }
+
+    #[test]
+    fn interpolate_should_follow_chains_and_fallbacks() {
+        let root = table(&[
+            ("a", text("${b}/x")),
+            ("b", text("${MISSING:-/srv}")),
+            ("literal", text("cost: $5, keep $${HOME}")),
+        ]);
+
+        let resolved = interpolate(&root, &env).unwrap();
+        assert_eq!(resolved.get_path("a"), Some(&text("/srv/x")));
+        assert_eq!(resolved.get_path("literal"), Some(&text("cost: $5, keep ${HOME}")));
+    }
+
+    #[test]
+    fn interpolate_should_report_unresolved_references() {
+        let root = table(&[("servers", ConfigNode::Array(vec![text("${NOPE}")]))]);
+
+        let err = interpolate(&root, &env).unwrap_err();
+        assert!(matches!(err, ConfigError::Interpolation { field, .. } if field == "servers[0]"));
+    }
+
+    #[test]
+    fn interpolate_should_detect_cycles() {
+        let root = table(&[("a", text("${b}")), ("b", text("x${a}"))]);
+
+        let err = interpolate(&root, &env).unwrap_err();
+        let ConfigError::Interpolation { reason, .. } = err else {
+            panic!("expected interpolation error");
+        };
+        assert_eq!(reason, "reference cycle a -> b -> a");
+    }
+
+    #[test]
+    fn interpolate_should_reject_tables_inside_text() {
+        let root = table(&[("db", table(&[])), ("url", text("x${db}"))]);
+        assert!(interpolate(&root, &env).is_err());
+    }
+
+    #[test]
+    fn interpolate_should_keep_raw_values_out_of_errors() {
+        let root = table(&[("password", text("hunter2${"))]);
+
+        let err = interpolate(&root, &env).unwrap_err();
+        assert!(!err.to_string().contains("hunter2"));
+    }
+}
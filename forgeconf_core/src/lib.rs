@@ -1,14 +1,26 @@
 //! Runtime primitives exposed to the macro-generated code.
 
+mod context;
 mod error;
+mod interpolate;
 mod node;
 mod parser;
+mod paths;
+mod probe;
+mod profile;
+mod provenance;
+mod reload;
+pub mod schema;
+mod secret;
 mod source;
+mod span;
+mod units;
 #[cfg(feature = "validators")]
 pub mod validators;
 
+pub use context::LoadContext;
 pub use error::ConfigError;
-pub use node::{ConfigNode, FromNode};
+pub use node::{Coercion, ConfigNode, FromNode, ToNode};
 #[cfg(all(feature = "parse", feature = "json"))]
 pub use parser::parse_json;
 #[cfg(feature = "parse")]
@@ -18,6 +30,29 @@ pub use parser::parse_toml;
 #[cfg(all(feature = "parse", feature = "yaml"))]
 pub use parser::parse_yaml;
 pub use parser::{FileFormat, load_from_path};
+pub use paths::PathOptions;
+pub use provenance::{Explained, Origin, Provenance};
+pub use reload::{BuildFn, ReloadEvent, ReloadHandle, Reloadable};
+pub use secret::Secret;
 #[cfg(feature = "cli")]
 pub use source::{CliArgsSource, CliArguments};
-pub use source::{ConfigBuilder, ConfigFile, ConfigSource, merge_nodes};
+pub use source::{
+    ConfigBuilder,
+    ConfigDir,
+    ConfigFile,
+    ConfigSource,
+    EnvSource,
+    Layers,
+    MergeStrategy,
+    merge_nodes,
+    merge_nodes_with,
+};
+pub use span::{SourceDocument, SourceMap};
+pub use units::ByteSize;
+
+/// Items used by the code `#[forgeconf]` generates; not part of the public
+/// API.
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::probe::{NodeProbe, ViaToNode, WithoutToNode};
+}
@@ -1,9 +1,17 @@
 use std::any::type_name;
-use std::collections::BTreeMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt::{self, Display, Formatter};
+use std::hash::Hash;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::num::{NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU128, NonZeroUsize};
+use std::path::PathBuf;
+use std::rc::Rc;
 use std::str::FromStr;
+use std::sync::Arc;
 
-use crate::ConfigError;
+use crate::secret::REDACTED;
+use crate::{ConfigError, FileFormat, LoadContext, MergeStrategy, PathOptions, parser, schema};
 
 /// Representation of a configuration tree.
 #[derive(Clone, Debug, PartialEq)]
@@ -12,8 +20,19 @@ pub enum ConfigNode {
     Table(BTreeMap<String, ConfigNode>),
     /// An ordered list of child nodes.
     Array(Vec<ConfigNode>),
-    /// A leaf value stored as a string.
+    /// An untyped leaf stored as text, as produced by environment variables
+    /// and CLI flags. Always coerced into the target type on demand.
     Scalar(String),
+    /// A string leaf.
+    String(String),
+    /// An integer leaf.
+    Integer(i64),
+    /// A floating point leaf.
+    Float(f64),
+    /// A boolean leaf.
+    Boolean(bool),
+    /// A date/time leaf, kept in its textual form.
+    Datetime(String),
     /// Absence of a value.
     Null,
 }
@@ -40,21 +59,206 @@ impl ConfigNode {
         }
     }
 
+    /// Returns `true` for leaf values (anything but tables, arrays and null).
+    pub fn is_leaf(&self) -> bool {
+        !matches!(self, ConfigNode::Table(_) | ConfigNode::Array(_) | ConfigNode::Null)
+    }
+
+    /// Returns the textual form of a leaf value.
+    pub fn as_text(&self) -> Option<Cow<'_, str>> {
+        match self {
+            ConfigNode::Scalar(text) | ConfigNode::String(text) | ConfigNode::Datetime(text) => {
+                Some(Cow::Borrowed(text))
+            },
+            ConfigNode::Integer(num) => Some(Cow::Owned(num.to_string())),
+            ConfigNode::Float(num) => Some(Cow::Owned(num.to_string())),
+            ConfigNode::Boolean(flag) => Some(Cow::Owned(flag.to_string())),
+            _ => None,
+        }
+    }
+
     /// Returns a textual identifier for the type of the node.
     pub fn kind(&self) -> &'static str {
         match self {
             ConfigNode::Table(_) => "table",
             ConfigNode::Array(_) => "array",
             ConfigNode::Scalar(_) => "scalar",
+            ConfigNode::String(_) => "string",
+            ConfigNode::Integer(_) => "integer",
+            ConfigNode::Float(_) => "float",
+            ConfigNode::Boolean(_) => "boolean",
+            ConfigNode::Datetime(_) => "datetime",
             ConfigNode::Null => "null",
         }
     }
+
+    /// Look up a descendant by path, using the same notation as error keys:
+    /// `database.port`, `servers[0].host`. An empty path returns `self`.
+    pub fn get_path(&self, path: &str) -> Option<&ConfigNode> {
+        if path.is_empty() {
+            return Some(self);
+        }
+
+        let mut node = self;
+        for segment in path.split('.') {
+            let (name, mut indices) = match segment.find('[') {
+                Some(start) => segment.split_at(start),
+                None => (segment, ""),
+            };
+            if !name.is_empty() {
+                node = node.as_table()?.get(name)?;
+            }
+            while let Some(rest) = indices.strip_prefix('[') {
+                let (index, tail) = rest.split_once(']')?;
+                let ConfigNode::Array(items) = node else {
+                    return None;
+                };
+                node = items.get(index.parse::<usize>().ok()?)?;
+                indices = tail;
+            }
+            if !indices.is_empty() {
+                return None;
+            }
+        }
+
+        Some(node)
+    }
+
+    /// Copy of the tree with every leaf replaced by `[redacted]`, keeping
+    /// its shape and any `Null`s.
+    pub fn redacted(&self) -> ConfigNode {
+        match self {
+            ConfigNode::Table(map) => ConfigNode::Table(
+                map.iter()
+                    .map(|(key, value)| (key.clone(), value.redacted()))
+                    .collect(),
+            ),
+            ConfigNode::Array(items) => {
+                ConfigNode::Array(items.iter().map(ConfigNode::redacted).collect())
+            },
+            ConfigNode::Null => ConfigNode::Null,
+            _ => ConfigNode::String(REDACTED.into()),
+        }
+    }
+
+    /// Turn a delimited `Scalar`, as read from an environment variable or a
+    /// CLI flag, into an `Array` of trimmed `Scalar` items.
+    ///
+    /// Values wrapped in `[...]` are read as a flat JSON array instead, so
+    /// items containing the separator can be quoted. Blank values become an
+    /// empty array. Other nodes, and malformed JSON arrays, are returned
+    /// unchanged.
+    pub fn split_list(&self, separator: &str) -> ConfigNode {
+        let ConfigNode::Scalar(text) = self else {
+            return self.clone();
+        };
+        let text = text.trim();
+        if text.is_empty() {
+            return ConfigNode::Array(Vec::new());
+        }
+        if let Some(inner) = text
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            return split_json_array(inner).map_or_else(|| self.clone(), ConfigNode::Array);
+        }
+        if separator.is_empty() {
+            return ConfigNode::Array(vec![ConfigNode::Scalar(text.to_string())]);
+        }
+        ConfigNode::Array(
+            text.split(separator)
+                .map(|item| ConfigNode::Scalar(item.trim().to_string()))
+                .collect(),
+        )
+    }
+
+    /// Render the tree as a document in `format`.
+    ///
+    /// TOML requires a table at the root and drops `Null` entries, which it
+    /// cannot represent. Fails when the format's feature is disabled.
+    pub fn to_string_as(&self, format: FileFormat) -> Result<String, ConfigError> {
+        parser::write_str(self, format)
+    }
+}
+
+/// Items of a flat JSON array body. Quoted items become `String`s, `null`
+/// becomes `Null` and anything else a `Scalar`; nested collections are
+/// rejected.
+fn split_json_array(inner: &str) -> Option<Vec<ConfigNode>> {
+    let mut items = Vec::new();
+    let mut rest = inner.trim_start();
+    if rest.is_empty() {
+        return Some(items);
+    }
+    loop {
+        let (item, tail) = match rest.strip_prefix('"') {
+            Some(quoted) => {
+                let (text, tail) = unquote_json(quoted)?;
+                (ConfigNode::String(text), tail)
+            },
+            None => {
+                let end = rest.find(',').unwrap_or(rest.len());
+                let (bare, tail) = rest.split_at(end);
+                let bare = bare.trim();
+                if bare.is_empty() || bare.contains(['[', ']', '{', '}', '"']) {
+                    return None;
+                }
+                let item = match bare {
+                    "null" => ConfigNode::Null,
+                    _ => ConfigNode::Scalar(bare.to_string()),
+                };
+                (item, tail)
+            },
+        };
+        items.push(item);
+        let tail = tail.trim_start();
+        if tail.is_empty() {
+            return Some(items);
+        }
+        rest = tail.strip_prefix(',')?.trim_start();
+    }
+}
+
+/// Read a JSON string body up to its closing quote, returning the unescaped
+/// text and what follows it.
+fn unquote_json(quoted: &str) -> Option<(String, &str)> {
+    let mut text = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((index, ch)) = chars.next() {
+        match ch {
+            '"' => return Some((text, &quoted[index + 1..])),
+            '\\' => {
+                let escaped = match chars.next()?.1 {
+                    'n' => '\n',
+                    't' => '\t',
+                    'r' => '\r',
+                    'u' => {
+                        let code: String = (0..4)
+                            .filter_map(|_| chars.next())
+                            .map(|(_, c)| c)
+                            .collect();
+                        char::from_u32(u32::from_str_radix(&code, 16).ok()?)?
+                    },
+                    other @ ('"' | '\\' | '/') => other,
+                    _ => return None,
+                };
+                text.push(escaped);
+            },
+            other => text.push(other),
+        }
+    }
+    None
 }
 
 impl Display for ConfigNode {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
-            ConfigNode::Scalar(value) => write!(f, "{value}"),
+            ConfigNode::Scalar(value) | ConfigNode::String(value) | ConfigNode::Datetime(value) => {
+                write!(f, "{value}")
+            },
+            ConfigNode::Integer(value) => write!(f, "{value}"),
+            ConfigNode::Float(value) => write!(f, "{value}"),
+            ConfigNode::Boolean(value) => write!(f, "{value}"),
             ConfigNode::Null => write!(f, "null"),
             ConfigNode::Array(items) => {
                 let mut first = true;
@@ -84,11 +288,75 @@ impl Display for ConfigNode {
     }
 }
 
+/// Controls how typed leaves are converted by [`FromNode`] implementations.
+///
+/// Untyped [`ConfigNode::Scalar`] leaves (env vars, CLI flags) carry no type
+/// information and are parsed in both modes.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Coercion {
+    /// Accept any leaf whose textual form parses into the target type, so a
+    /// quoted `"8080"` still loads into a `u16`.
+    #[default]
+    Lenient,
+    /// Reject typed leaves of the wrong kind, e.g. a string for an integer.
+    Strict,
+}
+
 /// Trait implemented by types that can be created from a [`ConfigNode`].
 pub trait FromNode: Sized {
     /// Convert `node` into `Self`, using `key` as the field name in error
     /// messages.
     fn from_node(node: &ConfigNode, key: &str) -> Result<Self, ConfigError>;
+
+    /// Convert `node` using the given [`Coercion`] mode.
+    ///
+    /// Types without a notion of strictness keep the default, which ignores
+    /// the mode and defers to [`FromNode::from_node`].
+    fn from_node_with(
+        node: &ConfigNode,
+        key: &str,
+        _coercion: Coercion,
+    ) -> Result<Self, ConfigError> {
+        Self::from_node(node, key)
+    }
+
+    /// Convert `node` as part of a load sharing `cx`.
+    ///
+    /// `#[forgeconf]` types and collections override this to pass the
+    /// context on to their fields and items, so a collecting load reports
+    /// every failure inside them. The default defers to
+    /// [`FromNode::from_node_with`].
+    fn from_node_in(
+        node: &ConfigNode,
+        key: &str,
+        coercion: Coercion,
+        _cx: &mut LoadContext<'_>,
+    ) -> Result<Self, ConfigError> {
+        Self::from_node_with(node, key, coercion)
+    }
+
+    /// JSON Schema of the values [`FromNode::from_node`] accepts; see
+    /// [`schema`](crate::schema). Defaults to accepting anything.
+    fn json_schema() -> ConfigNode {
+        schema::any()
+    }
+
+    /// How arrays inside values of this type combine across sources, keyed
+    /// by path relative to the value; see
+    /// [`ConfigBuilder::merge_strategy`](crate::ConfigBuilder::merge_strategy).
+    /// `#[forgeconf]` structs list their `#[field(merge = ...)]` fields, so
+    /// that they apply when the struct is a nested section. Defaults to none.
+    fn merge_strategies() -> Vec<(String, MergeStrategy)> {
+        Vec::new()
+    }
+
+    /// Path-valued fields inside values of this type, keyed by path
+    /// relative to the value; see
+    /// [`ConfigBuilder::resolve_path`](crate::ConfigBuilder::resolve_path).
+    /// Defaults to none.
+    fn path_options() -> Vec<(String, PathOptions)> {
+        Vec::new()
+    }
 }
 
 impl FromNode for ConfigNode {
@@ -99,43 +367,109 @@ impl FromNode for ConfigNode {
 
 impl FromNode for String {
     fn from_node(node: &ConfigNode, key: &str) -> Result<Self, ConfigError> {
+        Self::from_node_with(node, key, Coercion::Lenient)
+    }
+
+    fn from_node_with(
+        node: &ConfigNode,
+        key: &str,
+        coercion: Coercion,
+    ) -> Result<Self, ConfigError> {
         match node {
-            ConfigNode::Scalar(value) => Ok(value.clone()),
             ConfigNode::Null => Err(ConfigError::missing(key)),
+            ConfigNode::Scalar(value) | ConfigNode::String(value) | ConfigNode::Datetime(value) => {
+                Ok(value.clone())
+            },
+            leaf if leaf.is_leaf() && coercion == Coercion::Lenient => {
+                Ok(leaf.as_text().unwrap_or_default().into_owned())
+            },
             other => Err(ConfigError::mismatch(key, "string", other.kind())),
         }
     }
+
+    fn json_schema() -> ConfigNode {
+        schema::of_type("string")
+    }
 }
 
 impl FromNode for bool {
     fn from_node(node: &ConfigNode, key: &str) -> Result<Self, ConfigError> {
-        parse_scalar(node, key)
+        Self::from_node_with(node, key, Coercion::Lenient)
     }
+
+    fn from_node_with(
+        node: &ConfigNode,
+        key: &str,
+        coercion: Coercion,
+    ) -> Result<Self, ConfigError> {
+        parse_scalar(node, key, coercion, LeafKind::Boolean)
+    }
+
+    fn json_schema() -> ConfigNode {
+        schema::of_type("boolean")
+    }
+}
+
+macro_rules! integer_schema {
+    ($ty:ty) => {
+        schema::integer(i64::try_from(<$ty>::MIN).ok(), i64::try_from(<$ty>::MAX).ok())
+    };
+}
+
+macro_rules! number_schema {
+    ($ty:ty) => {
+        schema::of_type("number")
+    };
 }
 
 macro_rules! impl_numeric_node {
-    ($($ty:ty),* $(,)?) => {
+    ($kind:expr, $schema:ident => $($ty:ty),* $(,)?) => {
         $(
             impl FromNode for $ty {
                 fn from_node(node: &ConfigNode, key: &str) -> Result<Self, ConfigError> {
-                    parse_scalar(node, key)
+                    Self::from_node_with(node, key, Coercion::Lenient)
+                }
+
+                fn from_node_with(
+                    node: &ConfigNode,
+                    key: &str,
+                    coercion: Coercion,
+                ) -> Result<Self, ConfigError> {
+                    parse_scalar(node, key, coercion, $kind)
+                }
+
+                fn json_schema() -> ConfigNode {
+                    $schema!($ty)
                 }
             }
         )*
     };
 }
 
-impl_numeric_node!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64);
+impl_numeric_node!(LeafKind::Integer, integer_schema => i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+impl_numeric_node!(LeafKind::Float, number_schema => f32, f64);
 
 impl FromNode for char {
     fn from_node(node: &ConfigNode, key: &str) -> Result<Self, ConfigError> {
-        let string = String::from_node(node, key)?;
+        Self::from_node_with(node, key, Coercion::Lenient)
+    }
+
+    fn from_node_with(
+        node: &ConfigNode,
+        key: &str,
+        coercion: Coercion,
+    ) -> Result<Self, ConfigError> {
+        let string = String::from_node_with(node, key, coercion)?;
         let mut chars = string.chars();
         match (chars.next(), chars.next()) {
             (Some(ch), None) => Ok(ch),
             _ => Err(ConfigError::mismatch(key, "character", string)),
         }
     }
+
+    fn json_schema() -> ConfigNode {
+        schema::length(schema::of_type("string"), Some(1), Some(1))
+    }
 }
 
 impl<T> FromNode for Vec<T>
@@ -143,19 +477,48 @@ where
     T: FromNode,
 {
     fn from_node(node: &ConfigNode, key: &str) -> Result<Self, ConfigError> {
+        Self::from_node_with(node, key, Coercion::Lenient)
+    }
+
+    fn from_node_with(
+        node: &ConfigNode,
+        key: &str,
+        coercion: Coercion,
+    ) -> Result<Self, ConfigError> {
+        Self::from_node_in(node, key, coercion, &mut LoadContext::new())
+    }
+
+    fn from_node_in(
+        node: &ConfigNode,
+        key: &str,
+        coercion: Coercion,
+        cx: &mut LoadContext<'_>,
+    ) -> Result<Self, ConfigError> {
         match node {
-            ConfigNode::Array(items) => items
-                .iter()
-                .enumerate()
-                .map(|(index, item)| {
-                    let nested_key = format!("{key}[{index}]");
-                    T::from_node(item, &nested_key)
-                })
-                .collect(),
+            ConfigNode::Array(items) => cx
+                .load_all(items.iter().enumerate(), |cx, (index, item)| {
+                    T::from_node_in(item, &format!("{key}[{index}]"), coercion, cx)
+                }),
             ConfigNode::Null => Ok(Vec::new()),
+            ConfigNode::Scalar(_) => match split_items(node) {
+                list @ ConfigNode::Array(_) => Self::from_node_in(&list, key, coercion, cx),
+                other => Err(ConfigError::mismatch(key, "array", other.kind())),
+            },
             other => Err(ConfigError::mismatch(key, "array", other.kind())),
         }
     }
+
+    fn json_schema() -> ConfigNode {
+        schema::array(T::json_schema())
+    }
+
+    fn merge_strategies() -> Vec<(String, MergeStrategy)> {
+        T::merge_strategies()
+    }
+
+    fn path_options() -> Vec<(String, PathOptions)> {
+        T::path_options()
+    }
 }
 
 impl<T> FromNode for Option<T>
@@ -163,24 +526,770 @@ where
     T: FromNode,
 {
     fn from_node(node: &ConfigNode, key: &str) -> Result<Self, ConfigError> {
+        Self::from_node_with(node, key, Coercion::Lenient)
+    }
+
+    fn from_node_with(
+        node: &ConfigNode,
+        key: &str,
+        coercion: Coercion,
+    ) -> Result<Self, ConfigError> {
+        match node {
+            ConfigNode::Null => Ok(None),
+            other => T::from_node_with(other, key, coercion).map(Some),
+        }
+    }
+
+    fn from_node_in(
+        node: &ConfigNode,
+        key: &str,
+        coercion: Coercion,
+        cx: &mut LoadContext<'_>,
+    ) -> Result<Self, ConfigError> {
         match node {
             ConfigNode::Null => Ok(None),
-            other => T::from_node(other, key).map(Some),
+            other => T::from_node_in(other, key, coercion, cx).map(Some),
+        }
+    }
+
+    fn json_schema() -> ConfigNode {
+        schema::nullable(T::json_schema())
+    }
+
+    fn merge_strategies() -> Vec<(String, MergeStrategy)> {
+        T::merge_strategies()
+    }
+
+    fn path_options() -> Vec<(String, PathOptions)> {
+        T::path_options()
+    }
+}
+
+impl<K, V> FromNode for BTreeMap<K, V>
+where
+    K: FromStr + Ord,
+    V: FromNode,
+{
+    fn from_node(node: &ConfigNode, key: &str) -> Result<Self, ConfigError> {
+        Self::from_node_with(node, key, Coercion::Lenient)
+    }
+
+    fn from_node_with(
+        node: &ConfigNode,
+        key: &str,
+        coercion: Coercion,
+    ) -> Result<Self, ConfigError> {
+        map_entries(node, key, coercion, &mut LoadContext::new())
+    }
+
+    fn from_node_in(
+        node: &ConfigNode,
+        key: &str,
+        coercion: Coercion,
+        cx: &mut LoadContext<'_>,
+    ) -> Result<Self, ConfigError> {
+        map_entries(node, key, coercion, cx)
+    }
+
+    fn json_schema() -> ConfigNode {
+        schema::map(V::json_schema())
+    }
+}
+
+impl<K, V> FromNode for HashMap<K, V>
+where
+    K: FromStr + Eq + Hash,
+    V: FromNode,
+{
+    fn from_node(node: &ConfigNode, key: &str) -> Result<Self, ConfigError> {
+        Self::from_node_with(node, key, Coercion::Lenient)
+    }
+
+    fn from_node_with(
+        node: &ConfigNode,
+        key: &str,
+        coercion: Coercion,
+    ) -> Result<Self, ConfigError> {
+        map_entries(node, key, coercion, &mut LoadContext::new())
+    }
+
+    fn from_node_in(
+        node: &ConfigNode,
+        key: &str,
+        coercion: Coercion,
+        cx: &mut LoadContext<'_>,
+    ) -> Result<Self, ConfigError> {
+        map_entries(node, key, coercion, cx)
+    }
+
+    fn json_schema() -> ConfigNode {
+        schema::map(V::json_schema())
+    }
+}
+
+macro_rules! impl_text_node {
+    ($($ty:ty => $expected:literal, $schema:expr);* $(;)?) => {
+        $(
+            impl FromNode for $ty {
+                fn from_node(node: &ConfigNode, key: &str) -> Result<Self, ConfigError> {
+                    Self::from_node_with(node, key, Coercion::Lenient)
+                }
+
+                fn from_node_with(
+                    node: &ConfigNode,
+                    key: &str,
+                    coercion: Coercion,
+                ) -> Result<Self, ConfigError> {
+                    let text = String::from_node_with(node, key, coercion)?;
+                    text.trim()
+                        .parse()
+                        .map_err(|_| ConfigError::mismatch(key, $expected, text))
+                }
+
+                fn json_schema() -> ConfigNode {
+                    $schema
+                }
+            }
+        )*
+    };
+}
+
+impl_text_node! {
+    SocketAddr => "socket address", schema::of_type("string");
+    IpAddr => "IP address", schema::of_type("string");
+    Ipv4Addr => "IPv4 address", string_format("ipv4");
+    Ipv6Addr => "IPv6 address", string_format("ipv6");
+}
+
+fn string_format(format: &str) -> ConfigNode {
+    schema::with(schema::of_type("string"), "format", ConfigNode::String(format.to_string()))
+}
+
+impl FromNode for PathBuf {
+    fn from_node(node: &ConfigNode, key: &str) -> Result<Self, ConfigError> {
+        Self::from_node_with(node, key, Coercion::Lenient)
+    }
+
+    fn from_node_with(
+        node: &ConfigNode,
+        key: &str,
+        coercion: Coercion,
+    ) -> Result<Self, ConfigError> {
+        String::from_node_with(node, key, coercion).map(PathBuf::from)
+    }
+
+    fn json_schema() -> ConfigNode {
+        schema::of_type("string")
+    }
+}
+
+macro_rules! impl_non_zero_node {
+    ($($ty:ty => $base:ty),* $(,)?) => {
+        $(
+            impl FromNode for $ty {
+                fn from_node(node: &ConfigNode, key: &str) -> Result<Self, ConfigError> {
+                    Self::from_node_with(node, key, Coercion::Lenient)
+                }
+
+                fn from_node_with(
+                    node: &ConfigNode,
+                    key: &str,
+                    coercion: Coercion,
+                ) -> Result<Self, ConfigError> {
+                    let value = <$base>::from_node_with(node, key, coercion)?;
+                    <$ty>::new(value)
+                        .ok_or_else(|| ConfigError::mismatch(key, "non-zero integer", "0"))
+                }
+
+                fn json_schema() -> ConfigNode {
+                    schema::with(<$base>::json_schema(), "minimum", ConfigNode::Integer(1))
+                }
+            }
+        )*
+    };
+}
+
+impl_non_zero_node!(
+    NonZeroU8 => u8,
+    NonZeroU16 => u16,
+    NonZeroU32 => u32,
+    NonZeroU64 => u64,
+    NonZeroU128 => u128,
+    NonZeroUsize => usize,
+);
+
+impl<T> FromNode for BTreeSet<T>
+where
+    T: FromNode + Ord,
+{
+    fn from_node(node: &ConfigNode, key: &str) -> Result<Self, ConfigError> {
+        Self::from_node_with(node, key, Coercion::Lenient)
+    }
+
+    fn from_node_with(
+        node: &ConfigNode,
+        key: &str,
+        coercion: Coercion,
+    ) -> Result<Self, ConfigError> {
+        unique_items(node, key, coercion, &mut LoadContext::new())
+    }
+
+    fn from_node_in(
+        node: &ConfigNode,
+        key: &str,
+        coercion: Coercion,
+        cx: &mut LoadContext<'_>,
+    ) -> Result<Self, ConfigError> {
+        unique_items(node, key, coercion, cx)
+    }
+
+    fn json_schema() -> ConfigNode {
+        unique_schema(T::json_schema())
+    }
+
+    fn merge_strategies() -> Vec<(String, MergeStrategy)> {
+        T::merge_strategies()
+    }
+
+    fn path_options() -> Vec<(String, PathOptions)> {
+        T::path_options()
+    }
+}
+
+impl<T> FromNode for HashSet<T>
+where
+    T: FromNode + Eq + Hash,
+{
+    fn from_node(node: &ConfigNode, key: &str) -> Result<Self, ConfigError> {
+        Self::from_node_with(node, key, Coercion::Lenient)
+    }
+
+    fn from_node_with(
+        node: &ConfigNode,
+        key: &str,
+        coercion: Coercion,
+    ) -> Result<Self, ConfigError> {
+        unique_items(node, key, coercion, &mut LoadContext::new())
+    }
+
+    fn from_node_in(
+        node: &ConfigNode,
+        key: &str,
+        coercion: Coercion,
+        cx: &mut LoadContext<'_>,
+    ) -> Result<Self, ConfigError> {
+        unique_items(node, key, coercion, cx)
+    }
+
+    fn json_schema() -> ConfigNode {
+        unique_schema(T::json_schema())
+    }
+
+    fn merge_strategies() -> Vec<(String, MergeStrategy)> {
+        T::merge_strategies()
+    }
+
+    fn path_options() -> Vec<(String, PathOptions)> {
+        T::path_options()
+    }
+}
+
+/// Convert the items of an array node, failing at `field[index]` on the
+/// first item that repeats an earlier one.
+fn unique_items<T, S>(
+    node: &ConfigNode,
+    key: &str,
+    coercion: Coercion,
+    cx: &mut LoadContext<'_>,
+) -> Result<S, ConfigError>
+where
+    T: FromNode + PartialEq,
+    S: FromIterator<T>,
+{
+    let items = Vec::<T>::from_node_in(node, key, coercion, cx)?;
+    for (index, item) in items.iter().enumerate() {
+        if let Some(first) = items[..index].iter().position(|earlier| earlier == item) {
+            return Err(ConfigError::mismatch(
+                format!("{key}[{index}]"),
+                "unique value",
+                format!("duplicate of {key}[{first}]"),
+            ));
+        }
+    }
+    Ok(items.into_iter().collect())
+}
+
+fn unique_schema(items: ConfigNode) -> ConfigNode {
+    schema::with(schema::array(items), "uniqueItems", ConfigNode::Boolean(true))
+}
+
+impl<T, const N: usize> FromNode for [T; N]
+where
+    T: FromNode,
+{
+    fn from_node(node: &ConfigNode, key: &str) -> Result<Self, ConfigError> {
+        Self::from_node_with(node, key, Coercion::Lenient)
+    }
+
+    fn from_node_with(
+        node: &ConfigNode,
+        key: &str,
+        coercion: Coercion,
+    ) -> Result<Self, ConfigError> {
+        Self::from_node_in(node, key, coercion, &mut LoadContext::new())
+    }
+
+    fn from_node_in(
+        node: &ConfigNode,
+        key: &str,
+        coercion: Coercion,
+        cx: &mut LoadContext<'_>,
+    ) -> Result<Self, ConfigError> {
+        let items = fixed_items(node, key, N)?;
+        cx.load_all(items.iter().enumerate(), |cx, (index, item)| {
+            T::from_node_in(item, &format!("{key}[{index}]"), coercion, cx)
+        })
+        .and_then(|items: Vec<T>| {
+            items
+                .try_into()
+                .map_err(|_| ConfigError::mismatch(key, format!("array of {N} items"), "array"))
+        })
+    }
+
+    fn json_schema() -> ConfigNode {
+        schema::length(schema::array(T::json_schema()), Some(N), Some(N))
+    }
+
+    fn merge_strategies() -> Vec<(String, MergeStrategy)> {
+        T::merge_strategies()
+    }
+
+    fn path_options() -> Vec<(String, PathOptions)> {
+        T::path_options()
+    }
+}
+
+/// Split a delimited scalar that reached a list type without a
+/// `#[field(separator = ...)]`, which the generated loader applies before
+/// conversion. Shared by `Vec`, arrays and tuples so they read the same text.
+fn split_items(node: &ConfigNode) -> ConfigNode {
+    node.split_list(",")
+}
+
+/// The items of an array node, which must hold exactly `len` of them.
+/// Delimited scalars are split first, like for `Vec`.
+fn fixed_items<'n>(
+    node: &'n ConfigNode,
+    key: &str,
+    len: usize,
+) -> Result<Cow<'n, [ConfigNode]>, ConfigError> {
+    let expected = || format!("array of {len} items");
+    match node {
+        ConfigNode::Array(items) if items.len() == len => Ok(Cow::Borrowed(items)),
+        ConfigNode::Array(items) => {
+            Err(ConfigError::mismatch(key, expected(), format!("{} items", items.len())))
+        },
+        ConfigNode::Null => Err(ConfigError::missing(key)),
+        ConfigNode::Scalar(_) => match split_items(node) {
+            ConfigNode::Array(items) => fixed_items(&ConfigNode::Array(items), key, len)
+                .map(|items| Cow::Owned(items.into_owned())),
+            other => Err(ConfigError::mismatch(key, expected(), other.kind())),
+        },
+        other => Err(ConfigError::mismatch(key, expected(), other.kind())),
+    }
+}
+
+macro_rules! impl_tuple_node {
+    ($len:literal => $($name:ident $index:tt),+) => {
+        impl<$($name),+> FromNode for ($($name,)+)
+        where
+            $($name: FromNode,)+
+        {
+            fn from_node(node: &ConfigNode, key: &str) -> Result<Self, ConfigError> {
+                Self::from_node_with(node, key, Coercion::Lenient)
+            }
+
+            fn from_node_with(
+                node: &ConfigNode,
+                key: &str,
+                coercion: Coercion,
+            ) -> Result<Self, ConfigError> {
+                Self::from_node_in(node, key, coercion, &mut LoadContext::new())
+            }
+
+            #[allow(non_snake_case)]
+            fn from_node_in(
+                node: &ConfigNode,
+                key: &str,
+                coercion: Coercion,
+                cx: &mut LoadContext<'_>,
+            ) -> Result<Self, ConfigError> {
+                let items = fixed_items(node, key, $len)?;
+                let mut errors = Vec::new();
+                let values = ($(
+                    match $name::from_node_in(
+                        &items[$index],
+                        &format!("{key}[{}]", $index),
+                        coercion,
+                        cx,
+                    ) {
+                        Ok(value) => Some(value),
+                        Err(err) if cx.collects_errors() => {
+                            errors.push(err);
+                            None
+                        },
+                        Err(err) => return Err(err),
+                    },
+                )+);
+                match values {
+                    ($(Some($name),)+) => Ok(($($name,)+)),
+                    _ => Err(ConfigError::multiple(errors)),
+                }
+            }
+
+            /// Positional `prefixItems`, one per element.
+            fn json_schema() -> ConfigNode {
+                let items = ConfigNode::Array(vec![$($name::json_schema()),+]);
+                let array = schema::with(schema::of_type("array"), "prefixItems", items);
+                schema::length(array, Some($len), Some($len))
+            }
+
+            fn merge_strategies() -> Vec<(String, MergeStrategy)> {
+                [$($name::merge_strategies()),+].concat()
+            }
+
+            fn path_options() -> Vec<(String, PathOptions)> {
+                [$($name::path_options()),+].concat()
+            }
+        }
+
+        impl<$($name),+> ToNode for ($($name,)+)
+        where
+            $($name: ToNode,)+
+        {
+            fn to_node(&self) -> ConfigNode {
+                ConfigNode::Array(vec![$(self.$index.to_node()),+])
+            }
         }
+    };
+}
+
+impl_tuple_node!(2 => A 0, B 1);
+impl_tuple_node!(3 => A 0, B 1, C 2);
+impl_tuple_node!(4 => A 0, B 1, C 2, D 3);
+
+macro_rules! impl_pointer_node {
+    ($($ptr:ident),* $(,)?) => {
+        $(
+            impl<T> FromNode for $ptr<T>
+            where
+                T: FromNode,
+            {
+                fn from_node(node: &ConfigNode, key: &str) -> Result<Self, ConfigError> {
+                    Self::from_node_with(node, key, Coercion::Lenient)
+                }
+
+                fn from_node_with(
+                    node: &ConfigNode,
+                    key: &str,
+                    coercion: Coercion,
+                ) -> Result<Self, ConfigError> {
+                    T::from_node_with(node, key, coercion).map($ptr::new)
+                }
+
+                fn from_node_in(
+                    node: &ConfigNode,
+                    key: &str,
+                    coercion: Coercion,
+                    cx: &mut LoadContext<'_>,
+                ) -> Result<Self, ConfigError> {
+                    T::from_node_in(node, key, coercion, cx).map($ptr::new)
+                }
+
+                fn json_schema() -> ConfigNode {
+                    T::json_schema()
+                }
+
+                fn merge_strategies() -> Vec<(String, MergeStrategy)> {
+                    T::merge_strategies()
+                }
+
+                fn path_options() -> Vec<(String, PathOptions)> {
+                    T::path_options()
+                }
+            }
+
+            impl<T> ToNode for $ptr<T>
+            where
+                T: ToNode + ?Sized,
+            {
+                fn to_node(&self) -> ConfigNode {
+                    (**self).to_node()
+                }
+            }
+        )*
+    };
+}
+
+impl_pointer_node!(Box, Rc, Arc);
+
+/// Convert every entry of a table node, parsing keys through `FromStr` and
+/// reporting errors under `field.key`.
+fn map_entries<K, V, M>(
+    node: &ConfigNode,
+    key: &str,
+    coercion: Coercion,
+    cx: &mut LoadContext<'_>,
+) -> Result<M, ConfigError>
+where
+    K: FromStr,
+    V: FromNode,
+    M: FromIterator<(K, V)>,
+{
+    match node {
+        ConfigNode::Table(map) => cx.load_all(map, |cx, (name, value)| {
+            let nested_key = format!("{key}.{name}");
+            let parsed = name
+                .parse::<K>()
+                .map_err(|_| ConfigError::mismatch(&nested_key, type_name::<K>(), name))?;
+            let value = V::from_node_in(value, &nested_key, coercion, cx)?;
+            Ok((parsed, value))
+        }),
+        ConfigNode::Null => Ok(std::iter::empty().collect()),
+        other => Err(ConfigError::mismatch(key, "table", other.kind())),
+    }
+}
+
+/// Trait implemented by types that can be turned back into a [`ConfigNode`],
+/// the inverse of [`FromNode`].
+pub trait ToNode {
+    fn to_node(&self) -> ConfigNode;
+}
+
+impl ToNode for ConfigNode {
+    fn to_node(&self) -> ConfigNode {
+        self.clone()
+    }
+}
+
+impl ToNode for String {
+    fn to_node(&self) -> ConfigNode {
+        ConfigNode::String(self.clone())
+    }
+}
+
+impl ToNode for str {
+    fn to_node(&self) -> ConfigNode {
+        ConfigNode::String(self.to_string())
+    }
+}
+
+impl ToNode for char {
+    fn to_node(&self) -> ConfigNode {
+        ConfigNode::String(self.to_string())
+    }
+}
+
+impl ToNode for bool {
+    fn to_node(&self) -> ConfigNode {
+        ConfigNode::Boolean(*self)
+    }
+}
+
+macro_rules! impl_integer_to_node {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl ToNode for $ty {
+                /// Values outside the `i64` range are kept as text.
+                #[allow(irrefutable_let_patterns)]
+                fn to_node(&self) -> ConfigNode {
+                    if let Ok(value) = i64::try_from(*self) {
+                        ConfigNode::Integer(value)
+                    } else {
+                        ConfigNode::Scalar(self.to_string())
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_integer_to_node!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+impl ToNode for f32 {
+    fn to_node(&self) -> ConfigNode {
+        ConfigNode::Float(f64::from(*self))
     }
 }
 
-fn parse_scalar<T>(node: &ConfigNode, key: &str) -> Result<T, ConfigError>
+impl ToNode for f64 {
+    fn to_node(&self) -> ConfigNode {
+        ConfigNode::Float(*self)
+    }
+}
+
+impl<T> ToNode for Vec<T>
+where
+    T: ToNode,
+{
+    fn to_node(&self) -> ConfigNode {
+        ConfigNode::Array(self.iter().map(ToNode::to_node).collect())
+    }
+}
+
+impl<T> ToNode for Option<T>
+where
+    T: ToNode,
+{
+    fn to_node(&self) -> ConfigNode {
+        self.as_ref().map_or(ConfigNode::Null, ToNode::to_node)
+    }
+}
+
+impl<K, V> ToNode for BTreeMap<K, V>
+where
+    K: Display,
+    V: ToNode,
+{
+    fn to_node(&self) -> ConfigNode {
+        table_of(self)
+    }
+}
+
+impl<K, V> ToNode for HashMap<K, V>
+where
+    K: Display,
+    V: ToNode,
+{
+    fn to_node(&self) -> ConfigNode {
+        table_of(self)
+    }
+}
+
+macro_rules! impl_display_to_node {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl ToNode for $ty {
+                fn to_node(&self) -> ConfigNode {
+                    ConfigNode::String(self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_display_to_node!(SocketAddr, IpAddr, Ipv4Addr, Ipv6Addr);
+
+/// Paths that are not valid UTF-8 are written lossily.
+impl ToNode for PathBuf {
+    fn to_node(&self) -> ConfigNode {
+        ConfigNode::String(self.to_string_lossy().into_owned())
+    }
+}
+
+macro_rules! impl_non_zero_to_node {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl ToNode for $ty {
+                fn to_node(&self) -> ConfigNode {
+                    self.get().to_node()
+                }
+            }
+        )*
+    };
+}
+
+impl_non_zero_to_node!(NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU128, NonZeroUsize);
+
+impl<T> ToNode for BTreeSet<T>
+where
+    T: ToNode,
+{
+    fn to_node(&self) -> ConfigNode {
+        ConfigNode::Array(self.iter().map(ToNode::to_node).collect())
+    }
+}
+
+impl<T> ToNode for HashSet<T>
+where
+    T: ToNode,
+{
+    fn to_node(&self) -> ConfigNode {
+        ConfigNode::Array(self.iter().map(ToNode::to_node).collect())
+    }
+}
+
+impl<T, const N: usize> ToNode for [T; N]
+where
+    T: ToNode,
+{
+    fn to_node(&self) -> ConfigNode {
+        ConfigNode::Array(self.iter().map(ToNode::to_node).collect())
+    }
+}
+
+fn table_of<'a, K, V>(entries: impl IntoIterator<Item = (&'a K, &'a V)>) -> ConfigNode
+where
+    K: Display + 'a,
+    V: ToNode + 'a,
+{
+    ConfigNode::Table(
+        entries
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value.to_node()))
+            .collect(),
+    )
+}
+
+/// The typed leaf a primitive expects under [`Coercion::Strict`].
+#[derive(Clone, Copy)]
+enum LeafKind {
+    Boolean,
+    Integer,
+    Float,
+}
+
+impl LeafKind {
+    fn label(self) -> &'static str {
+        match self {
+            LeafKind::Boolean => "boolean",
+            LeafKind::Integer => "integer",
+            LeafKind::Float => "float",
+        }
+    }
+
+    fn accepts(self, node: &ConfigNode) -> bool {
+        matches!(
+            (self, node),
+            (_, ConfigNode::Scalar(_))
+                | (LeafKind::Boolean, ConfigNode::Boolean(_))
+                | (LeafKind::Integer, ConfigNode::Integer(_))
+                | (LeafKind::Float, ConfigNode::Float(_) | ConfigNode::Integer(_))
+        )
+    }
+}
+
+fn parse_scalar<T>(
+    node: &ConfigNode,
+    key: &str,
+    coercion: Coercion,
+    kind: LeafKind,
+) -> Result<T, ConfigError>
 where
     T: FromStr,
-    <T as FromStr>::Err: ToString,
 {
     match node {
-        ConfigNode::Scalar(value) => value
-            .trim()
-            .parse::<T>()
-            .map_err(|_| ConfigError::mismatch(key, type_name::<T>(), value.clone())),
         ConfigNode::Null => Err(ConfigError::missing(key)),
+        leaf if leaf.is_leaf() => {
+            if coercion == Coercion::Strict && !kind.accepts(leaf) {
+                return Err(ConfigError::mismatch(key, kind.label(), leaf.kind()));
+            }
+
+            let text = leaf.as_text().unwrap_or_default();
+            text.trim()
+                .parse::<T>()
+                .map_err(|_| ConfigError::mismatch(key, type_name::<T>(), text.into_owned()))
+        },
         other => Err(ConfigError::mismatch(key, type_name::<T>(), other.kind())),
     }
 }
@@ -189,6 +1298,75 @@ where
 mod tests {
     use super::*;
 
+    #[test]
+    fn get_path_should_follow_tables_and_indices() {
+        let mut server = BTreeMap::new();
+        server.insert("host".to_string(), ConfigNode::String("a".into()));
+        let mut root = BTreeMap::new();
+        root.insert("servers".to_string(), ConfigNode::Array(vec![ConfigNode::Table(server)]));
+        let node = ConfigNode::Table(root);
+
+        assert_eq!(node.get_path("servers[0].host"), Some(&ConfigNode::String("a".into())));
+        assert!(node.get_path("servers[1]").is_none());
+        assert!(node.get_path("servers.host").is_none());
+        assert_eq!(node.get_path(""), Some(&node));
+    }
+
+    #[test]
+    fn to_node_should_round_trip_through_from_node() {
+        let mut ports = BTreeMap::new();
+        ports.insert("http".to_string(), vec![80u16, 8080]);
+        let node = ports.to_node();
+
+        assert_eq!(BTreeMap::<String, Vec<u16>>::from_node(&node, "ports").unwrap(), ports);
+        assert_eq!(u128::MAX.to_node(), ConfigNode::Scalar(u128::MAX.to_string()));
+        assert_eq!(None::<u8>.to_node(), ConfigNode::Null);
+    }
+
+    #[test]
+    fn sets_should_reject_duplicates_at_their_index() {
+        let node = vec!["a", "b", "a"]
+            .into_iter()
+            .map(|item| ConfigNode::String(item.into()))
+            .collect::<Vec<_>>();
+        let err = BTreeSet::<String>::from_node(&ConfigNode::Array(node), "tags").unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::TypeMismatch { field, found, .. }
+                if field == "tags[2]" && found == "duplicate of tags[0]"
+        ));
+    }
+
+    #[test]
+    fn fixed_arrays_and_tuples_should_check_their_length() {
+        let node = vec![1u8, 2].to_node();
+        assert_eq!(<[u8; 2]>::from_node(&node, "pair").unwrap(), [1, 2]);
+        assert_eq!(<(u8, String)>::from_node(&node, "pair").unwrap(), (1, "2".to_string()));
+
+        let err = <[u8; 3]>::from_node(&node, "rgb").unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::TypeMismatch { field, expected, found, .. }
+                if field == "rgb" && expected == "array of 3 items" && found == "2 items"
+        ));
+    }
+
+    #[test]
+    fn addresses_should_report_what_they_expected() {
+        let node = ConfigNode::String("localhost".into());
+        let err = SocketAddr::from_node(&node, "listen").unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::TypeMismatch { expected, found, .. }
+                if expected == "socket address" && found == "localhost"
+        ));
+
+        let err = NonZeroU32::from_node(&ConfigNode::Integer(0), "workers").unwrap_err();
+        assert!(
+            matches!(err, ConfigError::TypeMismatch { expected, .. } if expected == "non-zero integer")
+        );
+    }
+
     #[test]
     fn vec_from_null_should_return_empty_vec() {
         let node = ConfigNode::Null;
@@ -261,8 +1439,19 @@ mod tests {
     }
 
     #[test]
-    fn vec_from_scalar_should_return_type_mismatch() {
-        let node = ConfigNode::Scalar("nope".into());
+    fn vec_from_invalid_scalar_should_report_the_item() {
+        let node = ConfigNode::Scalar("1, nope".into());
+        let err = Vec::<u8>::from_node(&node, "values").unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::TypeMismatch { field, expected, .. }
+                if field == "values[1]" && expected.contains("u8")
+        ));
+    }
+
+    #[test]
+    fn vec_from_string_leaf_should_return_type_mismatch() {
+        let node = ConfigNode::String("1,2".into());
         let err = Vec::<u8>::from_node(&node, "values").unwrap_err();
         assert!(matches!(
             err,
@@ -270,4 +1459,171 @@ mod tests {
                 if field == "values" && expected == "array"
         ));
     }
+
+    #[test]
+    fn vec_from_delimited_scalar_should_trim_items() {
+        let node = ConfigNode::Scalar(" a, b ,c ".into());
+        let parsed = Vec::<String>::from_node(&node, "hosts").unwrap();
+        assert_eq!(parsed, ["a", "b", "c"]);
+
+        let empty = Vec::<String>::from_node(&ConfigNode::Scalar("  ".into()), "hosts").unwrap();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn split_list_should_read_json_arrays() {
+        let node = ConfigNode::Scalar(r#"["a,b", 2, null, "\"q\""]"#.into());
+        assert_eq!(
+            node.split_list(";"),
+            ConfigNode::Array(vec![
+                ConfigNode::String("a,b".into()),
+                ConfigNode::Scalar("2".into()),
+                ConfigNode::Null,
+                ConfigNode::String("\"q\"".into()),
+            ])
+        );
+        assert_eq!(node.split_list(";").split_list(";"), node.split_list(";"));
+
+        let nested = ConfigNode::Scalar("[[1], 2]".into());
+        assert_eq!(nested.split_list(","), nested);
+    }
+
+    #[test]
+    fn split_list_should_use_the_given_separator() {
+        let node = ConfigNode::Scalar("1;2 ; 3".into());
+        let parsed = <[u8; 3]>::from_node(&node.split_list(";"), "ports").unwrap();
+        assert_eq!(parsed, [1, 2, 3]);
+        assert!(<(u8, u8)>::from_node(&ConfigNode::Scalar("1,2".into()), "pair").is_ok());
+    }
+
+    #[test]
+    fn integer_from_typed_integer_should_parse() {
+        let node = ConfigNode::Integer(8080);
+        let parsed = u16::from_node(&node, "port").unwrap();
+        assert_eq!(parsed, 8080);
+    }
+
+    #[test]
+    fn integer_from_out_of_range_integer_should_return_type_mismatch() {
+        let node = ConfigNode::Integer(-1);
+        let err = u16::from_node(&node, "port").unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::TypeMismatch { field, found, .. } if field == "port" && found == "-1"
+        ));
+    }
+
+    #[test]
+    fn lenient_integer_from_string_leaf_should_parse() {
+        let node = ConfigNode::String("8080".into());
+        let parsed = u16::from_node(&node, "port").unwrap();
+        assert_eq!(parsed, 8080);
+    }
+
+    #[test]
+    fn strict_integer_from_string_leaf_should_return_type_mismatch() {
+        let node = ConfigNode::String("8080".into());
+        let err = u16::from_node_with(&node, "port", Coercion::Strict).unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::TypeMismatch { field, expected, found, .. }
+                if field == "port" && expected == "integer" && found == "string"
+        ));
+    }
+
+    #[test]
+    fn strict_integer_from_untyped_scalar_should_parse() {
+        let node = ConfigNode::Scalar("8080".into());
+        let parsed = u16::from_node_with(&node, "port", Coercion::Strict).unwrap();
+        assert_eq!(parsed, 8080);
+    }
+
+    #[test]
+    fn strict_float_from_integer_leaf_should_parse() {
+        let node = ConfigNode::Integer(3);
+        let parsed = f64::from_node_with(&node, "ratio", Coercion::Strict).unwrap();
+        assert_eq!(parsed, 3.0);
+    }
+
+    #[test]
+    fn strict_string_from_integer_leaf_should_return_type_mismatch() {
+        let node = ConfigNode::Integer(42);
+        let err = String::from_node_with(&node, "name", Coercion::Strict).unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::TypeMismatch { expected, found, .. }
+                if expected == "string" && found == "integer"
+        ));
+    }
+
+    #[test]
+    fn strict_vec_should_apply_to_items() {
+        let node = ConfigNode::Array(vec![ConfigNode::Integer(1), ConfigNode::String("2".into())]);
+        let err = Vec::<u8>::from_node_with(&node, "values", Coercion::Strict).unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::TypeMismatch { field, .. } if field == "values[1]"
+        ));
+    }
+
+    #[test]
+    fn btree_map_from_table_should_convert_values() {
+        let mut map = BTreeMap::new();
+        map.insert("team".to_string(), ConfigNode::String("core".into()));
+        map.insert("tier".to_string(), ConfigNode::String("gold".into()));
+        let parsed =
+            BTreeMap::<String, String>::from_node(&ConfigNode::Table(map), "labels").unwrap();
+        assert_eq!(parsed.get("team").map(String::as_str), Some("core"));
+        assert_eq!(parsed.len(), 2);
+    }
+
+    #[test]
+    fn hash_map_should_parse_keys_with_from_str() {
+        let mut map = BTreeMap::new();
+        map.insert("8080".to_string(), ConfigNode::Integer(1));
+        map.insert("9090".to_string(), ConfigNode::Integer(2));
+        let parsed = HashMap::<u16, u8>::from_node(&ConfigNode::Table(map), "routes").unwrap();
+        assert_eq!(parsed.get(&8080), Some(&1));
+        assert_eq!(parsed.get(&9090), Some(&2));
+    }
+
+    #[test]
+    fn map_with_invalid_key_should_report_nested_key() {
+        let mut map = BTreeMap::new();
+        map.insert("http".to_string(), ConfigNode::Integer(1));
+        let err = HashMap::<u16, u8>::from_node(&ConfigNode::Table(map), "routes").unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::TypeMismatch { field, found, .. }
+                if field == "routes.http" && found == "http"
+        ));
+    }
+
+    #[test]
+    fn map_with_invalid_value_should_report_nested_key() {
+        let mut map = BTreeMap::new();
+        map.insert("api".to_string(), ConfigNode::String("abc".into()));
+        let err = BTreeMap::<String, u16>::from_node(&ConfigNode::Table(map), "ports").unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::TypeMismatch { field, .. } if field == "ports.api"
+        ));
+    }
+
+    #[test]
+    fn map_from_null_should_return_empty_map() {
+        let parsed = HashMap::<String, u8>::from_node(&ConfigNode::Null, "labels").unwrap();
+        assert!(parsed.is_empty());
+    }
+
+    #[test]
+    fn map_from_array_should_return_type_mismatch() {
+        let err =
+            BTreeMap::<String, u8>::from_node(&ConfigNode::Array(vec![]), "labels").unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::TypeMismatch { field, expected, .. }
+                if field == "labels" && expected == "table"
+        ));
+    }
 }
@@ -1,23 +1,210 @@
 use std::collections::BTreeMap;
 
 use jzon::JsonValue;
+use jzon::number::Number;
+use miette::SourceSpan;
 
+use crate::span::{index_path, join_path};
 use crate::{ConfigError, ConfigNode};
 
 /// Parse JSON content into a ConfigNode tree.
 pub fn parse(input: &str) -> Result<ConfigNode, ConfigError> {
-    let value = jzon::parse(input).map_err(|source| ConfigError::Json { source, span: None })?;
+    let value = jzon::parse(input).map_err(|source| ConfigError::Json {
+        span: error_offset(input, &source).map(SourceSpan::from),
+        source,
+        src: None,
+    })?;
     Ok(convert(value))
 }
 
+/// Byte offset of a parse error; jzon reports 1-based lines and char columns.
+fn error_offset(input: &str, err: &jzon::Error) -> Option<usize> {
+    match *err {
+        jzon::Error::UnexpectedCharacter { line, column, .. } => {
+            let start = input
+                .split_inclusive('\n')
+                .take(line.saturating_sub(1))
+                .map(str::len)
+                .sum::<usize>();
+            let offset = input[start..]
+                .char_indices()
+                .nth(column.saturating_sub(1))
+                .map_or(input.len(), |(offset, _)| start + offset);
+            Some(offset)
+        },
+        jzon::Error::UnexpectedEndOfJson => Some(input.len()),
+        _ => None,
+    }
+}
+
+/// Collect the byte span of every value in the document, keyed by path.
+///
+/// jzon does not expose positions, so this re-scans the text with a small
+/// scanner of its own. It follows the JSON grammar closely enough for valid
+/// documents (escapes in keys and strings, nesting, empty collections), and
+/// stops at the first syntax error, leaving the rest of the document without
+/// spans.
+pub fn locate(input: &str) -> BTreeMap<String, SourceSpan> {
+    let mut locator = Locator {
+        input: input.as_bytes(),
+        pos: 0,
+        spans: BTreeMap::new(),
+    };
+    // Malformed input only cuts the map short; the parse itself reports it.
+    let _ = locator.value("");
+    locator.spans
+}
+
+/// Minimal scanner recording where each value starts and ends. It assumes
+/// the document is well-formed and gives up on the first surprise.
+struct Locator<'a> {
+    input: &'a [u8],
+    pos: usize,
+    spans: BTreeMap<String, SourceSpan>,
+}
+
+impl Locator<'_> {
+    fn value(&mut self, path: &str) -> Option<()> {
+        self.skip_whitespace();
+        let start = self.pos;
+        match self.peek()? {
+            b'{' => {
+                self.pos += 1;
+                if !self.close(b'}') {
+                    loop {
+                        self.skip_whitespace();
+                        let key = self.string()?;
+                        self.skip_whitespace();
+                        self.expect(b':')?;
+                        self.value(&join_path(path, &key))?;
+                        if !self.separator(b'}')? {
+                            break;
+                        }
+                    }
+                }
+            },
+            b'[' => {
+                self.pos += 1;
+                if !self.close(b']') {
+                    let mut index = 0;
+                    loop {
+                        self.value(&index_path(path, index))?;
+                        index += 1;
+                        if !self.separator(b']')? {
+                            break;
+                        }
+                    }
+                }
+            },
+            b'"' => {
+                self.string()?;
+            },
+            _ => {
+                while self.peek().is_some_and(|byte| {
+                    !matches!(byte, b',' | b'}' | b']') && !byte.is_ascii_whitespace()
+                }) {
+                    self.pos += 1;
+                }
+            },
+        }
+
+        if !path.is_empty() {
+            self.spans
+                .insert(path.to_string(), (start, self.pos - start).into());
+        }
+        Some(())
+    }
+
+    /// Consume `,` (returning `true`) or the closing delimiter (`false`).
+    fn separator(&mut self, close: u8) -> Option<bool> {
+        self.skip_whitespace();
+        match self.peek()? {
+            b',' => {
+                self.pos += 1;
+                Some(true)
+            },
+            byte if byte == close => {
+                self.pos += 1;
+                Some(false)
+            },
+            _ => None,
+        }
+    }
+
+    /// Consume `close` if it is the next non-blank byte (an empty container).
+    fn close(&mut self, close: u8) -> bool {
+        self.skip_whitespace();
+        let found = self.peek() == Some(close);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        (self.peek()? == byte).then(|| self.pos += 1)
+    }
+
+    /// Consume a string literal and return its decoded contents.
+    fn string(&mut self) -> Option<String> {
+        self.expect(b'"')?;
+        let mut decoded = Vec::new();
+        let mut units = Vec::new();
+        loop {
+            let byte = self.peek()?;
+            self.pos += 1;
+            if byte == b'\\' && self.peek()? == b'u' {
+                self.pos += 1;
+                let hex = self.input.get(self.pos..self.pos + 4)?;
+                units.push(u16::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+                self.pos += 4;
+                continue;
+            }
+            if !units.is_empty() {
+                let text: String = char::decode_utf16(units.drain(..))
+                    .map(|unit| unit.unwrap_or(char::REPLACEMENT_CHARACTER))
+                    .collect();
+                decoded.extend_from_slice(text.as_bytes());
+            }
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let escaped = self.peek()?;
+                    self.pos += 1;
+                    decoded.push(match escaped {
+                        b'b' => 0x08,
+                        b'f' => 0x0c,
+                        b'n' => b'\n',
+                        b'r' => b'\r',
+                        b't' => b'\t',
+                        other => other,
+                    });
+                },
+                other => decoded.push(other),
+            }
+        }
+        String::from_utf8(decoded).ok()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|byte| byte.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+}
+
 /// Convert a JSON value into a ConfigNode.
 fn convert(value: JsonValue) -> ConfigNode {
     match value {
         JsonValue::Null => ConfigNode::Null,
-        JsonValue::Boolean(flag) => ConfigNode::Scalar(flag.to_string()),
-        JsonValue::Number(num) => ConfigNode::Scalar(num.to_string()),
-        JsonValue::String(text) => ConfigNode::Scalar(text),
-        JsonValue::Short(short) => ConfigNode::Scalar(short.to_string()),
+        JsonValue::Boolean(flag) => ConfigNode::Boolean(flag),
+        JsonValue::Number(num) => convert_number(num),
+        JsonValue::String(text) => ConfigNode::String(text),
+        JsonValue::Short(short) => ConfigNode::String(short.to_string()),
         JsonValue::Array(items) => ConfigNode::Array(items.into_iter().map(convert).collect()),
         JsonValue::Object(map) => {
             let entries = map
@@ -29,6 +216,48 @@ fn convert(value: JsonValue) -> ConfigNode {
     }
 }
 
+/// Convert a JSON number, keeping integral values as integers.
+///
+/// Integers that do not fit into an `i64` are kept as untyped text so they
+/// can still be parsed into wider types without losing precision.
+fn convert_number(num: Number) -> ConfigNode {
+    let (positive, mantissa, exponent) = num.as_parts();
+    if exponent != 0 || num.is_nan() {
+        return ConfigNode::Float(f64::from(num));
+    }
+
+    match i64::try_from(mantissa) {
+        Ok(value) if positive => ConfigNode::Integer(value),
+        Ok(value) => ConfigNode::Integer(-value),
+        Err(_) => ConfigNode::Scalar(num.to_string()),
+    }
+}
+
+/// Render a node as a pretty-printed JSON document.
+pub fn write(node: &ConfigNode) -> String {
+    format!("{}\n", to_json(node).pretty(2))
+}
+
+fn to_json(node: &ConfigNode) -> JsonValue {
+    match node {
+        ConfigNode::Table(map) => {
+            let mut object = jzon::object::Object::new();
+            for (key, value) in map {
+                object.insert(key, to_json(value));
+            }
+            JsonValue::Object(object)
+        },
+        ConfigNode::Array(items) => JsonValue::Array(items.iter().map(to_json).collect()),
+        ConfigNode::Scalar(text) | ConfigNode::String(text) | ConfigNode::Datetime(text) => {
+            JsonValue::String(text.clone())
+        },
+        ConfigNode::Integer(num) => JsonValue::Number(Number::from(*num)),
+        ConfigNode::Float(num) => JsonValue::Number(Number::from(*num)),
+        ConfigNode::Boolean(flag) => JsonValue::Boolean(*flag),
+        ConfigNode::Null => JsonValue::Null,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,4 +334,57 @@ mod tests {
         let err = parse(input).unwrap_err();
         assert!(matches!(err, ConfigError::Json { .. }));
     }
+
+    #[test]
+    fn keeps_leaf_types() {
+        let input = r#"{"port": 8080, "quoted": "8080", "ratio": 0.5, "enabled": true}"#;
+        let node = parse(input).unwrap();
+        let table = node.as_table().unwrap();
+        assert_eq!(table.get("port"), Some(&ConfigNode::Integer(8080)));
+        assert_eq!(table.get("quoted"), Some(&ConfigNode::String("8080".into())));
+        assert_eq!(table.get("ratio"), Some(&ConfigNode::Float(0.5)));
+        assert_eq!(table.get("enabled"), Some(&ConfigNode::Boolean(true)));
+    }
+
+    #[test]
+    fn reports_parse_error_span() {
+        let input = "{\n  \"port\": 80,\n  \"hé\": x\n}";
+        let err = parse(input).unwrap_err();
+        let ConfigError::Json { span: Some(span), .. } = err else {
+            panic!("expected a json error with a span");
+        };
+        assert_eq!(&input[span.offset()..span.offset() + 1], "x");
+    }
+
+    fn snippet<'a>(input: &'a str, spans: &BTreeMap<String, SourceSpan>, path: &str) -> &'a str {
+        let span = spans.get(path).unwrap();
+        &input[span.offset()..span.offset() + span.len()]
+    }
+
+    #[test]
+    fn locates_nested_values() {
+        let input = r#"{"database": {"port": "abc", "tags": [1, {"a\u00e9": true}]}, "empty": []}"#;
+        let spans = locate(input);
+        assert_eq!(snippet(input, &spans, "database.port"), "\"abc\"");
+        assert_eq!(snippet(input, &spans, "database.tags[0]"), "1");
+        assert_eq!(snippet(input, &spans, "database.tags[1].aé"), "true");
+        assert_eq!(snippet(input, &spans, "empty"), "[]");
+    }
+
+    #[test]
+    fn locates_values_around_escapes_and_nesting() {
+        let input = "{\n  \"a\\\"}\": \"x]\\\"\",\n  \"b\" : [ [1, 2], {} ],\n  \"c\": -1.5e3\n}";
+        let spans = locate(input);
+        assert_eq!(snippet(input, &spans, "a\"}"), "\"x]\\\"\"");
+        assert_eq!(snippet(input, &spans, "b[0][1]"), "2");
+        assert_eq!(snippet(input, &spans, "b[1]"), "{}");
+        assert_eq!(snippet(input, &spans, "c"), "-1.5e3");
+    }
+
+    #[test]
+    fn write_round_trips_through_parse() {
+        let input = r#"{"name": "api", "ratio": 0.25, "hosts": ["a", "b"], "database": {"port": 5432, "password": null}}"#;
+        let node = parse(input).unwrap();
+        assert_eq!(parse(&write(&node)).unwrap(), node);
+    }
 }
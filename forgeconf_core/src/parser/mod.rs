@@ -1,6 +1,10 @@
+use std::collections::BTreeMap;
 use std::path::Path;
 use std::str::FromStr;
 
+use miette::SourceSpan;
+
+use crate::span::SourceDocument;
 use crate::{ConfigError, ConfigNode};
 
 #[cfg(feature = "json")]
@@ -57,7 +61,14 @@ pub fn load_from_path(
     path: impl AsRef<Path>,
     explicit: Option<FileFormat>,
 ) -> Result<ConfigNode, ConfigError> {
-    let path = path.as_ref();
+    read_document(path.as_ref(), explicit)?.parse()
+}
+
+/// Read a configuration file, keeping its contents around for diagnostics.
+pub(crate) fn read_document(
+    path: &Path,
+    explicit: Option<FileFormat>,
+) -> Result<SourceDocument, ConfigError> {
     let format = match explicit {
         Some(fmt) => fmt,
         None => infer_from_extension(path)?,
@@ -68,7 +79,59 @@ pub fn load_from_path(
     }
 
     let contents = std::fs::read_to_string(path)?;
-    parse_str(&contents, format)
+    Ok(SourceDocument::new(path.display().to_string(), format, contents).with_path(path))
+}
+
+/// Compute the byte span of every value in `input`, keyed by path.
+#[allow(unused_variables)]
+pub(crate) fn locate(input: &str, format: FileFormat) -> BTreeMap<String, SourceSpan> {
+    match format {
+        #[cfg(feature = "toml")]
+        FileFormat::Toml => toml::locate(input),
+        #[cfg(feature = "yaml")]
+        FileFormat::Yaml => yaml::locate(input),
+        #[cfg(feature = "json")]
+        FileFormat::Json => json::locate(input),
+        #[allow(unreachable_patterns)]
+        _ => BTreeMap::new(),
+    }
+}
+
+/// Render `node` as a document in `format`.
+#[allow(unused_variables)]
+pub(crate) fn write_str(node: &ConfigNode, format: FileFormat) -> Result<String, ConfigError> {
+    match format {
+        #[cfg(feature = "toml")]
+        FileFormat::Toml => toml::write(node),
+        #[cfg(feature = "yaml")]
+        FileFormat::Yaml => yaml::write(node),
+        #[cfg(feature = "json")]
+        FileFormat::Json => Ok(json::write(node)),
+        #[allow(unreachable_patterns)]
+        other => {
+            Err(ConfigError::UnsupportedFormat(format!("{} (feature disabled)", other.label())))
+        },
+    }
+}
+
+/// Render a single `key`/`value` entry the way it appears inside a document
+/// in `format`. Only TOML and YAML are line oriented enough to support this.
+#[allow(unused_variables)]
+pub(crate) fn write_entry(
+    key: &str,
+    node: &ConfigNode,
+    format: FileFormat,
+) -> Result<String, ConfigError> {
+    match format {
+        #[cfg(feature = "toml")]
+        FileFormat::Toml => toml::write_entry(key, node),
+        #[cfg(feature = "yaml")]
+        FileFormat::Yaml => yaml::write_entry(key, node),
+        #[allow(unreachable_patterns)]
+        other => {
+            Err(ConfigError::UnsupportedFormat(format!("{} (feature disabled)", other.label())))
+        },
+    }
 }
 
 /// Parse an in-memory string.
@@ -126,6 +189,13 @@ pub fn parse_json(input: &str) -> Result<ConfigNode, ConfigError> {
     json::parse(input)
 }
 
+/// The enabled format matching the extension of `path`, if any.
+pub(crate) fn detect_format(path: &Path) -> Option<FileFormat> {
+    infer_from_extension(path)
+        .ok()
+        .filter(|format| format_supported(*format))
+}
+
 fn infer_from_extension(path: &Path) -> Result<FileFormat, ConfigError> {
     let Some(ext) = path
         .extension()
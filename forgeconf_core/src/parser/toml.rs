@@ -1,24 +1,63 @@
 use std::collections::BTreeMap;
 
-use toml::Value;
+use miette::SourceSpan;
+use toml::de::{DeTable, DeValue};
+use toml::value::Datetime;
+use toml::{Table, Value};
 
-use crate::{ConfigError, ConfigNode};
+use crate::span::{index_path, join_path};
+use crate::{ConfigError, ConfigNode, FileFormat};
 
 /// Parse TOML content into a ConfigNode tree.
 pub fn parse(input: &str) -> Result<ConfigNode, ConfigError> {
-    let value: Value =
-        toml::from_str(input).map_err(|source| ConfigError::Toml { source, span: None })?;
+    let value: Value = toml::from_str(input).map_err(|source| ConfigError::Toml {
+        span: source.span().map(SourceSpan::from),
+        source,
+        src: None,
+    })?;
     Ok(convert(value))
 }
 
+/// Collect the byte span of every value in the document, keyed by path,
+/// using the spans the `toml` deserializer records for every key and value.
+pub fn locate(input: &str) -> BTreeMap<String, SourceSpan> {
+    let mut spans = BTreeMap::new();
+    if let Ok(table) = DeTable::parse(input) {
+        locate_table(table.get_ref(), "", &mut spans);
+    }
+    spans
+}
+
+fn locate_table(table: &DeTable<'_>, path: &str, spans: &mut BTreeMap<String, SourceSpan>) {
+    for (key, value) in table {
+        let path = join_path(path, key.get_ref());
+        spans.insert(path.clone(), value.span().into());
+        locate_value(value.get_ref(), &path, spans);
+    }
+}
+
+fn locate_value(value: &DeValue<'_>, path: &str, spans: &mut BTreeMap<String, SourceSpan>) {
+    match value {
+        DeValue::Table(table) => locate_table(table, path, spans),
+        DeValue::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                let path = index_path(path, index);
+                spans.insert(path.clone(), item.span().into());
+                locate_value(item.get_ref(), &path, spans);
+            }
+        },
+        _ => {},
+    }
+}
+
 /// Convert a TOML value into a ConfigNode.
 fn convert(value: Value) -> ConfigNode {
     match value {
-        Value::Boolean(flag) => ConfigNode::Scalar(flag.to_string()),
-        Value::Integer(num) => ConfigNode::Scalar(num.to_string()),
-        Value::Float(num) => ConfigNode::Scalar(num.to_string()),
-        Value::String(text) => ConfigNode::Scalar(text),
-        Value::Datetime(dt) => ConfigNode::Scalar(dt.to_string()),
+        Value::Boolean(flag) => ConfigNode::Boolean(flag),
+        Value::Integer(num) => ConfigNode::Integer(num),
+        Value::Float(num) => ConfigNode::Float(num),
+        Value::String(text) => ConfigNode::String(text),
+        Value::Datetime(dt) => ConfigNode::Datetime(dt.to_string()),
         Value::Array(values) => ConfigNode::Array(values.into_iter().map(convert).collect()),
         Value::Table(values) => {
             let entries = values
@@ -30,6 +69,68 @@ fn convert(value: Value) -> ConfigNode {
     }
 }
 
+/// Render a table node as a TOML document. `Null` entries are skipped.
+pub fn write(node: &ConfigNode) -> Result<String, ConfigError> {
+    let ConfigNode::Table(map) = node else {
+        return Err(ConfigError::serialize(
+            FileFormat::Toml,
+            "root",
+            format!("expected a table, found {}", node.kind()),
+        ));
+    };
+    let table = to_table(map, "")?;
+    toml::to_string(&table)
+        .map_err(|err| ConfigError::serialize(FileFormat::Toml, "root", err.to_string()))
+}
+
+/// Render `key = value` on a single line, with tables and arrays inline.
+pub fn write_entry(key: &str, node: &ConfigNode) -> Result<String, ConfigError> {
+    let value = to_value(node, key)?;
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-');
+    if bare {
+        Ok(format!("{key} = {value}"))
+    } else {
+        Ok(format!("{} = {value}", Value::String(key.to_string())))
+    }
+}
+
+fn to_table(map: &BTreeMap<String, ConfigNode>, path: &str) -> Result<Table, ConfigError> {
+    let mut table = Table::new();
+    for (key, value) in map {
+        if !matches!(value, ConfigNode::Null) {
+            table.insert(key.clone(), to_value(value, &join_path(path, key))?);
+        }
+    }
+    Ok(table)
+}
+
+fn to_value(node: &ConfigNode, path: &str) -> Result<Value, ConfigError> {
+    Ok(match node {
+        ConfigNode::Table(map) => Value::Table(to_table(map, path)?),
+        ConfigNode::Array(items) => Value::Array(
+            items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| to_value(item, &index_path(path, index)))
+                .collect::<Result<_, _>>()?,
+        ),
+        ConfigNode::Scalar(text) | ConfigNode::String(text) => Value::String(text.clone()),
+        ConfigNode::Integer(num) => Value::Integer(*num),
+        ConfigNode::Float(num) => Value::Float(*num),
+        ConfigNode::Boolean(flag) => Value::Boolean(*flag),
+        ConfigNode::Datetime(text) => match text.parse::<Datetime>() {
+            Ok(datetime) => Value::Datetime(datetime),
+            Err(_) => Value::String(text.clone()),
+        },
+        ConfigNode::Null => {
+            return Err(ConfigError::serialize(FileFormat::Toml, path, "TOML has no null value"));
+        },
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -117,5 +218,87 @@ mod tests {
             let err = parse(input).unwrap_err();
             assert!(matches!(err, ConfigError::Toml { .. }));
         }
+
+        #[test]
+        fn should_report_parse_error_span() {
+            let err = parse("port = 80\nhost = ").unwrap_err();
+            assert!(matches!(err, ConfigError::Toml { span: Some(_), .. }));
+        }
+
+        #[test]
+        fn should_keep_leaf_types() {
+            let input = r#"
+                port = 8080
+                quoted = "8080"
+                ratio = 0.5
+                enabled = true
+                since = 1979-05-27T07:32:00Z
+            "#;
+            let node = parse(input).unwrap();
+            let table = node.as_table().unwrap();
+            assert_eq!(table.get("port"), Some(&ConfigNode::Integer(8080)));
+            assert_eq!(table.get("quoted"), Some(&ConfigNode::String("8080".into())));
+            assert_eq!(table.get("ratio"), Some(&ConfigNode::Float(0.5)));
+            assert_eq!(table.get("enabled"), Some(&ConfigNode::Boolean(true)));
+            assert!(matches!(table.get("since"), Some(ConfigNode::Datetime(_))));
+        }
+    }
+
+    mod locate {
+        use super::*;
+
+        #[test]
+        fn should_return_span_of_nested_value() {
+            let input = "[database]\nport = \"abc\"\n";
+            let spans = locate(input);
+            let span = spans.get("database.port").unwrap();
+            assert_eq!(&input[span.offset()..span.offset() + span.len()], "\"abc\"");
+        }
+
+        #[test]
+        fn should_locate_quoted_dotted_and_inline_keys() {
+            let input = "\"my key\" = 1\nserver.port = 2\ndb = { host = \"h\" \
+                         }\n\n[[listeners]]\nname = \"a\"\n";
+            let spans = locate(input);
+            let snippet = |path: &str| {
+                let span = spans.get(path).unwrap();
+                &input[span.offset()..span.offset() + span.len()]
+            };
+            assert_eq!(snippet("my key"), "1");
+            assert_eq!(snippet("server.port"), "2");
+            assert_eq!(snippet("db.host"), "\"h\"");
+            assert_eq!(snippet("listeners[0].name"), "\"a\"");
+        }
+
+        #[test]
+        fn should_return_span_of_array_item() {
+            let input = "ports = [80, 443]";
+            let spans = locate(input);
+            let span = spans.get("ports[1]").unwrap();
+            assert_eq!(&input[span.offset()..span.offset() + span.len()], "443");
+        }
+    }
+
+    mod write {
+        use super::*;
+
+        #[test]
+        fn should_round_trip_typed_values() {
+            let input = "name = \"api\"\nsince = 1979-05-27T07:32:00Z\n\n[database]\nports = [80, \
+                         443]\nratio = 0.5\n";
+            let node = parse(input).unwrap();
+            assert_eq!(parse(&write(&node).unwrap()).unwrap(), node);
+        }
+
+        #[test]
+        fn should_skip_null_entries_and_reject_null_items() {
+            let mut map = BTreeMap::new();
+            map.insert("missing".to_string(), ConfigNode::Null);
+            assert_eq!(write(&ConfigNode::Table(map.clone())).unwrap(), "");
+
+            map.insert("items".to_string(), ConfigNode::Array(vec![ConfigNode::Null]));
+            let err = write(&ConfigNode::Table(map)).unwrap_err();
+            assert!(matches!(err, ConfigError::Serialize { path, .. } if path == "items[0]"));
+        }
     }
 }
@@ -1,25 +1,206 @@
 use std::collections::BTreeMap;
 
-use yaml_rust2::{Yaml, YamlLoader};
+use miette::SourceSpan;
+use yaml_rust2::parser::{MarkedEventReceiver, Parser};
+use yaml_rust2::scanner::{Marker, TScalarStyle};
+use yaml_rust2::yaml::Hash;
+use yaml_rust2::{Event, Yaml, YamlEmitter, YamlLoader};
 
-use crate::{ConfigError, ConfigNode};
+use crate::span::{index_path, join_path};
+use crate::{ConfigError, ConfigNode, FileFormat};
 
 /// Parse YAML content into a ConfigNode tree.
 /// If multiple YAML documents are present, only the first is used.
 pub fn parse(input: &str) -> Result<ConfigNode, ConfigError> {
-    let docs = YamlLoader::load_from_str(input)
-        .map_err(|source| ConfigError::Yaml { source, span: None })?;
+    let docs = YamlLoader::load_from_str(input).map_err(|source| {
+        let offset = CharCursor::default().byte_offset(input, source.marker().index());
+        ConfigError::Yaml {
+            source,
+            span: Some(offset.into()),
+            src: None,
+        }
+    })?;
     let document = docs.into_iter().next().unwrap_or(Yaml::Null);
     Ok(convert(document))
 }
 
+/// Collect the byte span of every value in the first document, keyed by path.
+///
+/// Start positions are the markers yaml-rust2 reports with each parser
+/// event; it reports no end, so lengths are recovered from the text:
+/// quoted scalars extend to their closing quote, plain scalars to the end of
+/// their text on that line, and block scalars (`|`, `>`) cover their first
+/// content line. Collections point at the key introducing them. Aliases and
+/// complex keys are not located, and later documents are ignored like they
+/// are by [`parse`].
+pub fn locate(input: &str) -> BTreeMap<String, SourceSpan> {
+    let mut locator = Locator {
+        input,
+        cursor: CharCursor::default(),
+        frames: Vec::new(),
+        spans: BTreeMap::new(),
+        finished: false,
+    };
+    // Errors only cut the map short; the parse itself reports them.
+    let _ = Parser::new_from_str(input).load(&mut locator, false);
+    locator.spans
+}
+
+struct Locator<'a> {
+    input: &'a str,
+    cursor: CharCursor,
+    frames: Vec<Frame>,
+    spans: BTreeMap<String, SourceSpan>,
+    finished: bool,
+}
+
+enum Frame {
+    Mapping {
+        path: String,
+        key: Option<(String, SourceSpan)>,
+    },
+    Sequence {
+        path: String,
+        index: usize,
+    },
+}
+
+impl Locator<'_> {
+    /// Path of the value the next event describes, along with the span of
+    /// its key when it sits in a mapping.
+    fn next_path(&mut self) -> Option<(String, Option<SourceSpan>)> {
+        match self.frames.last_mut()? {
+            Frame::Mapping { path, key } => {
+                let (key, span) = key.take()?;
+                Some((join_path(path, &key), Some(span)))
+            },
+            Frame::Sequence { path, index } => {
+                *index += 1;
+                Some((index_path(path, *index - 1), None))
+            },
+        }
+    }
+
+    fn set_key(&mut self, value: String, span: SourceSpan) {
+        if let Some(Frame::Mapping { key, .. }) = self.frames.last_mut() {
+            *key = Some((value, span));
+        }
+    }
+
+    fn is_key(&self) -> bool {
+        matches!(self.frames.last(), Some(Frame::Mapping { key: None, .. }))
+    }
+}
+
+impl MarkedEventReceiver for Locator<'_> {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        if self.finished {
+            return;
+        }
+
+        let start = self.cursor.byte_offset(self.input, mark.index());
+        match event {
+            Event::DocumentEnd => self.finished = true,
+            Event::Scalar(value, style, ..) => {
+                let len = scalar_len(&self.input[start..], &value, style);
+                if self.is_key() {
+                    self.set_key(value, (start, len).into());
+                } else if let Some((path, _)) = self.next_path() {
+                    self.spans.insert(path, (start, len).into());
+                }
+            },
+            Event::Alias(_) => {
+                self.next_path();
+            },
+            Event::MappingStart(..) | Event::SequenceStart(..) => {
+                // Complex keys are not addressable; park them under an empty
+                // key so the mapping stays in step.
+                let path = if self.is_key() {
+                    self.set_key(String::new(), start.into());
+                    String::new()
+                } else {
+                    // Block collections start at their first entry, so point
+                    // at the key that introduces them instead when there is
+                    // one.
+                    let (path, key) = self.next_path().unwrap_or_default();
+                    if !path.is_empty() {
+                        self.spans.insert(path.clone(), key.unwrap_or(start.into()));
+                    }
+                    path
+                };
+                self.frames.push(match event {
+                    Event::MappingStart(..) => Frame::Mapping { path, key: None },
+                    _ => Frame::Sequence { path, index: 0 },
+                });
+            },
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.frames.pop();
+            },
+            _ => {},
+        }
+    }
+}
+
+/// Length in bytes of the scalar starting at the beginning of `rest`.
+fn scalar_len(rest: &str, value: &str, style: TScalarStyle) -> usize {
+    let line = rest.find('\n').map_or(rest, |end| &rest[..end]);
+    match style {
+        TScalarStyle::DoubleQuoted => closing_quote(rest, '"', true),
+        TScalarStyle::SingleQuoted => closing_quote(rest, '\'', false),
+        TScalarStyle::Plain if rest.starts_with(value) => value.len(),
+        _ => line.trim_end().len(),
+    }
+}
+
+/// Offset just past the quote closing the one `rest` starts with.
+fn closing_quote(rest: &str, quote: char, backslash_escapes: bool) -> usize {
+    let mut chars = rest.char_indices().skip(1);
+    while let Some((offset, ch)) = chars.next() {
+        if backslash_escapes && ch == '\\' {
+            chars.next();
+        } else if ch == quote {
+            if !backslash_escapes && rest[offset + 1..].starts_with(quote) {
+                chars.next();
+            } else {
+                return offset + 1;
+            }
+        }
+    }
+    rest.len()
+}
+
+/// Converts the char indices reported by yaml-rust2 into byte offsets,
+/// resuming from the last position since markers mostly move forward.
+#[derive(Default)]
+struct CharCursor {
+    chars: usize,
+    bytes: usize,
+}
+
+impl CharCursor {
+    fn byte_offset(&mut self, input: &str, index: usize) -> usize {
+        if index < self.chars {
+            *self = Self::default();
+        }
+        for ch in input[self.bytes..].chars().take(index - self.chars) {
+            self.bytes += ch.len_utf8();
+            self.chars += 1;
+        }
+        self.bytes
+    }
+}
+
 /// Convert a YAML value into a ConfigNode.
 fn convert(value: Yaml) -> ConfigNode {
     match value {
         Yaml::Null | Yaml::BadValue => ConfigNode::Null,
-        Yaml::Real(text) | Yaml::String(text) => ConfigNode::Scalar(text),
-        Yaml::Boolean(flag) => ConfigNode::Scalar(flag.to_string()),
-        Yaml::Integer(num) => ConfigNode::Scalar(num.to_string()),
+        Yaml::Real(ref text) => value
+            .as_f64()
+            .map(ConfigNode::Float)
+            .unwrap_or_else(|| ConfigNode::Scalar(text.clone())),
+        Yaml::String(text) => ConfigNode::String(text),
+        Yaml::Boolean(flag) => ConfigNode::Boolean(flag),
+        Yaml::Integer(num) => ConfigNode::Integer(num),
         Yaml::Array(values) => ConfigNode::Array(values.into_iter().map(convert).collect()),
         Yaml::Hash(map) => {
             let entries = map
@@ -42,6 +223,61 @@ fn scalar_key(key: Yaml) -> String {
     }
 }
 
+/// Render a node as a YAML document.
+pub fn write(node: &ConfigNode) -> Result<String, ConfigError> {
+    let mut output = String::new();
+    YamlEmitter::new(&mut output)
+        .dump(&to_yaml(node))
+        .map_err(|err| ConfigError::serialize(FileFormat::Yaml, "root", err.to_string()))?;
+    let body = output.strip_prefix("---").unwrap_or(&output).trim_start();
+    Ok(format!("{body}\n"))
+}
+
+/// Render `key: value`, placing non-empty collections in an indented block
+/// below the key.
+pub fn write_entry(key: &str, node: &ConfigNode) -> Result<String, ConfigError> {
+    let key = write(&ConfigNode::String(key.to_string()))?;
+    let key = key.trim_end();
+    let body = write(node)?;
+    let body = body.trim_end();
+    let block = match node {
+        ConfigNode::Array(items) => !items.is_empty(),
+        ConfigNode::Table(map) => !map.is_empty(),
+        _ => false,
+    };
+    if block {
+        let lines: Vec<String> = body.lines().map(|line| format!("  {line}")).collect();
+        Ok(format!("{key}:\n{}", lines.join("\n")))
+    } else {
+        Ok(format!("{key}: {body}"))
+    }
+}
+
+fn to_yaml(node: &ConfigNode) -> Yaml {
+    match node {
+        ConfigNode::Table(map) => {
+            let mut hash = Hash::new();
+            for (key, value) in map {
+                hash.insert(Yaml::String(key.clone()), to_yaml(value));
+            }
+            Yaml::Hash(hash)
+        },
+        ConfigNode::Array(items) => Yaml::Array(items.iter().map(to_yaml).collect()),
+        ConfigNode::Scalar(text) | ConfigNode::String(text) | ConfigNode::Datetime(text) => {
+            Yaml::String(text.clone())
+        },
+        ConfigNode::Integer(num) => Yaml::Integer(*num),
+        ConfigNode::Float(num) => Yaml::Real(match num {
+            num if num.is_nan() => ".nan".into(),
+            num if num.is_infinite() && *num > 0.0 => ".inf".into(),
+            num if num.is_infinite() => "-.inf".into(),
+            num => format!("{num:?}"),
+        }),
+        ConfigNode::Boolean(flag) => Yaml::Boolean(*flag),
+        ConfigNode::Null => Yaml::Null,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,4 +341,93 @@ mod tests {
         let err = parse(input).unwrap_err();
         assert!(matches!(err, ConfigError::Yaml { .. }));
     }
+
+    #[test]
+    fn keeps_leaf_types() {
+        let input = r#"
+            port: 8080
+            quoted: "8080"
+            ratio: 0.5
+            enabled: true
+        "#;
+        let node = parse(input).unwrap();
+        let table = node.as_table().unwrap();
+        assert_eq!(table.get("port"), Some(&ConfigNode::Integer(8080)));
+        assert_eq!(table.get("quoted"), Some(&ConfigNode::String("8080".into())));
+        assert_eq!(table.get("ratio"), Some(&ConfigNode::Float(0.5)));
+        assert_eq!(table.get("enabled"), Some(&ConfigNode::Boolean(true)));
+    }
+
+    #[test]
+    fn reports_parse_error_span() {
+        let input = "héllo: [1, 2\n";
+        let err = parse(input).unwrap_err();
+        let ConfigError::Yaml { span: Some(span), .. } = err else {
+            panic!("expected a yaml error with a span");
+        };
+        assert!(input.is_char_boundary(span.offset()));
+    }
+
+    fn snippet<'a>(input: &'a str, spans: &BTreeMap<String, SourceSpan>, path: &str) -> &'a str {
+        let span = spans.get(path).unwrap();
+        &input[span.offset()..span.offset() + span.len()]
+    }
+
+    #[test]
+    fn locates_nested_values() {
+        let input =
+            "name: \"héllo\"\ndatabase:\n  port: 'a''b'\n  hosts:\n    - one\n    - two # c\n";
+        let spans = locate(input);
+        assert_eq!(snippet(input, &spans, "name"), "\"héllo\"");
+        assert_eq!(snippet(input, &spans, "database.port"), "'a''b'");
+        assert_eq!(snippet(input, &spans, "database.hosts[1]"), "two");
+        assert!(spans.contains_key("database.hosts"));
+    }
+
+    #[test]
+    fn locates_flow_collections() {
+        let input = "ports: [80, 443]\nlimits: {cpu: 2}\n";
+        let spans = locate(input);
+        assert_eq!(snippet(input, &spans, "ports[1]"), "443");
+        assert_eq!(snippet(input, &spans, "limits.cpu"), "2");
+    }
+
+    #[test]
+    fn locates_quoted_keys_and_flow_mappings() {
+        let input =
+            "\"db host\": a\n'port': 'x'\nlimits: { \"cpu\": \"2, 3\", mem: [1, {a: b}] }\n";
+        let spans = locate(input);
+        assert_eq!(snippet(input, &spans, "db host"), "a");
+        assert_eq!(snippet(input, &spans, "port"), "'x'");
+        assert_eq!(snippet(input, &spans, "limits.cpu"), "\"2, 3\"");
+        assert_eq!(snippet(input, &spans, "limits.mem[1].a"), "b");
+    }
+
+    #[test]
+    fn locates_only_the_first_document() {
+        let input = "port: 1\n---\nport: 2\nextra: 3\n";
+        let spans = locate(input);
+        assert_eq!(snippet(input, &spans, "port"), "1");
+        assert!(!spans.contains_key("extra"));
+    }
+
+    #[test]
+    fn locates_block_scalars_and_aliases() {
+        let input = "base: &base 5\ncopy: *base\ntext: |\n  line\nafter: ok\n";
+        let spans = locate(input);
+        assert_eq!(snippet(input, &spans, "base"), "5");
+        assert!(!spans.contains_key("copy"));
+        assert_eq!(snippet(input, &spans, "text"), "line");
+        assert_eq!(snippet(input, &spans, "after"), "ok");
+    }
+
+    #[test]
+    fn write_round_trips_through_parse() {
+        let input = "name: api\nratio: 1.0\nenabled: true\nhosts:\n  - a\n  - b\ndatabase:\n  \
+                     port: 5432\n  password: ~\n";
+        let node = parse(input).unwrap();
+        let written = write(&node).unwrap();
+        assert!(!written.starts_with("---"));
+        assert_eq!(parse(&written).unwrap(), node);
+    }
 }
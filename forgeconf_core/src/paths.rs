@@ -0,0 +1,179 @@
+//! Path-valued fields resolved against the file that set them.
+//!
+//! A relative `tls_cert = "certs/server.pem"` in `/etc/app/app.toml` becomes
+//! `/etc/app/certs/server.pem`, independent of the working directory. Values
+//! from sources without a file (environment variables, CLI flags) are left
+//! relative to the working directory.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::span::{index_path, join_path};
+use crate::{ConfigError, ConfigNode, SourceMap};
+
+/// How [`ConfigBuilder::resolve_path`](crate::ConfigBuilder::resolve_path)
+/// rewrites a path-valued field after merging.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PathOptions {
+    /// Join relative paths onto the directory of the file that supplied them.
+    pub relative_to_config: bool,
+    /// Expand a leading `~` and `$VAR` / `${VAR}` references first.
+    pub expand: bool,
+}
+
+/// Rewrite the string (or list of strings) at each registered path.
+///
+/// Arrays met on the way to a path are entered item by item, so a path
+/// registered for a section nested in a list applies to every entry.
+pub(crate) fn resolve_paths(
+    mut root: ConfigNode,
+    fields: &BTreeMap<String, PathOptions>,
+    sources: &SourceMap,
+    env: &dyn Fn(&str) -> Option<String>,
+) -> Result<ConfigNode, ConfigError> {
+    for (path, options) in fields {
+        let segments: Vec<&str> = path.split('.').collect();
+        let resolver = Resolver { options, sources, env };
+        resolver.resolve(&mut root, &segments, "")?;
+    }
+    Ok(root)
+}
+
+/// One registered path being applied to the merged tree.
+struct Resolver<'a> {
+    options: &'a PathOptions,
+    sources: &'a SourceMap,
+    env: &'a dyn Fn(&str) -> Option<String>,
+}
+
+impl Resolver<'_> {
+    /// Follow `segments` down from `node`, whose concrete path (with
+    /// indices) is `path`.
+    fn resolve(
+        &self,
+        node: &mut ConfigNode,
+        segments: &[&str],
+        path: &str,
+    ) -> Result<(), ConfigError> {
+        match (node, segments) {
+            (ConfigNode::Array(items), []) => {
+                // Appended items may come from different files.
+                for (index, item) in items.iter_mut().enumerate() {
+                    let path = index_path(path, index);
+                    resolve_leaf(item, &path, self.options, self.base_dir(&path), self.env)?;
+                }
+                Ok(())
+            },
+            (leaf, []) => resolve_leaf(leaf, path, self.options, self.base_dir(path), self.env),
+            (ConfigNode::Array(items), segments) => {
+                for (index, item) in items.iter_mut().enumerate() {
+                    self.resolve(item, segments, &index_path(path, index))?;
+                }
+                Ok(())
+            },
+            (ConfigNode::Table(map), [key, rest @ ..]) => match map.get_mut(*key) {
+                Some(child) => self.resolve(child, rest, &join_path(path, key)),
+                None => Ok(()),
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn base_dir(&self, path: &str) -> Option<&Path> {
+        if self.options.relative_to_config { self.sources.base_dir(path) } else { None }
+    }
+}
+
+fn resolve_leaf(
+    node: &mut ConfigNode,
+    key: &str,
+    options: &PathOptions,
+    base: Option<&Path>,
+    env: &dyn Fn(&str) -> Option<String>,
+) -> Result<(), ConfigError> {
+    let (ConfigNode::String(text) | ConfigNode::Scalar(text)) = node else {
+        return Ok(());
+    };
+    if options.expand {
+        *text = expand(text, key, env)?;
+    }
+    if let Some(base) = base
+        && Path::new(text.as_str()).is_relative()
+    {
+        *text = base.join(text.as_str()).to_string_lossy().into_owned();
+    }
+    Ok(())
+}
+
+/// Replace a leading `~` with the home directory and `$VAR` / `${VAR}` with
+/// the variable's value. A `$` not followed by a name is kept as is.
+fn expand(
+    text: &str,
+    key: &str,
+    env: &dyn Fn(&str) -> Option<String>,
+) -> Result<String, ConfigError> {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    if rest == "~" || rest.starts_with("~/") {
+        let home = env("HOME")
+            .or_else(|| env("USERPROFILE"))
+            .ok_or_else(|| ConfigError::interpolation(key, "`~` needs HOME to be set"))?;
+        out.push_str(&home);
+        rest = &rest[1..];
+    }
+
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, tail) = match after.strip_prefix('{') {
+            Some(body) => {
+                let end = body.find('}').ok_or_else(|| {
+                    ConfigError::interpolation(key, "unterminated `${` reference")
+                })?;
+                (&body[..end], &body[end + 1..])
+            },
+            None => {
+                let end = after
+                    .find(|ch: char| !ch.is_ascii_alphanumeric() && ch != '_')
+                    .unwrap_or(after.len());
+                after.split_at(end)
+            },
+        };
+        if name.is_empty() {
+            out.push('$');
+            rest = after;
+            continue;
+        }
+        let value = env(name).ok_or_else(|| {
+            ConfigError::interpolation(key, format!("environment variable `{name}` is not set"))
+        })?;
+        out.push_str(&value);
+        rest = tail;
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(name: &str) -> Option<String> {
+        match name {
+            "HOME" => Some("/home/app".into()),
+            "CERTS" => Some("/srv/certs".into()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn expand_replaces_home_and_variables() {
+        assert_eq!(expand("~/data", "dir", &env).unwrap(), "/home/app/data");
+        assert_eq!(expand("$CERTS/a.pem", "cert", &env).unwrap(), "/srv/certs/a.pem");
+        assert_eq!(expand("${CERTS}.d", "cert", &env).unwrap(), "/srv/certs.d");
+        assert_eq!(expand("a~b$", "cert", &env).unwrap(), "a~b$");
+
+        let err = expand("$MISSING/x", "cert", &env).unwrap_err();
+        assert!(matches!(err, ConfigError::Interpolation { .. }));
+    }
+}
@@ -0,0 +1,47 @@
+//! Converts a value with [`ToNode`] when its type implements it, and
+//! yields `None` otherwise, so that generated code can show defaults without
+//! requiring `ToNode` of every field type.
+//!
+//! Calling `(&NodeProbe(&value)).probe_node()` resolves to [`ViaToNode`],
+//! whose `&self` matches the receiver exactly, when `T: ToNode`; otherwise
+//! it autorefs once more and reaches [`WithoutToNode`]. Only concrete types are
+//! seen through; a bare type parameter always takes the fallback.
+
+use crate::{ConfigNode, ToNode};
+
+pub struct NodeProbe<'a, T>(pub &'a T);
+
+pub trait ViaToNode {
+    fn probe_node(&self) -> Option<ConfigNode>;
+}
+
+impl<T: ToNode> ViaToNode for NodeProbe<'_, T> {
+    fn probe_node(&self) -> Option<ConfigNode> {
+        Some(self.0.to_node())
+    }
+}
+
+pub trait WithoutToNode {
+    fn probe_node(&self) -> Option<ConfigNode>;
+}
+
+impl<T> WithoutToNode for &NodeProbe<'_, T> {
+    fn probe_node(&self) -> Option<ConfigNode> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Opaque;
+
+    // The explicit borrow is what selects between the two impls.
+    #[allow(clippy::needless_borrow)]
+    #[test]
+    fn probe_uses_to_node_when_implemented() {
+        assert_eq!((&NodeProbe(&3_u8)).probe_node(), Some(3_u8.to_node()));
+        assert_eq!((&NodeProbe(&Opaque)).probe_node(), None);
+    }
+}
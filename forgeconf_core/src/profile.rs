@@ -0,0 +1,170 @@
+//! `[default]` and `[profile.<name>]` sections.
+//!
+//! With profiles enabled, each source is flattened before merging: its
+//! top-level keys, then its `default` section, then the section of the
+//! selected profile, later ones winning. Merging per source keeps priorities
+//! intact, so an environment variable still beats `[profile.prod]` in a file.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{ConfigError, ConfigNode, merge_nodes};
+
+const DEFAULT_KEY: &str = "default";
+const PROFILE_KEY: &str = "profile";
+
+/// How the active profile is chosen.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ProfileSelector {
+    /// Set through the builder; always wins.
+    pub(crate) name: Option<String>,
+    /// Environment variable consulted when no source names a profile.
+    pub(crate) env: Option<String>,
+}
+
+impl ProfileSelector {
+    /// The active profile: the builder's choice, then a string `profile` key
+    /// from the highest priority source that has one (`--profile=prod`),
+    /// then the environment variable.
+    pub(crate) fn select<'n>(
+        &self,
+        layers: impl DoubleEndedIterator<Item = &'n ConfigNode>,
+        env: &dyn Fn(&str) -> Option<String>,
+    ) -> Option<String> {
+        if let Some(name) = &self.name {
+            return Some(name.clone());
+        }
+        let from_sources = layers.rev().find_map(|node| {
+            let value = node.as_table()?.get(PROFILE_KEY)?;
+            value.as_text().map(|name| name.into_owned())
+        });
+        from_sources.or_else(|| env(self.env.as_deref()?))
+    }
+}
+
+/// A source after applying profiles.
+pub(crate) struct Flattened {
+    pub(crate) node: ConfigNode,
+    /// Document prefixes the values came from, highest precedence first,
+    /// such as `profile.prod` and `default`.
+    pub(crate) sections: Vec<String>,
+    /// Profiles the source defines.
+    pub(crate) profiles: BTreeSet<String>,
+}
+
+/// Merge the `default` and `profile` sections of `node` into its root.
+pub(crate) fn flatten(node: ConfigNode, profile: Option<&str>) -> Flattened {
+    let ConfigNode::Table(mut root) = node else {
+        return Flattened {
+            node,
+            sections: Vec::new(),
+            profiles: BTreeSet::new(),
+        };
+    };
+
+    let default = root.remove(DEFAULT_KEY);
+    let mut profiles = match root.remove(PROFILE_KEY) {
+        Some(ConfigNode::Table(profiles)) => profiles,
+        _ => BTreeMap::new(),
+    };
+    let names = profiles.keys().cloned().collect();
+
+    let mut node = ConfigNode::Table(root);
+    let mut sections = vec![String::new()];
+    if let Some(default @ ConfigNode::Table(_)) = default {
+        node = merge_nodes(node, default);
+        sections.insert(0, DEFAULT_KEY.to_string());
+    }
+    if let Some((name, section)) = profile.and_then(|name| profiles.remove_entry(name)) {
+        node = merge_nodes(node, section);
+        sections.insert(0, format!("{PROFILE_KEY}.{name}"));
+    }
+
+    Flattened { node, sections, profiles: names }
+}
+
+/// Fail when `profile` was selected but none of the sources, which define
+/// `known` between them, has a section for it.
+pub(crate) fn check_known(profile: &str, known: &BTreeSet<String>) -> Result<(), ConfigError> {
+    if known.is_empty() || known.contains(profile) {
+        return Ok(());
+    }
+    Err(ConfigError::unknown_profile(profile, known.iter().map(String::as_str)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(entries: &[(&str, ConfigNode)]) -> ConfigNode {
+        ConfigNode::Table(
+            entries
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect(),
+        )
+    }
+
+    fn file() -> ConfigNode {
+        table(&[
+            ("name", ConfigNode::String("app".into())),
+            ("default", table(&[("port", ConfigNode::Integer(8080))])),
+            (
+                "profile",
+                table(&[
+                    ("dev", table(&[("debug", ConfigNode::Boolean(true))])),
+                    ("prod", table(&[("port", ConfigNode::Integer(80))])),
+                ]),
+            ),
+        ])
+    }
+
+    #[test]
+    fn flatten_merges_the_profile_over_the_default() {
+        let flattened = flatten(file(), Some("prod"));
+
+        assert_eq!(flattened.node.get_path("port"), Some(&ConfigNode::Integer(80)));
+        assert_eq!(flattened.node.get_path("name"), Some(&ConfigNode::String("app".into())));
+        assert!(flattened.node.get_path("debug").is_none());
+        assert!(flattened.node.get_path("profile").is_none());
+        assert_eq!(flattened.sections, ["profile.prod", "default", ""]);
+        assert_eq!(flattened.profiles.len(), 2);
+    }
+
+    #[test]
+    fn flatten_without_a_profile_uses_the_default() {
+        let flattened = flatten(file(), None);
+        assert_eq!(flattened.node.get_path("port"), Some(&ConfigNode::Integer(8080)));
+        assert_eq!(flattened.sections, ["default", ""]);
+    }
+
+    #[test]
+    fn select_prefers_the_builder_then_sources_then_env() {
+        let cli = table(&[("profile", ConfigNode::Scalar("staging".into()))]);
+        let env = |_: &str| Some("dev".to_string());
+        let selector = ProfileSelector {
+            name: None,
+            env: Some("APP_PROFILE".into()),
+        };
+
+        assert_eq!(
+            selector
+                .select([&file(), &cli].into_iter(), &env)
+                .as_deref(),
+            Some("staging")
+        );
+        assert_eq!(selector.select([&file()].into_iter(), &env).as_deref(), Some("dev"));
+
+        let explicit = ProfileSelector { name: Some("prod".into()), ..selector };
+        assert_eq!(explicit.select([&cli].into_iter(), &env).as_deref(), Some("prod"));
+    }
+
+    #[test]
+    fn check_known_reports_typos() {
+        let known = BTreeSet::from(["dev".to_string(), "prod".to_string()]);
+        assert!(check_known("prod", &known).is_ok());
+        assert!(check_known("anything", &BTreeSet::new()).is_ok());
+
+        let err = check_known("prdo", &known).unwrap_err();
+        assert!(matches!(err, ConfigError::UnknownProfile { profile, .. } if profile == "prdo"));
+    }
+}
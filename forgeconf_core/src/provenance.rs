@@ -0,0 +1,204 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Deref, DerefMut};
+
+use crate::secret::REDACTED;
+
+/// Where a configuration value came from.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Origin {
+    /// Name of the source: a file path, `env APP_PORT`, `cli --port`,
+    /// `default`, ...
+    pub source: String,
+    /// Priority of the source, for values that went through the merge.
+    pub priority: Option<u8>,
+    /// 1-based line and column inside the file, when known.
+    pub location: Option<(usize, usize)>,
+    /// The value as written in the source.
+    pub value: Option<String>,
+}
+
+impl Origin {
+    /// A value supplied by a merged source.
+    pub fn source(name: impl Into<String>, priority: u8) -> Self {
+        Self {
+            source: name.into(),
+            priority: Some(priority),
+            location: None,
+            value: None,
+        }
+    }
+
+    /// A value read from a field-level `env = "..."` override.
+    pub fn env(var: &str) -> Self {
+        Self::bare(format!("env {var}"))
+    }
+
+    /// A value read from a field-level `cli = "..."` override.
+    pub fn cli(flag: &str) -> Self {
+        Self::bare(format!("cli --{flag}"))
+    }
+
+    /// A value taken from a field's `default = ...`.
+    pub fn default_value() -> Self {
+        Self::bare("default".into())
+    }
+
+    pub fn with_location(mut self, line: usize, column: usize) -> Self {
+        self.location = Some((line, column));
+        self
+    }
+
+    pub fn with_value(mut self, value: impl Into<String>) -> Self {
+        self.value = Some(value.into());
+        self
+    }
+
+    /// Hide the recorded value, for secret fields.
+    pub fn redacted(mut self) -> Self {
+        if self.value.is_some() {
+            self.value = Some(REDACTED.into());
+        }
+        self
+    }
+
+    fn bare(source: String) -> Self {
+        Self {
+            source,
+            priority: None,
+            location: None,
+            value: None,
+        }
+    }
+}
+
+impl Display for Origin {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)?;
+        if let Some((line, column)) = self.location {
+            write!(f, ":{line}:{column}")?;
+        }
+        if let Some(priority) = self.priority {
+            write!(f, " (priority {priority})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Map from configuration path (`database.port`) to the [`Origin`] of its
+/// final value.
+///
+/// Its `Display` implementation renders an "effective configuration" report
+/// with one `path = value  # origin` line per entry.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Provenance {
+    entries: BTreeMap<String, Origin>,
+}
+
+impl Provenance {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, path: impl Into<String>, origin: Origin) {
+        self.entries.insert(path.into(), origin);
+    }
+
+    pub fn get(&self, path: &str) -> Option<&Origin> {
+        self.entries.get(path)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Origin)> {
+        self.entries
+            .iter()
+            .map(|(path, origin)| (path.as_str(), origin))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Display for Provenance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (path, origin) in &self.entries {
+            match &origin.value {
+                Some(value) => writeln!(f, "{path} = {value}  # {origin}")?,
+                None => writeln!(f, "{path}  # {origin}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A loaded configuration together with the [`Provenance`] of its values.
+///
+/// Dereferences to the configuration itself.
+#[derive(Debug)]
+pub struct Explained<T> {
+    value: T,
+    provenance: Provenance,
+}
+
+impl<T> Explained<T> {
+    pub fn new(value: T, provenance: Provenance) -> Self {
+        Self { value, provenance }
+    }
+
+    /// Where each value came from.
+    pub fn sources(&self) -> &Provenance {
+        &self.provenance
+    }
+
+    /// Render the effective configuration with the origin of every value.
+    pub fn explain(&self) -> String {
+        self.provenance.to_string()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    pub fn into_parts(self) -> (T, Provenance) {
+        (self.value, self.provenance)
+    }
+}
+
+impl<T> Deref for Explained<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Explained<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn origin_display_includes_location_and_priority() {
+        let origin = Origin::source("app.toml", 10).with_location(3, 8);
+        assert_eq!(origin.to_string(), "app.toml:3:8 (priority 10)");
+        assert_eq!(Origin::env("APP_PORT").to_string(), "env APP_PORT");
+    }
+
+    #[test]
+    fn provenance_display_lists_entries_in_path_order() {
+        let mut provenance = Provenance::new();
+        provenance.insert("port", Origin::default_value().with_value("8080"));
+        provenance.insert("database.url", Origin::env("DB_URL"));
+
+        assert_eq!(provenance.to_string(), "database.url  # env DB_URL\nport = 8080  # default\n");
+    }
+}
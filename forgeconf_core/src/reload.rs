@@ -0,0 +1,303 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+use std::{fs, thread};
+
+use crate::{ConfigBuilder, ConfigError, ConfigNode, SourceMap};
+
+/// Turns a merged tree into the typed configuration.
+pub type BuildFn<T> = Box<dyn Fn(&ConfigNode, &SourceMap) -> Result<T, ConfigError> + Send + Sync>;
+
+/// Implemented by the generated `<Struct>Loader`s so a [`ReloadHandle`] can
+/// re-run them.
+pub trait Reloadable {
+    type Config;
+
+    /// Split the loader into its sources and the conversion applied to the
+    /// merged tree.
+    fn into_parts(self) -> (ConfigBuilder, BuildFn<Self::Config>);
+}
+
+/// Published to subscribers after every reload attempt.
+#[derive(Debug)]
+pub enum ReloadEvent<T> {
+    /// The files changed and the new configuration is now current.
+    Reloaded(Arc<T>),
+    /// The files changed but failed to load or validate; the previous
+    /// configuration stays current.
+    Failed(Arc<ConfigError>),
+}
+
+impl<T> Clone for ReloadEvent<T> {
+    fn clone(&self) -> Self {
+        match self {
+            ReloadEvent::Reloaded(config) => ReloadEvent::Reloaded(Arc::clone(config)),
+            ReloadEvent::Failed(err) => ReloadEvent::Failed(Arc::clone(err)),
+        }
+    }
+}
+
+/// Keeps a configuration up to date with the files it was loaded from.
+///
+/// A background thread polls the modification time, size and contents of
+/// every file source (see [`ConfigSource::watched_paths`](crate::ConfigSource),
+/// including files pulled in by `include`) and re-runs the loader when one of
+/// them changes, appears or disappears. Valid configurations replace the
+/// [`current`](ReloadHandle::current) value; invalid ones are reported to
+/// subscribers and leave it untouched. Changes to environment variables or CLI
+/// arguments alone do not trigger a reload.
+///
+/// Dropping the handle stops the thread.
+pub struct ReloadHandle<T> {
+    shared: Arc<Shared<T>>,
+    _stop: Sender<()>,
+}
+
+struct Shared<T> {
+    builder: ConfigBuilder,
+    build: BuildFn<T>,
+    current: RwLock<Arc<T>>,
+    last_error: Mutex<Option<Arc<ConfigError>>>,
+    subscribers: Mutex<Vec<Sender<ReloadEvent<T>>>>,
+    /// Watched files and their fingerprints as of the last reload; also
+    /// serialises reloads.
+    fingerprints: Mutex<Vec<(PathBuf, Fingerprint)>>,
+}
+
+/// `None` for a missing path, otherwise its modification time, size and a
+/// hash of its contents (of its entry names, for a directory). The hash
+/// catches same-size edits made within the file system's mtime granularity.
+type Fingerprint = Option<(Option<SystemTime>, u64, u64)>;
+
+impl<T> ReloadHandle<T>
+where
+    T: Send + Sync + 'static,
+{
+    /// Load `loader` and start watching its files every `interval`.
+    ///
+    /// Fails with the error of the initial load.
+    pub fn watch<L>(loader: L, interval: Duration) -> Result<Self, ConfigError>
+    where
+        L: Reloadable<Config = T>,
+    {
+        let (builder, build) = loader.into_parts();
+        Self::spawn(builder, interval, build)
+    }
+
+    /// Like [`ReloadHandle::watch`], for a bare [`ConfigBuilder`] and the
+    /// conversion to apply to each merged tree.
+    pub fn spawn(
+        builder: ConfigBuilder,
+        interval: Duration,
+        build: BuildFn<T>,
+    ) -> Result<Self, ConfigError> {
+        let (value, sources) = builder.merge()?;
+        let initial = build(&value, &sources)?;
+        // Includes are only known once the sources have been loaded.
+        let fingerprints = fingerprint(builder.watched_paths());
+
+        let shared = Arc::new(Shared {
+            builder,
+            build,
+            current: RwLock::new(Arc::new(initial)),
+            last_error: Mutex::new(None),
+            subscribers: Mutex::new(Vec::new()),
+            fingerprints: Mutex::new(fingerprints),
+        });
+
+        let (stop, stopped) = mpsc::channel();
+        let watcher = Arc::clone(&shared);
+        thread::Builder::new()
+            .name("forgeconf-reload".into())
+            .spawn(move || poll(&watcher, &stopped, interval))
+            .map_err(ConfigError::Io)?;
+
+        Ok(Self { shared, _stop: stop })
+    }
+}
+
+impl<T> ReloadHandle<T> {
+    /// The most recent valid configuration.
+    pub fn current(&self) -> Arc<T> {
+        Arc::clone(
+            &self
+                .shared
+                .current
+                .read()
+                .unwrap_or_else(|err| err.into_inner()),
+        )
+    }
+
+    /// The error of the last reload attempt, cleared by the next successful
+    /// one.
+    pub fn last_error(&self) -> Option<Arc<ConfigError>> {
+        lock(&self.shared.last_error).clone()
+    }
+
+    /// Receive a [`ReloadEvent`] for every reload attempt from now on.
+    pub fn subscribe(&self) -> Receiver<ReloadEvent<T>> {
+        let (sender, receiver) = mpsc::channel();
+        lock(&self.shared.subscribers).push(sender);
+        receiver
+    }
+
+    /// Reload right away, whether or not the files changed.
+    pub fn reload(&self) -> ReloadEvent<T> {
+        let mut fingerprints = lock(&self.shared.fingerprints);
+        let event = self.shared.reload();
+        *fingerprints = fingerprint(self.shared.builder.watched_paths());
+        event
+    }
+}
+
+impl<T> Shared<T> {
+    fn reload(&self) -> ReloadEvent<T> {
+        let result = self
+            .builder
+            .merge()
+            .and_then(|(value, sources)| (self.build)(&value, &sources));
+
+        let event = match result {
+            Ok(config) => {
+                let config = Arc::new(config);
+                *self.current.write().unwrap_or_else(|err| err.into_inner()) = Arc::clone(&config);
+                *lock(&self.last_error) = None;
+                ReloadEvent::Reloaded(config)
+            },
+            Err(err) => {
+                let err = Arc::new(err);
+                *lock(&self.last_error) = Some(Arc::clone(&err));
+                ReloadEvent::Failed(err)
+            },
+        };
+
+        lock(&self.subscribers).retain(|subscriber| subscriber.send(event.clone()).is_ok());
+        event
+    }
+
+    /// Reload if a watched file changed since the last reload.
+    fn reload_if_changed(&self) {
+        let mut fingerprints = lock(&self.fingerprints);
+        let latest = fingerprint(fingerprints.iter().map(|(path, _)| path.clone()));
+        if *fingerprints != latest {
+            self.reload();
+            *fingerprints = fingerprint(self.builder.watched_paths());
+        }
+    }
+}
+
+fn poll<T>(shared: &Shared<T>, stopped: &Receiver<()>, interval: Duration) {
+    while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+        shared.reload_if_changed();
+    }
+}
+
+fn fingerprint(paths: impl IntoIterator<Item = PathBuf>) -> Vec<(PathBuf, Fingerprint)> {
+    paths
+        .into_iter()
+        .map(|path| {
+            let print = fingerprint_of(&path);
+            (path, print)
+        })
+        .collect()
+}
+
+fn fingerprint_of(path: &Path) -> Fingerprint {
+    let metadata = fs::metadata(path).ok()?;
+    let mut hasher = DefaultHasher::new();
+    if metadata.is_dir() {
+        let mut names: Vec<_> = fs::read_dir(path)
+            .ok()?
+            .filter_map(|entry| Some(entry.ok()?.file_name()))
+            .collect();
+        names.sort();
+        names.hash(&mut hasher);
+    } else {
+        fs::read(path).ok()?.hash(&mut hasher);
+    }
+    Some((metadata.modified().ok(), metadata.len(), hasher.finish()))
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+#[cfg(all(test, feature = "toml"))]
+mod tests {
+    use std::io::Write;
+
+    use tempfile::NamedTempFile;
+
+    use super::*;
+    use crate::{ConfigFile, FromNode};
+
+    fn port_of(value: &ConfigNode, _: &SourceMap) -> Result<u16, ConfigError> {
+        let node = value
+            .get_path("port")
+            .ok_or_else(|| ConfigError::missing("port"))?;
+        u16::from_node(node, "port")
+    }
+
+    fn write(file: &NamedTempFile, contents: &str) {
+        std::fs::write(file.path(), contents).unwrap();
+    }
+
+    fn spawn(file: &NamedTempFile, interval: Duration) -> ReloadHandle<u16> {
+        let builder = ConfigBuilder::new()
+            .add_source(ConfigFile::new(file.path()).with_format(crate::FileFormat::Toml));
+        ReloadHandle::spawn(builder, interval, Box::new(port_of)).unwrap()
+    }
+
+    #[test]
+    fn reload_should_publish_valid_changes() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "port = 8080").unwrap();
+        let handle = spawn(&file, Duration::from_secs(3600));
+        let events = handle.subscribe();
+
+        write(&file, "port = 9090\n");
+        assert!(matches!(handle.reload(), ReloadEvent::Reloaded(port) if *port == 9090));
+        assert_eq!(*handle.current(), 9090);
+        assert!(matches!(events.try_recv(), Ok(ReloadEvent::Reloaded(_))));
+    }
+
+    #[test]
+    fn reload_should_keep_previous_value_on_error() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "port = 8080").unwrap();
+        let handle = spawn(&file, Duration::from_secs(3600));
+
+        write(&file, "port = \"abc\"\n");
+        assert!(matches!(handle.reload(), ReloadEvent::Failed(_)));
+        assert_eq!(*handle.current(), 8080);
+        assert!(handle.last_error().is_some());
+    }
+
+    #[test]
+    fn fingerprint_should_notice_same_size_edits_with_the_same_mtime() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "port = 8080").unwrap();
+        let before = fingerprint_of(file.path()).unwrap();
+
+        write(&file, "port = 9090\n");
+        file.as_file().set_modified(before.0.unwrap()).unwrap();
+        let after = fingerprint_of(file.path()).unwrap();
+
+        assert_eq!((before.0, before.1), (after.0, after.1));
+        assert_ne!(before, after);
+    }
+
+    #[test]
+    fn poll_should_notice_modified_files() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "port = 8080").unwrap();
+        let handle = spawn(&file, Duration::from_millis(10));
+        let events = handle.subscribe();
+
+        write(&file, "port = 10080\n");
+        let event = events.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(matches!(event, ReloadEvent::Reloaded(port) if *port == 10080));
+    }
+}
@@ -0,0 +1,212 @@
+//! Building blocks for JSON Schema (draft 2020-12) documents.
+//!
+//! Schemas are plain [`ConfigNode`] tables, so they can be rendered with
+//! [`ConfigNode::to_string_as`]. Every [`FromNode`](crate::FromNode) type
+//! describes itself through `FromNode::json_schema`, and `#[forgeconf]`
+//! structs combine those into a document through their generated
+//! `json_schema()` method. Custom `FromNode` implementations can use these
+//! helpers to override the default, which accepts any value.
+//!
+//! [`example`] turns a schema back into an annotated example document.
+
+use std::collections::BTreeMap;
+
+use crate::ConfigNode;
+
+mod example;
+
+pub use example::example;
+
+/// The `$schema` URI of the supported draft.
+pub const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// A schema accepting any value.
+pub fn any() -> ConfigNode {
+    ConfigNode::empty_table()
+}
+
+/// `{"type": name}`.
+pub fn of_type(name: &str) -> ConfigNode {
+    with(any(), "type", ConfigNode::String(name.to_string()))
+}
+
+/// An integer schema with optional inclusive bounds.
+pub fn integer(min: Option<i64>, max: Option<i64>) -> ConfigNode {
+    let mut schema = of_type("integer");
+    if let Some(min) = min {
+        schema = with(schema, "minimum", ConfigNode::Integer(min));
+    }
+    if let Some(max) = max {
+        schema = with(schema, "maximum", ConfigNode::Integer(max));
+    }
+    schema
+}
+
+/// An array whose items match `items`.
+pub fn array(items: ConfigNode) -> ConfigNode {
+    with(of_type("array"), "items", items)
+}
+
+/// A table with arbitrary keys whose values match `values`.
+pub fn map(values: ConfigNode) -> ConfigNode {
+    with(of_type("object"), "additionalProperties", values)
+}
+
+/// A table with the given `properties`, of which `required` must be present.
+/// `additional` controls whether other keys are allowed.
+pub fn object<'r>(
+    properties: impl IntoIterator<Item = (String, ConfigNode)>,
+    required: impl IntoIterator<Item = &'r str>,
+    additional: bool,
+) -> ConfigNode {
+    let required: Vec<ConfigNode> = required
+        .into_iter()
+        .map(|key| ConfigNode::String(key.to_string()))
+        .collect();
+
+    let mut schema =
+        with(of_type("object"), "properties", ConfigNode::Table(properties.into_iter().collect()));
+    if !required.is_empty() {
+        schema = with(schema, "required", ConfigNode::Array(required));
+    }
+    if !additional {
+        schema = with(schema, "additionalProperties", ConfigNode::Boolean(false));
+    }
+    schema
+}
+
+/// Accept `schema` or `null`. Uses `anyOf`, since `null` also matches
+/// [`any`] and would fail `oneOf`.
+pub fn nullable(schema: ConfigNode) -> ConfigNode {
+    with(any(), "anyOf", ConfigNode::Array(vec![schema, of_type("null")]))
+}
+
+/// Accept exactly one of `alternatives`; a single alternative is returned
+/// as is.
+pub fn one_of(alternatives: impl IntoIterator<Item = ConfigNode>) -> ConfigNode {
+    let mut alternatives: Vec<ConfigNode> = alternatives.into_iter().collect();
+    if alternatives.len() == 1 {
+        return alternatives.remove(0);
+    }
+    with(any(), "oneOf", ConfigNode::Array(alternatives))
+}
+
+/// Restrict `schema` to the given values.
+pub fn enumerated(schema: ConfigNode, values: impl IntoIterator<Item = ConfigNode>) -> ConfigNode {
+    with(schema, "enum", ConfigNode::Array(values.into_iter().collect()))
+}
+
+/// Bound the value of a numeric `schema`. Bounds that are not numbers are
+/// ignored.
+pub fn range(schema: ConfigNode, min: ConfigNode, max: ConfigNode) -> ConfigNode {
+    let mut schema = schema;
+    for (keyword, bound) in [("minimum", min), ("maximum", max)] {
+        if matches!(bound, ConfigNode::Integer(_) | ConfigNode::Float(_)) {
+            schema = with(schema, keyword, bound);
+        }
+    }
+    schema
+}
+
+/// Bound the length of `schema`, using `minItems`, `minProperties` or
+/// `minLength` (and their `max` forms) depending on its type.
+pub fn length(schema: ConfigNode, min: Option<usize>, max: Option<usize>) -> ConfigNode {
+    let suffix = match type_of(&schema) {
+        Some("array") => "Items",
+        Some("object") => "Properties",
+        _ => "Length",
+    };
+    let mut schema = schema;
+    for (prefix, bound) in [("min", min), ("max", max)] {
+        if let Some(bound) = bound {
+            let bound = i64::try_from(bound).unwrap_or(i64::MAX);
+            schema = with(schema, &format!("{prefix}{suffix}"), ConfigNode::Integer(bound));
+        }
+    }
+    schema
+}
+
+/// Set `keyword` on `schema`. Schemas that are not tables are returned
+/// unchanged.
+pub fn with(schema: ConfigNode, keyword: &str, value: ConfigNode) -> ConfigNode {
+    match schema {
+        ConfigNode::Table(mut map) => {
+            map.insert(keyword.to_string(), value);
+            ConfigNode::Table(map)
+        },
+        other => other,
+    }
+}
+
+/// Wrap `schema` into a standalone document with `$schema` and `title`.
+pub fn document(title: &str, schema: ConfigNode) -> ConfigNode {
+    let schema = with(schema, "title", ConfigNode::String(title.to_string()));
+    with(schema, "$schema", ConfigNode::String(DIALECT.to_string()))
+}
+
+/// Insert `tag: name` into the properties of an object schema, as required
+/// by internally tagged enum variants.
+pub fn tagged(schema: ConfigNode, tag: &str, name: &str) -> ConfigNode {
+    let ConfigNode::Table(mut map) = schema else {
+        return schema;
+    };
+    let constant = with(any(), "const", ConfigNode::String(name.to_string()));
+    match map
+        .entry("properties".to_string())
+        .or_insert_with(ConfigNode::empty_table)
+    {
+        ConfigNode::Table(properties) => {
+            properties.insert(tag.to_string(), constant);
+        },
+        other => *other = ConfigNode::Table(BTreeMap::from([(tag.to_string(), constant)])),
+    }
+    let required = map
+        .entry("required".to_string())
+        .or_insert_with(|| ConfigNode::Array(Vec::new()));
+    if let ConfigNode::Array(required) = required {
+        required.insert(0, ConfigNode::String(tag.to_string()));
+    }
+    map.insert("type".to_string(), ConfigNode::String("object".to_string()));
+    ConfigNode::Table(map)
+}
+
+fn type_of(schema: &ConfigNode) -> Option<&str> {
+    match schema.as_table()?.get("type")? {
+        ConfigNode::String(name) => Some(name),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn length_uses_the_keyword_matching_the_type() {
+        let text = length(of_type("string"), Some(1), None);
+        assert_eq!(text.get_path("minLength"), Some(&ConfigNode::Integer(1)));
+
+        let list = length(array(any()), None, Some(3));
+        assert_eq!(list.get_path("maxItems"), Some(&ConfigNode::Integer(3)));
+    }
+
+    #[test]
+    fn object_lists_required_properties() {
+        let schema = object([("port".to_string(), integer(Some(0), None))], ["port"], false);
+
+        assert_eq!(schema.get_path("required[0]"), Some(&ConfigNode::String("port".into())));
+        assert_eq!(schema.get_path("additionalProperties"), Some(&ConfigNode::Boolean(false)));
+        assert_eq!(schema.get_path("properties.port.minimum"), Some(&ConfigNode::Integer(0)));
+    }
+
+    #[test]
+    fn tagged_requires_the_tag() {
+        let schema = tagged(object([], [], true), "kind", "disk");
+
+        assert_eq!(schema.get_path("required[0]"), Some(&ConfigNode::String("kind".into())));
+        assert_eq!(
+            schema.get_path("properties.kind.const"),
+            Some(&ConfigNode::String("disk".into()))
+        );
+    }
+}
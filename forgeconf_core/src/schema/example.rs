@@ -0,0 +1,240 @@
+use std::fmt::Write;
+
+use crate::parser::{write_entry, write_str};
+use crate::{ConfigError, ConfigNode, FileFormat};
+
+/// Render an annotated example document for an object `schema`.
+///
+/// Each property becomes an entry preceded by its `description` as
+/// comments. Properties with a `default` show it, required ones get a
+/// placeholder, and the rest are commented out. Nested objects become
+/// sections. JSON has no comments, so it gets the bare example values.
+pub fn example(schema: &ConfigNode, format: FileFormat) -> Result<String, ConfigError> {
+    if format == FileFormat::Json {
+        return write_str(&sample(schema, ""), format);
+    }
+    let mut out = String::new();
+    write_table(&mut out, schema, &[], format)?;
+    Ok(out)
+}
+
+struct Entry<'s> {
+    key: &'s str,
+    comments: Vec<String>,
+    body: Body<'s>,
+}
+
+enum Body<'s> {
+    Value { value: ConfigNode, commented: bool },
+    Section(&'s ConfigNode),
+}
+
+fn entries(schema: &ConfigNode) -> Vec<Entry<'_>> {
+    let Some(properties) = schema.get_path("properties").and_then(ConfigNode::as_table) else {
+        return Vec::new();
+    };
+    let required: Vec<&str> = match schema.get_path("required") {
+        Some(ConfigNode::Array(keys)) => keys
+            .iter()
+            .filter_map(|key| match key {
+                ConfigNode::String(key) => Some(key.as_str()),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let (mut values, sections): (Vec<_>, Vec<_>) = properties
+        .iter()
+        .map(|(key, property)| {
+            let mut comments: Vec<String> = keyword(property, "description")
+                .and_then(ConfigNode::as_text)
+                .map(|text| text.lines().map(str::to_string).collect())
+                .unwrap_or_default();
+            if let Some(ConfigNode::Array(values)) = keyword(property, "enum") {
+                let values: Vec<String> = values.iter().map(ToString::to_string).collect();
+                comments.push(format!("one of: {}", values.join(", ")));
+            }
+
+            let body = match keyword(property, "default") {
+                Some(ConfigNode::Null) | None if property.get_path("properties").is_some() => {
+                    Body::Section(property)
+                },
+                Some(default) if !matches!(default, ConfigNode::Null) => {
+                    Body::Value { value: default.clone(), commented: false }
+                },
+                _ => {
+                    let is_required = required.contains(&key.as_str());
+                    if is_required {
+                        comments.push("required".to_string());
+                    }
+                    Body::Value {
+                        value: sample(property, key),
+                        commented: !is_required,
+                    }
+                },
+            };
+            Entry { key, comments, body }
+        })
+        .partition(|entry| matches!(entry.body, Body::Value { .. }));
+    values.extend(sections);
+    values
+}
+
+fn write_table(
+    out: &mut String,
+    schema: &ConfigNode,
+    path: &[&str],
+    format: FileFormat,
+) -> Result<(), ConfigError> {
+    let indent = match format {
+        FileFormat::Yaml => "  ".repeat(path.len()),
+        _ => String::new(),
+    };
+
+    for (index, entry) in entries(schema).into_iter().enumerate() {
+        let is_section = matches!(entry.body, Body::Section(_));
+        if index > 0 && (is_section || !entry.comments.is_empty()) {
+            out.push('\n');
+        }
+        for comment in &entry.comments {
+            let _ = writeln!(out, "{indent}# {comment}");
+        }
+        match entry.body {
+            Body::Value { value, commented } => {
+                let line = write_entry(entry.key, &value, format)?;
+                let prefix = if commented { "# " } else { "" };
+                for line in line.lines() {
+                    let _ = writeln!(out, "{indent}{prefix}{line}");
+                }
+            },
+            Body::Section(section) => {
+                let mut nested = path.to_vec();
+                nested.push(entry.key);
+                let key = key_of(entry.key, format)?;
+                match format {
+                    FileFormat::Yaml => {
+                        let _ = writeln!(out, "{indent}{key}:");
+                    },
+                    _ => {
+                        let mut header = Vec::new();
+                        for part in &nested {
+                            header.push(key_of(part, format)?);
+                        }
+                        let _ = writeln!(out, "[{}]", header.join("."));
+                    },
+                }
+                write_table(out, section, &nested, format)?;
+            },
+        }
+    }
+    Ok(())
+}
+
+/// `key` as it appears in front of a value in `format`, quoted if needed.
+fn key_of(key: &str, format: FileFormat) -> Result<String, ConfigError> {
+    let entry = write_entry(key, &ConfigNode::Boolean(true), format)?;
+    let key = entry.trim_end_matches("true").trim_end();
+    Ok(key.trim_end_matches(['=', ':']).trim_end().to_string())
+}
+
+/// A value matching `schema`, used for required properties: its default,
+/// first allowed value, lower bound, or a `<key>` placeholder.
+fn sample(schema: &ConfigNode, key: &str) -> ConfigNode {
+    if let Some(value) = keyword(schema, "const").or_else(|| keyword(schema, "default")) {
+        return value.clone();
+    }
+    if let Some(ConfigNode::Array(values)) = keyword(schema, "enum")
+        && let Some(first) = values.first()
+    {
+        return first.clone();
+    }
+    if let Some(ConfigNode::Array(alternatives)) =
+        keyword(schema, "oneOf").or_else(|| keyword(schema, "anyOf"))
+    {
+        let alternative = alternatives
+            .iter()
+            .find(|alternative| {
+                keyword(alternative, "type") != Some(&ConfigNode::String("null".into()))
+            })
+            .or(alternatives.first());
+        if let Some(alternative) = alternative {
+            return sample(alternative, key);
+        }
+    }
+
+    let minimum = keyword(schema, "minimum").cloned();
+    match keyword(schema, "type")
+        .and_then(ConfigNode::as_text)
+        .as_deref()
+    {
+        Some("integer") => minimum.unwrap_or(ConfigNode::Integer(0)),
+        Some("number") => minimum.unwrap_or(ConfigNode::Float(0.0)),
+        Some("boolean") => ConfigNode::Boolean(false),
+        Some("array") => ConfigNode::Array(Vec::new()),
+        Some("null") => ConfigNode::Null,
+        Some("object") => {
+            let properties = schema.get_path("properties").and_then(ConfigNode::as_table);
+            let required: Vec<ConfigNode> = match schema.get_path("required") {
+                Some(ConfigNode::Array(keys)) => keys.clone(),
+                _ => Vec::new(),
+            };
+            ConfigNode::Table(
+                properties
+                    .into_iter()
+                    .flatten()
+                    .filter(|(name, property)| {
+                        required.contains(&ConfigNode::String((*name).clone()))
+                            || keyword(property, "default").is_some()
+                    })
+                    .map(|(name, property)| (name.clone(), sample(property, name)))
+                    .collect(),
+            )
+        },
+        _ => ConfigNode::String(format!("<{key}>")),
+    }
+}
+
+fn keyword<'s>(schema: &'s ConfigNode, name: &str) -> Option<&'s ConfigNode> {
+    schema.as_table()?.get(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{integer, nullable, object, of_type, with};
+
+    fn schema() -> ConfigNode {
+        let port = with(integer(Some(1), None), "default", ConfigNode::Integer(8080));
+        let host =
+            with(of_type("string"), "description", ConfigNode::String("Bind address".into()));
+        let http = object([("host".to_string(), host), ("port".to_string(), port)], ["host"], true);
+        object(
+            [("http".to_string(), http), ("motd".to_string(), nullable(of_type("string")))],
+            [],
+            true,
+        )
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_example_has_sections_comments_and_placeholders() {
+        let rendered = example(&schema(), FileFormat::Toml).unwrap();
+        assert_eq!(
+            rendered,
+            "# motd = \"<motd>\"\n\n[http]\n# Bind address\n# required\nhost = \"<host>\"\nport = \
+             8080\n"
+        );
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn yaml_example_indents_sections() {
+        let rendered = example(&schema(), FileFormat::Yaml).unwrap();
+        assert_eq!(
+            rendered,
+            "# motd: \"<motd>\"\n\nhttp:\n  # Bind address\n  # required\n  host: \"<host>\"\n  \
+             port: 8080\n"
+        );
+    }
+}
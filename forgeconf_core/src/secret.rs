@@ -0,0 +1,108 @@
+use std::fmt::{self, Debug, Display, Formatter};
+
+use crate::{Coercion, ConfigError, ConfigNode, FromNode, ToNode, schema};
+
+/// Placeholder shown instead of secret values.
+pub(crate) const REDACTED: &str = "[redacted]";
+
+/// A configuration value that must not end up in logs.
+///
+/// `Debug` and `Display` print `[redacted]`, conversion errors omit the
+/// offending value, and [`ToNode`] dumps write `[redacted]` in its place. Read
+/// the value with [`Secret::expose`].
+#[derive(Clone, Default, Eq, Hash, PartialEq)]
+pub struct Secret<T>(T);
+
+impl<T> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Access the secret value.
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    /// Unwrap the secret value.
+    pub fn into_exposed(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> Debug for Secret<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Secret({REDACTED})")
+    }
+}
+
+impl<T> Display for Secret<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T> FromNode for Secret<T>
+where
+    T: FromNode,
+{
+    fn from_node(node: &ConfigNode, key: &str) -> Result<Self, ConfigError> {
+        Self::from_node_with(node, key, Coercion::Lenient)
+    }
+
+    fn from_node_with(
+        node: &ConfigNode,
+        key: &str,
+        coercion: Coercion,
+    ) -> Result<Self, ConfigError> {
+        T::from_node_with(node, key, coercion)
+            .map(Secret)
+            .map_err(ConfigError::redact)
+    }
+
+    /// The schema of `T`, marked `writeOnly`.
+    fn json_schema() -> ConfigNode {
+        schema::with(T::json_schema(), "writeOnly", ConfigNode::Boolean(true))
+    }
+}
+
+impl<T> ToNode for Secret<T>
+where
+    T: ToNode,
+{
+    fn to_node(&self) -> ConfigNode {
+        self.0.to_node().redacted()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formatting_should_hide_the_value() {
+        let secret = Secret::new("hunter2".to_string());
+        assert_eq!(format!("{secret:?}"), "Secret([redacted])");
+        assert_eq!(secret.to_string(), "[redacted]");
+        assert_eq!(secret.expose(), "hunter2");
+    }
+
+    #[test]
+    fn from_node_errors_should_not_echo_the_value() {
+        let err =
+            Secret::<u16>::from_node(&ConfigNode::String("hunter2".into()), "pin").unwrap_err();
+        assert!(matches!(err, ConfigError::TypeMismatch { found, .. } if found == REDACTED));
+    }
+
+    #[test]
+    fn to_node_should_redact_leaves() {
+        let secret = Secret::new(vec!["a".to_string()]);
+        assert_eq!(secret.to_node(), ConfigNode::Array(vec![ConfigNode::String(REDACTED.into())]));
+        assert_eq!(Secret::new(None::<String>).to_node(), ConfigNode::Null);
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::BTreeMap;
 use std::env;
 
-use super::ConfigSource;
+use super::{ConfigSource, insert_segments};
 use crate::{ConfigError, ConfigNode};
 
 /// A `ConfigSource` backed by a pre-parsed flat map of ke -> value strings.
@@ -36,6 +36,10 @@ impl ConfigSource for CliArgsSource {
         self.priority
     }
 
+    fn name(&self) -> String {
+        "cli".into()
+    }
+
     fn load(&self) -> Result<ConfigNode, ConfigError> {
         let mut tree = BTreeMap::new();
         for (key, value) in &self.args {
@@ -87,6 +91,10 @@ impl ConfigSource for CliArguments {
         self.priority
     }
 
+    fn name(&self) -> String {
+        "cli".into()
+    }
+
     fn load(&self) -> Result<ConfigNode, ConfigError> {
         let mut tree = BTreeMap::new();
         for arg in self.args() {
@@ -121,31 +129,6 @@ fn insert_path(tree: &mut BTreeMap<String, ConfigNode>, path: &str, value: &str)
     insert_segments(tree, &segments, value);
 }
 
-fn insert_segments(tree: &mut BTreeMap<String, ConfigNode>, segments: &[&str], value: &str) {
-    if let Some((head, tail)) = segments.split_first() {
-        if tail.is_empty() {
-            tree.insert((*head).to_string(), ConfigNode::Scalar(value.to_owned()));
-        } else {
-            let branch = tree
-                .entry((*head).to_string())
-                .or_insert_with(ConfigNode::empty_table);
-            let map = as_table(branch);
-            insert_segments(map, tail, value);
-        }
-    }
-}
-
-fn as_table(node: &mut ConfigNode) -> &mut BTreeMap<String, ConfigNode> {
-    if !matches!(node, ConfigNode::Table(_)) {
-        *node = ConfigNode::empty_table();
-    }
-
-    match node {
-        ConfigNode::Table(map) => map,
-        _ => unreachable!("ensured above"),
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
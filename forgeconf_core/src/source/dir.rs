@@ -0,0 +1,128 @@
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::include::{load_with_includes, supported_files};
+use super::{ConfigSource, Layers, merge_nodes};
+use crate::{ConfigError, ConfigNode};
+
+/// Source that loads every configuration file in a directory, such as
+/// `conf.d/`.
+///
+/// Files are read in file name order, so `20-cache.toml` overrides
+/// `10-base.yaml`. Only files whose extension names an enabled format are
+/// loaded. With [`ConfigDir::with_includes`], each may use `include` like a
+/// [`ConfigFile`](super::ConfigFile).
+pub struct ConfigDir {
+    path: PathBuf,
+    priority: u8,
+    optional: bool,
+    includes: bool,
+    /// Files read by the last load.
+    loaded: Mutex<Vec<PathBuf>>,
+}
+
+impl ConfigDir {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            priority: 10,
+            optional: false,
+            includes: false,
+            loaded: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn with_priority(mut self, priority: u8) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Treat a missing directory as empty instead of an error.
+    pub fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+
+    /// Follow the `include` key of each file; see
+    /// [`ConfigFile::with_includes`](super::ConfigFile::with_includes).
+    pub fn with_includes(mut self) -> Self {
+        self.includes = true;
+        self
+    }
+}
+
+impl ConfigSource for ConfigDir {
+    fn priority(&self) -> u8 {
+        self.priority
+    }
+
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn watched_paths(&self) -> Vec<PathBuf> {
+        let loaded = self.loaded.lock().expect("loaded paths lock poisoned");
+        let mut paths = vec![self.path.clone()];
+        paths.extend(loaded.iter().cloned());
+        paths
+    }
+
+    fn load(&self) -> Result<ConfigNode, ConfigError> {
+        let layers = self.load_documents()?;
+        Ok(layers
+            .into_iter()
+            .fold(ConfigNode::empty_table(), |merged, (node, _)| merge_nodes(merged, node)))
+    }
+
+    fn load_documents(&self) -> Result<Layers, ConfigError> {
+        let files = match supported_files(&self.path) {
+            Ok(files) => files,
+            Err(ConfigError::Io(err)) if self.optional && err.kind() == ErrorKind::NotFound => {
+                Vec::new()
+            },
+            Err(err) => return Err(err),
+        };
+
+        let mut visited = Vec::new();
+        let mut layers = Vec::new();
+        for file in files {
+            layers.extend(load_with_includes(&file, None, self.includes, &mut visited)?);
+        }
+        *self.loaded.lock().expect("loaded paths lock poisoned") = visited;
+        Ok(layers)
+    }
+}
+
+#[cfg(all(test, feature = "toml"))]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn files_are_merged_in_name_order() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("20-override.toml"), "port = 9090\n").unwrap();
+        fs::write(dir.path().join("10-base.toml"), "port = 8080\nhost = \"a\"\n").unwrap();
+        fs::write(dir.path().join("README.md"), "not config").unwrap();
+
+        let source = ConfigDir::new(dir.path());
+        let node = source.load().unwrap();
+        assert_eq!(node.get_path("port").unwrap().to_string(), "9090");
+        assert_eq!(node.get_path("host").unwrap().to_string(), "a");
+        assert_eq!(source.watched_paths().len(), 3);
+    }
+
+    #[test]
+    fn optional_missing_dir_loads_empty_table() {
+        let dir = tempdir().unwrap();
+        let node = ConfigDir::new(dir.path().join("conf.d"))
+            .optional()
+            .load()
+            .unwrap();
+        assert_eq!(node, ConfigNode::empty_table());
+    }
+}
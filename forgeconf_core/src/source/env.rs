@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+use std::env;
+
+use super::{ConfigSource, insert_segments};
+use crate::{ConfigError, ConfigNode};
+
+/// Pulls a whole configuration tree from prefixed environment variables.
+///
+/// Variables are matched against `prefix` (case-sensitive), the prefix is
+/// stripped, and the remainder is split on `separator` into lower-cased key
+/// segments: with the prefix `APP_` and the default `__` separator,
+/// `APP_DATABASE__HOST` becomes `database.host`. The prefix must end at a
+/// word boundary: either it ends with `_`, or the name continues with `_` or
+/// the separator, which is skipped. So `APP` and `APP_` both read `APP_PORT`,
+/// but neither reads `APPLE_X`. Variables whose name or value is not valid
+/// Unicode are ignored.
+pub struct EnvSource {
+    prefix: String,
+    separator: String,
+    priority: u8,
+    vars: Option<BTreeMap<String, String>>,
+}
+
+impl EnvSource {
+    /// Create a source reading every variable that starts with `prefix`.
+    ///
+    /// The default priority (100) sits between config files and CLI
+    /// arguments.
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            separator: "__".into(),
+            priority: 100,
+            vars: None,
+        }
+    }
+
+    /// Change the separator used to split nested keys (defaults to `__`).
+    pub fn with_separator(mut self, separator: impl Into<String>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    pub fn with_priority(mut self, priority: u8) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Provide a fixed set of variables instead of the process environment;
+    /// useful for tests.
+    pub fn with_vars<I, K, V>(mut self, vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.vars = Some(
+            vars.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        );
+        self
+    }
+
+    fn vars(&self) -> Vec<(String, String)> {
+        match &self.vars {
+            Some(custom) => custom
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            None => env::vars_os()
+                .filter_map(|(key, value)| {
+                    Some((key.into_string().ok()?, value.into_string().ok()?))
+                })
+                .collect(),
+        }
+    }
+
+    fn segments(&self, name: &str) -> Option<Vec<String>> {
+        let stripped = name.strip_prefix(&self.prefix)?;
+        let stripped = match (!self.separator.is_empty())
+            .then(|| stripped.strip_prefix(&self.separator))
+            .flatten()
+            .or_else(|| stripped.strip_prefix('_'))
+        {
+            Some(rest) => rest,
+            None if self.prefix.is_empty() || self.prefix.ends_with('_') => stripped,
+            None => return None,
+        };
+        let segments: Vec<String> = if self.separator.is_empty() {
+            vec![stripped.to_lowercase()]
+        } else {
+            stripped
+                .split(&self.separator)
+                .map(str::to_lowercase)
+                .collect()
+        };
+
+        if segments.iter().any(|segment| segment.is_empty()) { None } else { Some(segments) }
+    }
+}
+
+impl ConfigSource for EnvSource {
+    fn priority(&self) -> u8 {
+        self.priority
+    }
+
+    fn name(&self) -> String {
+        format!("env {}*", self.prefix)
+    }
+
+    fn load(&self) -> Result<ConfigNode, ConfigError> {
+        let mut tree = BTreeMap::new();
+        for (name, value) in self.vars() {
+            if let Some(segments) = self.segments(&name) {
+                insert_segments(&mut tree, &segments, &value);
+            }
+        }
+
+        Ok(ConfigNode::Table(tree))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_should_nest_keys_on_separator() {
+        let source = EnvSource::new("APP_").with_vars([
+            ("APP_DATABASE__HOST", "db.internal"),
+            ("APP_DATABASE__PORT", "5432"),
+            ("APP_PORT", "8080"),
+        ]);
+        let node = source.load().unwrap();
+        let table = node.as_table().unwrap();
+
+        let db = table.get("database").unwrap().as_table().unwrap();
+        assert_eq!(db.get("host").unwrap().to_string(), "db.internal");
+        assert_eq!(db.get("port").unwrap().to_string(), "5432");
+        assert_eq!(table.get("port").unwrap().to_string(), "8080");
+    }
+
+    #[test]
+    fn load_should_ignore_variables_without_prefix() {
+        let source = EnvSource::new("APP_").with_vars([("OTHER_PORT", "1"), ("PATH", "/bin")]);
+        let node = source.load().unwrap();
+        assert!(node.as_table().unwrap().is_empty());
+    }
+
+    #[test]
+    fn load_should_require_a_boundary_after_the_prefix() {
+        let source = EnvSource::new("APP").with_vars([("APPLE_X", "1"), ("APP_PORT", "2")]);
+        let node = source.load().unwrap();
+        let table = node.as_table().unwrap();
+
+        assert_eq!(table.keys().collect::<Vec<_>>(), ["port"]);
+    }
+
+    #[test]
+    fn load_should_skip_empty_segments() {
+        let source = EnvSource::new("APP_").with_vars([("APP_", "x"), ("APP_DB____HOST", "y")]);
+        let node = source.load().unwrap();
+        assert!(node.as_table().unwrap().is_empty());
+    }
+
+    #[test]
+    fn load_should_honor_custom_separator() {
+        let source = EnvSource::new("SVC")
+            .with_separator("_")
+            .with_vars([("SVC_HTTP_PORT", "9000")]);
+        let node = source.load().unwrap();
+        let http = node
+            .as_table()
+            .unwrap()
+            .get("http")
+            .unwrap()
+            .as_table()
+            .unwrap();
+        assert_eq!(http.get("port").unwrap().to_string(), "9000");
+    }
+}
@@ -1,14 +1,26 @@
+use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
-use super::ConfigSource;
-use crate::parser::load_from_path;
+use super::include::load_with_includes;
+use super::{ConfigSource, Layers, merge_nodes};
 use crate::{ConfigError, ConfigNode, FileFormat};
 
 /// Source backed by a configuration file on disk.
+///
+/// With [`ConfigFile::with_includes`], a top-level `include` key pulls in
+/// further files (`include = ["conf.d/*.toml"]`), resolved relative to the
+/// including file. Each included file infers its format from its extension
+/// and overrides the file that includes it.
 pub struct ConfigFile {
     path: PathBuf,
     format: Option<FileFormat>,
     priority: u8,
+    optional: bool,
+    includes: bool,
+    /// Files read by the last load, including the ones pulled in by
+    /// `include`.
+    loaded: Mutex<Vec<PathBuf>>,
 }
 
 impl ConfigFile {
@@ -17,6 +29,9 @@ impl ConfigFile {
             path: path.as_ref().to_path_buf(),
             format: None,
             priority: 10,
+            optional: false,
+            includes: false,
+            loaded: Mutex::new(Vec::new()),
         }
     }
 
@@ -29,6 +44,20 @@ impl ConfigFile {
         self.priority = priority;
         self
     }
+
+    /// Treat a missing file as an empty table instead of an error. Other IO
+    /// failures (such as permission errors) and parse errors still fail.
+    pub fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+
+    /// Read the top-level `include` key as a path or list of paths to load
+    /// after this file, instead of as an ordinary value.
+    pub fn with_includes(mut self) -> Self {
+        self.includes = true;
+        self
+    }
 }
 
 impl ConfigSource for ConfigFile {
@@ -36,7 +65,73 @@ impl ConfigSource for ConfigFile {
         self.priority
     }
 
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn watched_paths(&self) -> Vec<PathBuf> {
+        let loaded = self.loaded.lock().expect("loaded paths lock poisoned");
+        let mut paths = vec![self.path.clone()];
+        paths.extend(loaded.iter().filter(|path| **path != self.path).cloned());
+        paths
+    }
+
     fn load(&self) -> Result<ConfigNode, ConfigError> {
-        load_from_path(&self.path, self.format)
+        let layers = self.load_documents()?;
+        Ok(layers
+            .into_iter()
+            .fold(ConfigNode::empty_table(), |merged, (node, _)| merge_nodes(merged, node)))
+    }
+
+    fn load_documents(&self) -> Result<Layers, ConfigError> {
+        let mut visited = Vec::new();
+        let layers = match load_with_includes(&self.path, self.format, self.includes, &mut visited)
+        {
+            Ok(layers) => layers,
+            Err(ConfigError::Io(err)) if self.optional && err.kind() == ErrorKind::NotFound => {
+                Vec::new()
+            },
+            Err(err) => return Err(err),
+        };
+        *self.loaded.lock().expect("loaded paths lock poisoned") = visited;
+        Ok(layers)
+    }
+}
+
+#[cfg(all(test, feature = "toml"))]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn optional_missing_file_loads_empty_table() {
+        let dir = tempdir().unwrap();
+        let node = ConfigFile::new(dir.path().join("absent.toml"))
+            .optional()
+            .load()
+            .unwrap();
+        assert_eq!(node, ConfigNode::empty_table());
+    }
+
+    #[test]
+    fn required_missing_file_fails() {
+        let dir = tempdir().unwrap();
+        let err = ConfigFile::new(dir.path().join("absent.toml"))
+            .load()
+            .unwrap_err();
+        assert!(matches!(err, ConfigError::Io(ref io) if io.kind() == ErrorKind::NotFound));
+    }
+
+    #[test]
+    fn optional_file_still_reports_parse_errors() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("broken.toml");
+        fs::write(&path, "port = [").unwrap();
+
+        let err = ConfigFile::new(&path).optional().load().unwrap_err();
+        assert!(matches!(err, ConfigError::Toml { .. }));
     }
 }
@@ -0,0 +1,246 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::Layers;
+use crate::parser::{detect_format, read_document};
+use crate::{ConfigError, ConfigNode, FileFormat};
+
+/// Top-level key listing the files a document pulls in.
+const INCLUDE_KEY: &str = "include";
+
+/// Load `path` followed by everything it includes, depth first, recording
+/// every file read in `visited`. Without `includes`, only `path` is read and
+/// an `include` key is left in place as ordinary data.
+///
+/// `include` takes a path or a list of paths relative to the including file.
+/// The file name may contain `*` and `?` wildcards, which match files of any
+/// enabled format in sorted order. Included layers come after the including
+/// one, so their values take precedence.
+pub(crate) fn load_with_includes(
+    path: &Path,
+    explicit: Option<FileFormat>,
+    includes: bool,
+    visited: &mut Vec<PathBuf>,
+) -> Result<Layers, ConfigError> {
+    let mut layers = Vec::new();
+    if !includes {
+        let document = read_document(path, explicit)?;
+        layers.push((document.parse()?, Some(document)));
+        visited.push(path.to_path_buf());
+        return Ok(layers);
+    }
+    let mut stack = Vec::new();
+    load_into(path, explicit, &mut stack, visited, &mut layers)?;
+    Ok(layers)
+}
+
+fn load_into(
+    path: &Path,
+    explicit: Option<FileFormat>,
+    stack: &mut Vec<PathBuf>,
+    visited: &mut Vec<PathBuf>,
+    layers: &mut Layers,
+) -> Result<(), ConfigError> {
+    let document = read_document(path, explicit)?;
+    let canonical = fs::canonicalize(path)?;
+    if let Some(start) = stack.iter().position(|entry| *entry == canonical) {
+        let cycle = stack[start..]
+            .iter()
+            .chain([&canonical])
+            .map(|entry| entry.display().to_string())
+            .collect::<Vec<_>>();
+        return Err(ConfigError::include(path, format!("include cycle {}", cycle.join(" -> "))));
+    }
+
+    let mut node = document.parse()?;
+    let patterns = take_includes(&mut node).map_err(|err| document.attach(err))?;
+    layers.push((node, Some(document)));
+    visited.push(path.to_path_buf());
+
+    let base = path.parent().unwrap_or(Path::new(""));
+    stack.push(canonical);
+    for pattern in patterns {
+        for file in expand(base, &pattern)? {
+            load_into(&file, None, stack, visited, layers).map_err(|err| match err {
+                ConfigError::Io(io) => ConfigError::include(&file, io.to_string()),
+                other => other,
+            })?;
+        }
+    }
+    stack.pop();
+
+    Ok(())
+}
+
+/// Remove the `include` key from the root table, returning its patterns.
+fn take_includes(node: &mut ConfigNode) -> Result<Vec<String>, ConfigError> {
+    let ConfigNode::Table(map) = node else {
+        return Ok(Vec::new());
+    };
+    match map.remove(INCLUDE_KEY) {
+        None | Some(ConfigNode::Null) => Ok(Vec::new()),
+        Some(ConfigNode::String(pattern) | ConfigNode::Scalar(pattern)) => Ok(vec![pattern]),
+        Some(ConfigNode::Array(items)) => items
+            .into_iter()
+            .enumerate()
+            .map(|(index, item)| match item {
+                ConfigNode::String(pattern) | ConfigNode::Scalar(pattern) => Ok(pattern),
+                other => Err(ConfigError::mismatch(
+                    format!("{INCLUDE_KEY}[{index}]"),
+                    "path",
+                    other.kind(),
+                )),
+            })
+            .collect(),
+        Some(other) => {
+            Err(ConfigError::mismatch(INCLUDE_KEY, "path or list of paths", other.kind()))
+        },
+    }
+}
+
+/// Resolve `pattern` against `base`. Patterns without wildcards name a
+/// single file, which must exist.
+fn expand(base: &Path, pattern: &str) -> Result<Vec<PathBuf>, ConfigError> {
+    let path = base.join(pattern);
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return Ok(vec![path]);
+    };
+    if !name.contains(['*', '?']) {
+        return Ok(vec![path]);
+    }
+
+    let dir = path.parent().unwrap_or(Path::new(""));
+    if dir.to_string_lossy().contains(['*', '?']) {
+        return Err(ConfigError::include(&path, "wildcards are only supported in the file name"));
+    }
+    let mut files = supported_files(dir)?
+        .into_iter()
+        .filter(|file| {
+            file.file_name()
+                .and_then(|file| file.to_str())
+                .is_some_and(|file| wildcard_match(name, file))
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+    Ok(files)
+}
+
+/// Files in `dir` whose extension maps to an enabled format, sorted by name.
+pub(crate) fn supported_files(dir: &Path) -> Result<Vec<PathBuf>, ConfigError> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && detect_format(&path).is_some() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Match `text` against a pattern where `*` matches any run of characters
+/// and `?` a single one.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            },
+            Some(&ch) if ch == '?' || ch == text[t] => {
+                p += 1;
+                t += 1;
+            },
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                },
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&ch| ch == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcard_match_handles_stars_and_marks() {
+        assert!(wildcard_match("*.toml", "10-db.toml"));
+        assert!(wildcard_match("??-*.yaml", "10-db.yaml"));
+        assert!(wildcard_match("*", "anything"));
+        assert!(!wildcard_match("*.toml", "db.yaml"));
+        assert!(!wildcard_match("a*b", "ac"));
+    }
+
+    #[cfg(feature = "toml")]
+    mod toml {
+        use tempfile::tempdir;
+
+        use super::*;
+
+        #[test]
+        fn includes_resolve_relative_to_the_including_file() {
+            let dir = tempdir().unwrap();
+            fs::create_dir(dir.path().join("conf.d")).unwrap();
+            fs::write(dir.path().join("app.toml"), "include = \"conf.d/*.toml\"\nport = 1\n")
+                .unwrap();
+            fs::write(dir.path().join("conf.d/20-b.toml"), "port = 3\n").unwrap();
+            fs::write(dir.path().join("conf.d/10-a.toml"), "port = 2\nname = \"a\"\n").unwrap();
+            fs::write(dir.path().join("conf.d/notes.txt"), "ignored").unwrap();
+
+            let mut visited = Vec::new();
+            let layers =
+                load_with_includes(&dir.path().join("app.toml"), None, true, &mut visited).unwrap();
+
+            let ports: Vec<_> = layers
+                .iter()
+                .map(|(node, _)| node.get_path("port").unwrap().to_string())
+                .collect();
+            assert_eq!(ports, ["1", "2", "3"]);
+            assert!(layers[0].0.get_path("include").is_none());
+            assert_eq!(visited.len(), 3);
+        }
+
+        #[test]
+        fn include_cycles_are_reported() {
+            let dir = tempdir().unwrap();
+            fs::write(dir.path().join("a.toml"), "include = \"b.toml\"\n").unwrap();
+            fs::write(dir.path().join("b.toml"), "include = [\"a.toml\"]\n").unwrap();
+
+            let Err(err) =
+                load_with_includes(&dir.path().join("a.toml"), None, true, &mut Vec::new())
+            else {
+                panic!("expected an error");
+            };
+            let ConfigError::Include { reason, .. } = err else {
+                panic!("expected include error, got {err:?}");
+            };
+            assert!(reason.starts_with("include cycle"));
+        }
+
+        #[test]
+        fn missing_includes_name_the_file() {
+            let dir = tempdir().unwrap();
+            fs::write(dir.path().join("a.toml"), "include = \"missing.toml\"\n").unwrap();
+
+            let Err(err) =
+                load_with_includes(&dir.path().join("a.toml"), None, true, &mut Vec::new())
+            else {
+                panic!("expected an error");
+            };
+            assert!(
+                matches!(err, ConfigError::Include { path, .. } if path.ends_with("missing.toml"))
+            );
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use crate::span::join_path;
+use crate::{ConfigError, ConfigNode};
+
+/// How an overlay value combines with the value it overrides.
+///
+/// Tables are always merged key by key; the strategy decides what happens to
+/// arrays. Scalars are always replaced.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum MergeStrategy {
+    /// The overlay replaces the base value.
+    #[default]
+    Replace,
+    /// Overlay items are added after the base items.
+    Append,
+    /// Overlay items are added before the base items.
+    Prepend,
+    /// Arrays of tables are matched on the given key: matching items are
+    /// merged, the others appended.
+    MergeByKey(String),
+}
+
+impl FromStr for MergeStrategy {
+    type Err = ConfigError;
+
+    /// Parses `replace`, `append`, `prepend` or `merge_by_key(<key>)`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        match trimmed {
+            "replace" => Ok(MergeStrategy::Replace),
+            "append" => Ok(MergeStrategy::Append),
+            "prepend" => Ok(MergeStrategy::Prepend),
+            _ => trimmed
+                .strip_prefix("merge_by_key(")
+                .and_then(|rest| rest.strip_suffix(')'))
+                .map(str::trim)
+                .filter(|key| !key.is_empty())
+                .map(|key| MergeStrategy::MergeByKey(key.to_string()))
+                .ok_or_else(|| {
+                    ConfigError::mismatch(
+                        "merge",
+                        "replace, append, prepend or merge_by_key(<key>)",
+                        s,
+                    )
+                }),
+        }
+    }
+}
+
+impl Display for MergeStrategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeStrategy::Replace => write!(f, "replace"),
+            MergeStrategy::Append => write!(f, "append"),
+            MergeStrategy::Prepend => write!(f, "prepend"),
+            MergeStrategy::MergeByKey(key) => write!(f, "merge_by_key({key})"),
+        }
+    }
+}
+
+/// Combine two configuration trees, where values from `overlay` take
+/// precedence.
+pub fn merge_nodes(base: ConfigNode, overlay: ConfigNode) -> ConfigNode {
+    merge_nodes_with(base, overlay, &BTreeMap::new())
+}
+
+/// Like [`merge_nodes`], but arrays found at the paths in `strategies` are
+/// combined according to their [`MergeStrategy`]. Items of arrays of tables
+/// share the path of the array, so `listeners.tags` addresses the `tags` of
+/// every listener.
+pub fn merge_nodes_with(
+    base: ConfigNode,
+    overlay: ConfigNode,
+    strategies: &BTreeMap<String, MergeStrategy>,
+) -> ConfigNode {
+    merge_at(base, &overlay, "", strategies)
+}
+
+/// Like [`merge_nodes_with`], copying only the parts of `overlay` that end
+/// up in the result, so the caller can keep it.
+pub(crate) fn merge_borrowed(
+    base: ConfigNode,
+    overlay: &ConfigNode,
+    strategies: &BTreeMap<String, MergeStrategy>,
+) -> ConfigNode {
+    merge_at(base, overlay, "", strategies)
+}
+
+fn merge_at(
+    base: ConfigNode,
+    overlay: &ConfigNode,
+    path: &str,
+    strategies: &BTreeMap<String, MergeStrategy>,
+) -> ConfigNode {
+    match (base, overlay) {
+        (ConfigNode::Table(mut left), ConfigNode::Table(right)) => {
+            for (key, value) in right {
+                let merged = match left.remove(key) {
+                    Some(existing) => merge_at(existing, value, &join_path(path, key), strategies),
+                    None => value.clone(),
+                };
+                left.insert(key.clone(), merged);
+            }
+            ConfigNode::Table(left)
+        },
+        (ConfigNode::Array(mut left), ConfigNode::Array(right)) => match strategies.get(path) {
+            Some(MergeStrategy::Append) => {
+                left.extend(right.iter().cloned());
+                ConfigNode::Array(left)
+            },
+            Some(MergeStrategy::Prepend) => {
+                let mut items = right.clone();
+                items.extend(left);
+                ConfigNode::Array(items)
+            },
+            Some(MergeStrategy::MergeByKey(key)) => {
+                for item in right {
+                    let position = item_key(item, key).and_then(|wanted| {
+                        left.iter()
+                            .position(|existing| item_key(existing, key).as_ref() == Some(&wanted))
+                    });
+                    match position {
+                        Some(index) => {
+                            let existing = std::mem::replace(&mut left[index], ConfigNode::Null);
+                            left[index] = merge_at(existing, item, path, strategies);
+                        },
+                        None => left.push(item.clone()),
+                    }
+                }
+                ConfigNode::Array(left)
+            },
+            Some(MergeStrategy::Replace) | None => ConfigNode::Array(right.clone()),
+        },
+        (_, other) => other.clone(),
+    }
+}
+
+/// The textual value of `key` when `item` is a table holding a leaf there.
+fn item_key(item: &ConfigNode, key: &str) -> Option<String> {
+    item.as_table()?
+        .get(key)?
+        .as_text()
+        .map(|text| text.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(items: &[&str]) -> ConfigNode {
+        ConfigNode::Array(
+            items
+                .iter()
+                .map(|item| ConfigNode::String(item.to_string()))
+                .collect(),
+        )
+    }
+
+    fn table(entries: &[(&str, ConfigNode)]) -> ConfigNode {
+        ConfigNode::Table(
+            entries
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect(),
+        )
+    }
+
+    fn listener(name: &str, port: i64) -> ConfigNode {
+        table(&[("name", ConfigNode::String(name.into())), ("port", ConfigNode::Integer(port))])
+    }
+
+    fn strategies(path: &str, strategy: MergeStrategy) -> BTreeMap<String, MergeStrategy> {
+        BTreeMap::from([(path.to_string(), strategy)])
+    }
+
+    #[test]
+    fn arrays_are_replaced_by_default() {
+        let merged = merge_nodes(
+            table(&[("plugins", strings(&["a"]))]),
+            table(&[("plugins", strings(&["b"]))]),
+        );
+        assert_eq!(merged, table(&[("plugins", strings(&["b"]))]));
+    }
+
+    #[test]
+    fn append_and_prepend_keep_both_sides() {
+        let base = table(&[("plugins", strings(&["a"]))]);
+        let overlay = table(&[("plugins", strings(&["b"]))]);
+
+        let appended = merge_nodes_with(
+            base.clone(),
+            overlay.clone(),
+            &strategies("plugins", MergeStrategy::Append),
+        );
+        assert_eq!(appended, table(&[("plugins", strings(&["a", "b"]))]));
+
+        let prepended =
+            merge_nodes_with(base, overlay, &strategies("plugins", MergeStrategy::Prepend));
+        assert_eq!(prepended, table(&[("plugins", strings(&["b", "a"]))]));
+    }
+
+    #[test]
+    fn merge_by_key_merges_matching_tables() {
+        let base = table(&[(
+            "server",
+            table(&[(
+                "listeners",
+                ConfigNode::Array(vec![listener("http", 80), listener("https", 443)]),
+            )]),
+        )]);
+        let overlay = table(&[(
+            "server",
+            table(&[(
+                "listeners",
+                ConfigNode::Array(vec![listener("http", 8080), listener("admin", 9000)]),
+            )]),
+        )]);
+
+        let merged = merge_nodes_with(
+            base,
+            overlay,
+            &strategies("server.listeners", MergeStrategy::MergeByKey("name".into())),
+        );
+        assert_eq!(
+            merged.get_path("server.listeners"),
+            Some(&ConfigNode::Array(vec![
+                listener("http", 8080),
+                listener("https", 443),
+                listener("admin", 9000),
+            ]))
+        );
+    }
+
+    #[test]
+    fn strategies_parse_from_strings() {
+        assert_eq!("append".parse::<MergeStrategy>().unwrap(), MergeStrategy::Append);
+        assert_eq!(
+            "merge_by_key(name)".parse::<MergeStrategy>().unwrap(),
+            MergeStrategy::MergeByKey("name".into())
+        );
+        assert!("merge_by_key()".parse::<MergeStrategy>().is_err());
+        assert!("concat".parse::<MergeStrategy>().is_err());
+    }
+}
@@ -1,12 +1,30 @@
-use crate::{ConfigError, ConfigNode};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use crate::interpolate::interpolate;
+use crate::paths::resolve_paths;
+use crate::profile::{ProfileSelector, check_known, flatten};
+use crate::{ConfigError, ConfigNode, PathOptions, Provenance, SourceDocument, SourceMap};
 
 #[cfg(feature = "cli")]
 mod cli;
+mod dir;
+mod env;
 mod file;
+mod include;
+mod merge;
 
 #[cfg(feature = "cli")]
 pub use cli::{CliArgsSource, CliArguments};
+pub use dir::ConfigDir;
+pub use env::EnvSource;
 pub use file::ConfigFile;
+use merge::merge_borrowed;
+pub use merge::{MergeStrategy, merge_nodes, merge_nodes_with};
+
+/// Layers loaded from a single source, lowest precedence first, each with
+/// the document it was parsed from when there is one.
+pub type Layers = Vec<(ConfigNode, Option<SourceDocument>)>;
 
 /// Trait implemented by configuration sources (files, CLI, etc).
 pub trait ConfigSource: Send + Sync {
@@ -15,36 +33,89 @@ pub trait ConfigSource: Send + Sync {
         0
     }
 
+    /// Name used to report where values came from, such as a file path.
+    fn name(&self) -> String {
+        std::any::type_name::<Self>().to_string()
+    }
+
+    /// Files whose changes should trigger a reload of this source; see
+    /// [`ReloadHandle`](crate::ReloadHandle).
+    fn watched_paths(&self) -> Vec<PathBuf> {
+        Vec::new()
+    }
+
     /// Load configuration data from the source.
     fn load(&self) -> Result<ConfigNode, ConfigError>;
+
+    /// Load configuration data along with the documents it was parsed from,
+    /// so errors can point back into them. Sources made of several files,
+    /// such as a file with includes, return one layer per file. Sources that
+    /// are not backed by a document keep the default.
+    fn load_documents(&self) -> Result<Layers, ConfigError> {
+        Ok(vec![(self.load()?, None)])
+    }
 }
 
-/// Combine two configuration trees, where values from `overlay` take
-/// precedence.
-pub fn merge_nodes(base: ConfigNode, overlay: ConfigNode) -> ConfigNode {
-    match (base, overlay) {
-        (ConfigNode::Table(mut left), ConfigNode::Table(right)) => {
-            for (key, value) in right {
-                match left.remove(&key) {
-                    Some(existing) => {
-                        let merged = merge_nodes(existing, value);
-                        left.insert(key, merged);
-                    },
-                    None => {
-                        left.insert(key, value);
-                    },
-                }
-            }
-            ConfigNode::Table(left)
-        },
-        (_, other) => other,
+/// Insert `value` into `tree` following the given key segments, creating
+/// intermediate tables as needed.
+pub(crate) fn insert_segments<S>(
+    tree: &mut BTreeMap<String, ConfigNode>,
+    segments: &[S],
+    value: &str,
+) where
+    S: AsRef<str>,
+{
+    if let Some((head, tail)) = segments.split_first() {
+        let head = head.as_ref();
+        if tail.is_empty() {
+            tree.insert(head.to_string(), ConfigNode::Scalar(value.to_owned()));
+        } else {
+            let branch = tree
+                .entry(head.to_string())
+                .or_insert_with(ConfigNode::empty_table);
+            let map = as_table(branch);
+            insert_segments(map, tail, value);
+        }
+    }
+}
+
+fn as_table(node: &mut ConfigNode) -> &mut BTreeMap<String, ConfigNode> {
+    if !matches!(node, ConfigNode::Table(_)) {
+        *node = ConfigNode::empty_table();
+    }
+
+    match node {
+        ConfigNode::Table(map) => map,
+        _ => unreachable!("ensured above"),
     }
 }
 
+type EnvLookup = dyn Fn(&str) -> Option<String> + Send + Sync;
+
 /// Builder that merges a set of [`ConfigSource`] instances.
-#[derive(Default)]
+///
+/// With [`ConfigBuilder::interpolate`], `${...}` references in string values
+/// are resolved against the merged tree and the environment after merging.
 pub struct ConfigBuilder {
     sources: Vec<Box<dyn ConfigSource>>,
+    strategies: BTreeMap<String, MergeStrategy>,
+    interpolate: bool,
+    env: Box<EnvLookup>,
+    profiles: Option<ProfileSelector>,
+    paths: BTreeMap<String, PathOptions>,
+}
+
+impl Default for ConfigBuilder {
+    fn default() -> Self {
+        Self {
+            sources: Vec::new(),
+            strategies: BTreeMap::new(),
+            interpolate: false,
+            env: Box::new(|name| std::env::var(name).ok()),
+            profiles: None,
+            paths: BTreeMap::new(),
+        }
+    }
 }
 
 impl ConfigBuilder {
@@ -60,23 +131,149 @@ impl ConfigBuilder {
         self
     }
 
-    pub fn load(mut self) -> Result<ConfigNode, ConfigError> {
-        self.sources.sort_by_key(|source| source.priority());
+    /// Choose how values at `path` (such as `plugins` or `server.listeners`)
+    /// from higher priority sources combine with lower priority ones. Paths
+    /// without a strategy use [`MergeStrategy::Replace`].
+    pub fn merge_strategy(mut self, path: impl Into<String>, strategy: MergeStrategy) -> Self {
+        self.strategies.insert(path.into(), strategy);
+        self
+    }
+
+    /// Treat the value at `path` (such as `tls.cert`) as a filesystem path
+    /// or list of paths, resolved after merging and interpolation.
+    ///
+    /// With `relative_to_config`, relative paths are joined onto the
+    /// directory of the file that supplied them; values from environment
+    /// variables and CLI flags stay relative to the working directory.
+    pub fn resolve_path(mut self, path: impl Into<String>, options: PathOptions) -> Self {
+        self.paths.insert(path.into(), options);
+        self
+    }
+
+    /// Resolve `${database.port}`-style key references, `${VAR}` environment
+    /// variables and `${VAR:-fallback}` defaults in string values after
+    /// merging. Without it, `${...}` is kept as literal text.
+    pub fn interpolate(mut self) -> Self {
+        self.interpolate = true;
+        self
+    }
+
+    /// Look up environment variables through `lookup` instead of
+    /// [`std::env::var`]. It serves interpolation, `~` and `$VAR` in resolved
+    /// paths and the profile variable set by [`ConfigBuilder::profile_env`].
+    pub fn env_lookup<F>(mut self, lookup: F) -> Self
+    where
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    {
+        self.env = Box::new(lookup);
+        self
+    }
+
+    /// Merge the `[profile.<name>]` section of each source over its
+    /// `[default]` section (and any top-level keys) before sources are merged
+    /// with each other.
+    ///
+    /// A selected profile that no source defines is reported as
+    /// [`ConfigError::UnknownProfile`].
+    pub fn profile(mut self, name: impl Into<String>) -> Self {
+        self.profiles.get_or_insert_default().name = Some(name.into());
+        self
+    }
+
+    /// Enable `[default]` / `[profile.<name>]` sections, reading the profile
+    /// from the environment variable `var` (such as `APP_PROFILE`).
+    ///
+    /// A string `profile` key supplied by a source, such as `--profile=prod`
+    /// through [`CliArguments`](crate::CliArguments), takes precedence over the
+    /// variable, and [`ConfigBuilder::profile`] over both. Without any of them
+    /// only `[default]` applies.
+    pub fn profile_env(mut self, var: impl Into<String>) -> Self {
+        self.profiles.get_or_insert_default().env = Some(var.into());
+        self
+    }
+
+    pub fn load(self) -> Result<ConfigNode, ConfigError> {
+        self.load_with_spans().map(|(node, _)| node)
+    }
+
+    /// Like [`ConfigBuilder::load`], but also returns the [`SourceMap`] used
+    /// to point errors at the document that supplied each value.
+    pub fn load_with_spans(self) -> Result<(ConfigNode, SourceMap), ConfigError> {
+        self.merge()
+    }
+
+    /// Files watched by the registered sources.
+    pub fn watched_paths(&self) -> Vec<PathBuf> {
+        self.sources
+            .iter()
+            .flat_map(|source| source.watched_paths())
+            .collect()
+    }
+
+    /// Load and merge every source without consuming the builder, so it can
+    /// be run again on reload.
+    pub(crate) fn merge(&self) -> Result<(ConfigNode, SourceMap), ConfigError> {
+        let mut sources: Vec<&dyn ConfigSource> = self.sources.iter().map(AsRef::as_ref).collect();
+        sources.sort_by_key(|source| source.priority());
+
+        let mut layers = Vec::new();
+        for source in sources {
+            for (value, document) in source.load_documents()? {
+                let name = match &document {
+                    Some(document) => document.name().to_string(),
+                    None => source.name(),
+                };
+                layers.push((name, source.priority(), value, document));
+            }
+        }
+
+        let env = &*self.env;
+        let profile = self
+            .profiles
+            .as_ref()
+            .map(|selector| selector.select(layers.iter().map(|(_, _, value, _)| value), env));
 
         let mut merged = ConfigNode::empty_table();
-        for source in self.sources {
-            let value = source.load()?;
-            merged = merge_nodes(merged, value);
+        let mut spans = SourceMap::with_strategies(self.strategies.clone());
+        let mut known = BTreeSet::new();
+        for (name, priority, value, document) in layers {
+            let (value, sections) = match &profile {
+                Some(profile) => {
+                    let flattened = flatten(value, profile.as_deref());
+                    known.extend(flattened.profiles);
+                    (flattened.node, flattened.sections)
+                },
+                None => (value, Vec::new()),
+            };
+            merged = merge_borrowed(merged, &value, &self.strategies);
+            spans.push_sections(name, priority, value, document, sections);
+        }
+        if let Some(Some(profile)) = &profile {
+            check_known(profile, &known)?;
+        }
+
+        if self.interpolate {
+            merged = interpolate(&merged, env).map_err(|err| spans.attach(err))?;
+        }
+        if !self.paths.is_empty() {
+            merged =
+                resolve_paths(merged, &self.paths, &spans, env).map_err(|err| spans.attach(err))?;
         }
 
-        Ok(merged)
+        Ok((merged, spans))
+    }
+
+    /// Like [`ConfigBuilder::load`], but also reports which source supplied
+    /// each leaf of the merged tree.
+    pub fn load_with_provenance(self) -> Result<(ConfigNode, Provenance), ConfigError> {
+        let (merged, spans) = self.load_with_spans()?;
+        let provenance = spans.provenance(&merged);
+        Ok((merged, provenance))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::BTreeMap;
-
     use super::*;
 
     #[derive(Clone)]
@@ -136,4 +333,18 @@ mod tests {
         let table = node.as_table().unwrap();
         assert_eq!(table.get("service").unwrap().to_string(), "override");
     }
+
+    #[test]
+    fn config_builder_reports_winning_source() {
+        let (_, provenance) = ConfigBuilder::new()
+            .add_source(StaticSource::table(5, "service", "base"))
+            .add_source(StaticSource::table(200, "service", "override"))
+            .load_with_provenance()
+            .unwrap();
+
+        let origin = provenance.get("service").unwrap();
+        assert_eq!(origin.priority, Some(200));
+        assert_eq!(origin.value.as_deref(), Some("override"));
+        assert!(origin.source.ends_with("StaticSource"));
+    }
 }
@@ -0,0 +1,572 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+use miette::{NamedSource, SourceSpan};
+
+use crate::error::downcast_nested;
+use crate::parser::{self, FileFormat};
+use crate::{ConfigError, ConfigNode, MergeStrategy, Origin, Provenance};
+
+/// The text of a configuration document together with its origin.
+///
+/// Spans are only computed on the error path: the first call to
+/// [`SourceDocument::span_of`] re-scans the contents and caches the byte span
+/// of every value, keyed by its path (`database.port`, `servers[0].host`).
+pub struct SourceDocument {
+    name: String,
+    format: FileFormat,
+    contents: String,
+    path: Option<PathBuf>,
+    spans: OnceLock<BTreeMap<String, SourceSpan>>,
+    source: OnceLock<Arc<NamedSource<String>>>,
+}
+
+impl SourceDocument {
+    pub fn new(name: impl Into<String>, format: FileFormat, contents: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            format,
+            contents: contents.into(),
+            path: None,
+            spans: OnceLock::new(),
+            source: OnceLock::new(),
+        }
+    }
+
+    /// Name shown in diagnostics, usually the file path.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Record the file the document was read from.
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// The file the document was read from, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn format(&self) -> FileFormat {
+        self.format
+    }
+
+    pub fn contents(&self) -> &str {
+        &self.contents
+    }
+
+    /// Parse the document, attaching it to any parse error.
+    pub fn parse(&self) -> Result<ConfigNode, ConfigError> {
+        parser::parse_str(&self.contents, self.format).map_err(|err| self.attach(err))
+    }
+
+    /// Byte span of the value at `path`, if the document defines it.
+    pub fn span_of(&self, path: &str) -> Option<SourceSpan> {
+        self.spans
+            .get_or_init(|| parser::locate(&self.contents, self.format))
+            .get(path)
+            .copied()
+    }
+
+    /// 1-based line and column of the byte at `offset`.
+    pub fn line_column(&self, offset: usize) -> (usize, usize) {
+        let before = &self.contents[..offset.min(self.contents.len())];
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+        let line = before.matches('\n').count() + 1;
+        (line, before[line_start..].chars().count() + 1)
+    }
+
+    /// Point `err` at this document, filling in spans for the paths it
+    /// mentions.
+    pub fn attach(&self, err: ConfigError) -> ConfigError {
+        let err = match err {
+            #[cfg(feature = "toml")]
+            ConfigError::Toml { source, span, src: None } => ConfigError::Toml {
+                source,
+                span,
+                src: Some(self.named_source()),
+            },
+            #[cfg(feature = "yaml")]
+            ConfigError::Yaml { source, span, src: None } => ConfigError::Yaml {
+                source,
+                span,
+                src: Some(self.named_source()),
+            },
+            #[cfg(feature = "json")]
+            ConfigError::Json { source, span, src: None } => ConfigError::Json {
+                source,
+                span,
+                src: Some(self.named_source()),
+            },
+            other => other,
+        };
+
+        attach_spans(err, "", &|path| self.span_of(path).map(|span| (span, self.named_source())))
+    }
+
+    fn named_source(&self) -> Arc<NamedSource<String>> {
+        self.source
+            .get_or_init(|| {
+                let source = NamedSource::new(&self.name, self.contents.clone())
+                    .with_language(self.format.label());
+                Arc::new(source)
+            })
+            .clone()
+    }
+}
+
+/// The layers merged by [`ConfigBuilder`](crate::ConfigBuilder), kept so
+/// errors and [`Origin`]s can be traced back to the source that supplied a
+/// value.
+#[derive(Default)]
+pub struct SourceMap {
+    layers: Vec<Layer>,
+    /// How arrays were combined, so items of an appended or prepended array
+    /// are traced to the layer that supplied them.
+    strategies: BTreeMap<String, MergeStrategy>,
+}
+
+struct Layer {
+    name: String,
+    priority: u8,
+    node: ConfigNode,
+    document: Option<SourceDocument>,
+    /// Document prefixes `node` was flattened from, highest precedence
+    /// first; see [`SourceMap::push_sections`].
+    sections: Vec<String>,
+}
+
+impl Layer {
+    /// Span of the value at `path` of `node` in the layer's document.
+    fn span_of(&self, path: &str) -> Option<(&SourceDocument, SourceSpan)> {
+        let document = self.document.as_ref()?;
+        let span = match self.sections.as_slice() {
+            [] => document.span_of(path),
+            sections => sections
+                .iter()
+                .find_map(|section| document.span_of(&join_path(section, path))),
+        }?;
+        Some((document, span))
+    }
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Like [`SourceMap::new`], for layers merged with `strategies`.
+    pub(crate) fn with_strategies(strategies: BTreeMap<String, MergeStrategy>) -> Self {
+        Self { layers: Vec::new(), strategies }
+    }
+
+    /// Record the layer loaded from the source called `name`. Layers pushed
+    /// later take precedence.
+    pub fn push(
+        &mut self,
+        name: impl Into<String>,
+        priority: u8,
+        node: ConfigNode,
+        document: Option<SourceDocument>,
+    ) {
+        self.push_sections(name, priority, node, document, Vec::new());
+    }
+
+    /// Like [`SourceMap::push`], for a `node` merged from several sections of
+    /// `document` (such as `profile.prod` over `default`). `sections` lists
+    /// their prefixes, highest precedence first, with `""` for the root.
+    pub fn push_sections(
+        &mut self,
+        name: impl Into<String>,
+        priority: u8,
+        node: ConfigNode,
+        document: Option<SourceDocument>,
+        sections: Vec<String>,
+    ) {
+        self.layers.push(Layer {
+            name: name.into(),
+            priority,
+            node,
+            document,
+            sections,
+        });
+    }
+
+    /// Find the document and span of the value that won the merge at `path`.
+    ///
+    /// Returns `None` when the value came from a source without a document,
+    /// such as environment variables or CLI arguments.
+    pub fn locate(&self, path: &str) -> Option<(&SourceDocument, SourceSpan)> {
+        let (layer, path) = self.supplier(path)?;
+        layer.span_of(&path)
+    }
+
+    /// Directory of the file that supplied the value that won the merge at
+    /// `path`. `None` when that source has no file.
+    pub fn base_dir(&self, path: &str) -> Option<&Path> {
+        let (layer, _) = self.supplier(path)?;
+        layer.document.as_ref()?.path()?.parent()
+    }
+
+    /// Describe the source of the value that won the merge at `path`, with
+    /// its line and column when the source is a document.
+    pub fn origin(&self, path: &str) -> Option<Origin> {
+        let (layer, path) = self.supplier(path)?;
+        let node = layer.node.get_path(&path)?;
+
+        let origin = Origin::source(&layer.name, layer.priority).with_value(node.to_string());
+        let location = layer
+            .span_of(&path)
+            .map(|(document, span)| document.line_column(span.offset()));
+        Some(match location {
+            Some((line, column)) => origin.with_location(line, column),
+            None => origin,
+        })
+    }
+
+    /// The layer that supplied the merged value at `path`, with the path of
+    /// that value within the layer.
+    ///
+    /// Indices into appended or prepended arrays are mapped back onto the
+    /// layer that contributed the item; other arrays are taken from the
+    /// latest layer that has the index.
+    fn supplier(&self, path: &str) -> Option<(&Layer, String)> {
+        let mut candidates: Vec<(&Layer, String)> = self
+            .layers
+            .iter()
+            .map(|layer| (layer, String::new()))
+            .collect();
+        let mut merged = String::new();
+        for segment in path_segments(path) {
+            candidates = match segment {
+                Segment::Key(key) => {
+                    merged = join_path(&merged, key);
+                    candidates
+                        .into_iter()
+                        .map(|(layer, path)| (layer, join_path(&path, key)))
+                        .filter(|(layer, path)| layer.node.get_path(path).is_some())
+                        .collect()
+                },
+                Segment::Index(index) => {
+                    let strategy = self.strategies.get(&strip_indices(&merged));
+                    merged = index_path(&merged, index);
+                    pick_item(candidates, strategy, index)
+                },
+            };
+        }
+        candidates.pop()
+    }
+    /// Record the origin of every leaf of `merged`. Arrays count as leaves.
+    pub fn provenance(&self, merged: &ConfigNode) -> Provenance {
+        let mut provenance = Provenance::new();
+        self.record_leaves(merged, "", &mut provenance);
+        provenance
+    }
+
+    fn record_leaves(&self, node: &ConfigNode, path: &str, provenance: &mut Provenance) {
+        match node {
+            ConfigNode::Table(map) => {
+                for (key, child) in map {
+                    self.record_leaves(child, &join_path(path, key), provenance);
+                }
+            },
+            _ => {
+                if let Some(origin) = self.origin(path) {
+                    provenance.insert(path, origin);
+                }
+            },
+        }
+    }
+
+    /// Fill in the spans of `err` from the layers that supplied each value.
+    pub fn attach(&self, err: ConfigError) -> ConfigError {
+        attach_spans(err, "", &|path| {
+            self.locate(path)
+                .map(|(document, span)| (span, document.named_source()))
+        })
+    }
+}
+
+type Locate<'a> = dyn Fn(&str) -> Option<(SourceSpan, Arc<NamedSource<String>>)> + 'a;
+
+/// Walk `err`, resolving each field relative to the enclosing `Nested`
+/// sections. Errors that already carry a span, and redacted ones, are left
+/// untouched.
+fn attach_spans(err: ConfigError, prefix: &str, locate: &Locate<'_>) -> ConfigError {
+    match err {
+        // The snippet would reveal a secret value.
+        ConfigError::TypeMismatch { redacted: true, .. } => err,
+        ConfigError::TypeMismatch {
+            field,
+            expected,
+            found,
+            span: None,
+            src: None,
+            redacted: false,
+        } => {
+            let (span, src) = locate(&join_path(prefix, &field)).unzip();
+            ConfigError::TypeMismatch {
+                field,
+                expected,
+                found,
+                span,
+                src,
+                redacted: false,
+            }
+        },
+        ConfigError::UnknownField {
+            field,
+            suggestion,
+            help,
+            span: None,
+            src: None,
+        } => {
+            let (span, src) = locate(&join_path(prefix, &field)).unzip();
+            ConfigError::UnknownField { field, suggestion, help, span, src }
+        },
+        ConfigError::Interpolation { field, reason, span: None, src: None } => {
+            let (span, src) = locate(&join_path(prefix, &field)).unzip();
+            ConfigError::Interpolation { field, reason, span, src }
+        },
+        ConfigError::Constraint { fields, reason, span: None, src: None } => {
+            let (span, src) = fields
+                .iter()
+                .find_map(|field| locate(&join_path(prefix, field)))
+                .unzip();
+            ConfigError::Constraint { fields, reason, span, src }
+        },
+        ConfigError::MissingValue { field, span: None, src: None } => {
+            let path = join_path(prefix, &field);
+            let (span, src) = parent_path(&path).and_then(locate).unzip();
+            ConfigError::MissingValue { field, span, src }
+        },
+        ConfigError::Nested { section, source, span, src } => match downcast_nested(source) {
+            Ok(inner) => {
+                let source = Box::new(attach_spans(inner, &join_path(prefix, &section), locate));
+                ConfigError::Nested { section, source, span, src }
+            },
+            Err(source) => ConfigError::Nested { section, source, span, src },
+        },
+        ConfigError::Multiple { errors } => ConfigError::Multiple {
+            errors: errors
+                .into_iter()
+                .map(|err| attach_spans(err, prefix, locate))
+                .collect(),
+        },
+        other => other,
+    }
+}
+
+/// Append `key` to `prefix`, where `key` may itself be a path such as
+/// `ports[0]` or `database.host`.
+pub(crate) fn join_path(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else if key.starts_with('[') {
+        format!("{prefix}{key}")
+    } else {
+        format!("{prefix}.{key}")
+    }
+}
+
+pub(crate) fn index_path(prefix: &str, index: usize) -> String {
+    format!("{prefix}[{index}]")
+}
+
+/// Narrow `candidates` to the layer that supplied item `index` of the merged
+/// array they hold.
+fn pick_item<'a>(
+    mut candidates: Vec<(&'a Layer, String)>,
+    strategy: Option<&MergeStrategy>,
+    index: usize,
+) -> Vec<(&'a Layer, String)> {
+    match strategy {
+        Some(MergeStrategy::Append) => {},
+        Some(MergeStrategy::Prepend) => candidates.reverse(),
+        _ => {
+            return candidates
+                .into_iter()
+                .map(|(layer, path)| (layer, index_path(&path, index)))
+                .filter(|(layer, path)| layer.node.get_path(path).is_some())
+                .collect();
+        },
+    }
+
+    let mut offset = index;
+    for (layer, path) in candidates {
+        let len = match layer.node.get_path(&path) {
+            Some(ConfigNode::Array(items)) => items.len(),
+            _ => 0,
+        };
+        if offset < len {
+            return vec![(layer, index_path(&path, offset))];
+        }
+        offset -= len;
+    }
+    Vec::new()
+}
+
+enum Segment<'p> {
+    Key(&'p str),
+    Index(usize),
+}
+
+/// Split `servers[0].host` into `servers`, `0` and `host`.
+fn path_segments(path: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    for part in path.split('.').filter(|part| !part.is_empty()) {
+        let (name, mut indices) = part.split_at(part.find('[').unwrap_or(part.len()));
+        if !name.is_empty() {
+            segments.push(Segment::Key(name));
+        }
+        while let Some((index, tail)) = indices
+            .strip_prefix('[')
+            .and_then(|rest| rest.split_once(']'))
+        {
+            if let Ok(index) = index.parse() {
+                segments.push(Segment::Index(index));
+            }
+            indices = tail;
+        }
+    }
+    segments
+}
+
+/// The strategy key of an array: items of arrays of tables share the path
+/// of the array, so `listeners[1].tags` becomes `listeners.tags`.
+fn strip_indices(path: &str) -> String {
+    let mut stripped = String::with_capacity(path.len());
+    let mut depth = 0;
+    for ch in path.chars() {
+        match ch {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            _ if depth == 0 => stripped.push(ch),
+            _ => {},
+        }
+    }
+    stripped
+}
+
+/// Strip the last segment of `path`; `None` for top-level keys.
+fn parent_path(path: &str) -> Option<&str> {
+    let cut = path.rfind(['.', '['])?;
+    Some(&path[..cut])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn join_path_handles_indices_and_roots() {
+        assert_eq!(join_path("", "port"), "port");
+        assert_eq!(join_path("database", "port"), "database.port");
+        assert_eq!(join_path("servers", "[1]"), "servers[1]");
+    }
+
+    #[test]
+    fn parent_path_strips_last_segment() {
+        assert_eq!(parent_path("database.port"), Some("database"));
+        assert_eq!(parent_path("servers[1]"), Some("servers"));
+        assert_eq!(parent_path("port"), None);
+    }
+
+    #[cfg(feature = "toml")]
+    mod toml {
+        use super::*;
+
+        fn document(contents: &str) -> SourceDocument {
+            SourceDocument::new("app.toml", FileFormat::Toml, contents)
+        }
+
+        #[test]
+        fn attach_should_locate_nested_mismatch() {
+            let doc = document("[database]\nport = \"abc\"\n");
+            let err = doc.attach(ConfigError::nested(
+                "database",
+                ConfigError::mismatch("port", "integer", "abc"),
+            ));
+
+            let ConfigError::Nested { source, .. } = err else {
+                panic!("expected nested error");
+            };
+            let inner = downcast_nested(source).unwrap();
+            assert!(matches!(
+                inner,
+                ConfigError::TypeMismatch { span: Some(span), src: Some(_), .. }
+                    if span.offset() == 18 && span.len() == 5
+            ));
+        }
+
+        #[test]
+        fn attach_should_skip_redacted_mismatches_only() {
+            let doc = document("pin = \"[redacted]\"\n");
+            let redacted = doc.attach(ConfigError::mismatch("pin", "integer", "12ab").redact());
+            let literal = doc.attach(ConfigError::mismatch("pin", "integer", "[redacted]"));
+
+            assert!(matches!(redacted, ConfigError::TypeMismatch { span: None, .. }));
+            assert!(matches!(literal, ConfigError::TypeMismatch { span: Some(_), .. }));
+        }
+
+        #[test]
+        fn attach_should_point_missing_value_at_parent() {
+            let doc = document("[database]\nhost = \"db\"\n");
+            let err = doc.attach(ConfigError::nested("database", ConfigError::missing("port")));
+
+            let ConfigError::Nested { source, .. } = err else {
+                panic!("expected nested error");
+            };
+            let inner = downcast_nested(source).unwrap();
+            assert!(matches!(inner, ConfigError::MissingValue { span: Some(_), .. }));
+        }
+
+        #[test]
+        fn attach_should_keep_parse_error_span() {
+            let doc = document("port = ");
+            let err = doc.parse().unwrap_err();
+            assert!(matches!(err, ConfigError::Toml { span: Some(_), src: Some(_), .. }));
+        }
+
+        #[test]
+        fn source_map_should_prefer_latest_layer() {
+            let mut map = SourceMap::new();
+            let base = document("port = 1\n");
+            let overlay = SourceDocument::new("override.toml", FileFormat::Toml, "\nport = 2\n");
+            map.push("app.toml", 10, base.parse().unwrap(), Some(base));
+            map.push("override.toml", 20, overlay.parse().unwrap(), Some(overlay));
+
+            let (document, span) = map.locate("port").unwrap();
+            assert_eq!(document.name(), "override.toml");
+            assert_eq!(span.offset(), 8);
+        }
+
+        #[test]
+        fn source_map_should_skip_values_without_document() {
+            let mut map = SourceMap::new();
+            let base = document("port = 1\n");
+            map.push("app.toml", 10, base.parse().unwrap(), Some(base));
+            let mut env = BTreeMap::new();
+            env.insert("port".to_string(), ConfigNode::Scalar("x".into()));
+            map.push("env APP_*", 100, ConfigNode::Table(env), None);
+
+            assert!(map.locate("port").is_none());
+        }
+
+        #[test]
+        fn source_map_should_report_origin_with_location() {
+            let mut map = SourceMap::new();
+            let base = document("name = \"api\"\n[database]\nport = 5432\n");
+            map.push("app.toml", 10, base.parse().unwrap(), Some(base));
+
+            let provenance = map.provenance(&map.layers[0].node.clone());
+            let origin = provenance.get("database.port").unwrap();
+            assert_eq!(origin.to_string(), "app.toml:3:8 (priority 10)");
+            assert_eq!(origin.value.as_deref(), Some("5432"));
+            assert_eq!(provenance.len(), 2);
+        }
+    }
+}
@@ -0,0 +1,317 @@
+//! Human-readable durations and byte sizes.
+//!
+//! [`Duration`] loads from `"30s"`, `"1h30m"`, `"250ms"` or a bare number of
+//! seconds; [`ByteSize`] from `"512MiB"`, `"1.5GB"` or a bare number of bytes.
+//! Both are written back in the same compact notation.
+
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+
+use crate::{ConfigError, ConfigNode, FromNode, ToNode, schema};
+
+pub(crate) const DURATION_EXPECTED: &str = "duration like \"30s\", \"1h30m\" or \"250ms\"";
+pub(crate) const BYTE_SIZE_EXPECTED: &str = "byte size like \"512MiB\" or \"1.5GB\"";
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// A number of bytes, loaded from values such as `"512MiB"` or `"1.5GB"`.
+///
+/// Units are case-insensitive: `KB`, `MB`, `GB`, `TB` and `PB` are powers of
+/// 1000, `KiB` through `PiB` powers of 1024, and bare numbers are bytes.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ByteSize(pub u64);
+
+impl ByteSize {
+    pub const fn new(bytes: u64) -> Self {
+        Self(bytes)
+    }
+
+    pub const fn kib(count: u64) -> Self {
+        Self(count * 1024)
+    }
+
+    pub const fn mib(count: u64) -> Self {
+        Self(count * 1024 * 1024)
+    }
+
+    pub const fn gib(count: u64) -> Self {
+        Self(count * 1024 * 1024 * 1024)
+    }
+
+    /// The size in bytes.
+    pub const fn as_u64(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for ByteSize {
+    fn from(bytes: u64) -> Self {
+        Self(bytes)
+    }
+}
+
+/// Uses the largest unit that represents the size exactly, preferring
+/// binary units: `512MiB`, `1500MB`, `1023B`.
+impl Display for ByteSize {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        const UNITS: [(&str, u64); 10] = [
+            ("PiB", 1 << 50),
+            ("TiB", 1 << 40),
+            ("GiB", 1 << 30),
+            ("MiB", 1 << 20),
+            ("KiB", 1 << 10),
+            ("PB", 1_000_000_000_000_000),
+            ("TB", 1_000_000_000_000),
+            ("GB", 1_000_000_000),
+            ("MB", 1_000_000),
+            ("kB", 1_000),
+        ];
+
+        let bytes = self.0;
+        let unit = UNITS
+            .iter()
+            .find(|(_, size)| bytes >= *size && bytes.is_multiple_of(*size));
+        match unit {
+            Some((name, size)) => write!(f, "{}{name}", bytes / size),
+            None => write!(f, "{bytes}B"),
+        }
+    }
+}
+
+impl FromNode for ByteSize {
+    fn from_node(node: &ConfigNode, key: &str) -> Result<Self, ConfigError> {
+        let bytes = match node {
+            ConfigNode::Null => return Err(ConfigError::missing(key)),
+            ConfigNode::Integer(value) => u64::try_from(*value).ok(),
+            ConfigNode::Float(value) if value.fract() == 0.0 && *value >= 0.0 => {
+                Some(*value as u64)
+            },
+            ConfigNode::Scalar(text) | ConfigNode::String(text) => parse_byte_size(text),
+            other => {
+                return Err(ConfigError::mismatch(key, BYTE_SIZE_EXPECTED, other.kind()));
+            },
+        };
+        bytes
+            .map(ByteSize)
+            .ok_or_else(|| ConfigError::mismatch(key, BYTE_SIZE_EXPECTED, node.to_string()))
+    }
+
+    fn json_schema() -> ConfigNode {
+        schema::one_of([schema::of_type("string"), schema::integer(Some(0), None)])
+    }
+}
+
+impl ToNode for ByteSize {
+    fn to_node(&self) -> ConfigNode {
+        ConfigNode::String(self.to_string())
+    }
+}
+
+impl FromNode for Duration {
+    fn from_node(node: &ConfigNode, key: &str) -> Result<Self, ConfigError> {
+        let duration = match node {
+            ConfigNode::Null => return Err(ConfigError::missing(key)),
+            ConfigNode::Integer(secs) => u64::try_from(*secs).ok().map(Duration::from_secs),
+            ConfigNode::Float(secs) => Duration::try_from_secs_f64(*secs).ok(),
+            ConfigNode::Scalar(text) | ConfigNode::String(text) => parse_duration(text),
+            other => {
+                return Err(ConfigError::mismatch(key, DURATION_EXPECTED, other.kind()));
+            },
+        };
+        duration.ok_or_else(|| ConfigError::mismatch(key, DURATION_EXPECTED, node.to_string()))
+    }
+
+    fn json_schema() -> ConfigNode {
+        let seconds = schema::with(schema::of_type("number"), "minimum", ConfigNode::Integer(0));
+        schema::one_of([schema::of_type("string"), seconds])
+    }
+}
+
+impl ToNode for Duration {
+    fn to_node(&self) -> ConfigNode {
+        ConfigNode::String(format_duration(*self))
+    }
+}
+
+/// Render `duration` as `1h30m`, `250ms` or `0s`.
+pub(crate) fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let nanos = duration.subsec_nanos();
+    let parts = [
+        (secs / 3600, "h"),
+        (secs / 60 % 60, "m"),
+        (secs % 60, "s"),
+        (u64::from(nanos / 1_000_000), "ms"),
+        (u64::from(nanos / 1_000 % 1_000), "us"),
+        (u64::from(nanos % 1_000), "ns"),
+    ];
+
+    let text: String = parts
+        .iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, unit)| format!("{count}{unit}"))
+        .collect();
+    if text.is_empty() { "0s".to_string() } else { text }
+}
+
+/// Parse a sequence of `<number><unit>` parts such as `1h 30m` or `1.5s`.
+/// A bare number is a count of seconds.
+pub(crate) fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    if let Some(nanos) = parse_decimal(text, NANOS_PER_SEC) {
+        return to_duration(nanos);
+    }
+
+    let mut total: u128 = 0;
+    let mut rest = text;
+    while !rest.is_empty() {
+        let (number, unit, tail) = split_part(rest)?;
+        let per_unit: u128 = match unit {
+            "ns" => 1,
+            "us" | "µs" => 1_000,
+            "ms" => 1_000_000,
+            "s" | "sec" | "secs" => NANOS_PER_SEC,
+            "m" | "min" | "mins" => 60 * NANOS_PER_SEC,
+            "h" | "hr" | "hrs" => 3600 * NANOS_PER_SEC,
+            "d" | "day" | "days" => 86_400 * NANOS_PER_SEC,
+            _ => return None,
+        };
+        total = total.checked_add(parse_decimal(number, per_unit)?)?;
+        rest = tail.trim_start();
+    }
+    to_duration(total)
+}
+
+/// Parse a number with an optional unit, such as `512MiB` or `1.5 GB`.
+pub(crate) fn parse_byte_size(text: &str) -> Option<u64> {
+    let (number, unit, tail) = split_part(text.trim())?;
+    if !tail.trim().is_empty() {
+        return None;
+    }
+    let per_unit: u128 = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1_000,
+        "m" | "mb" => 1_000_000,
+        "g" | "gb" => 1_000_000_000,
+        "t" | "tb" => 1_000_000_000_000,
+        "p" | "pb" => 1_000_000_000_000_000,
+        "ki" | "kib" => 1 << 10,
+        "mi" | "mib" => 1 << 20,
+        "gi" | "gib" => 1 << 30,
+        "ti" | "tib" => 1 << 40,
+        "pi" | "pib" => 1 << 50,
+        _ => return None,
+    };
+    u64::try_from(parse_decimal(number, per_unit)?).ok()
+}
+
+/// Split `text` into a leading decimal number, the unit that follows it and
+/// the remainder.
+fn split_part(text: &str) -> Option<(&str, &str, &str)> {
+    let number_end = text
+        .find(|ch: char| !ch.is_ascii_digit() && ch != '.')
+        .unwrap_or(text.len());
+    if number_end == 0 {
+        return None;
+    }
+    let (number, rest) = text.split_at(number_end);
+    let rest = rest.trim_start();
+    let unit_end = rest
+        .find(|ch: char| !ch.is_alphabetic())
+        .unwrap_or(rest.len());
+    let (unit, tail) = rest.split_at(unit_end);
+    Some((number, unit, tail))
+}
+
+/// `number * per_unit` for a non-negative decimal `number`, truncating
+/// anything below one unit of the result.
+fn parse_decimal(number: &str, per_unit: u128) -> Option<u128> {
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if whole.is_empty() && fraction.is_empty() {
+        return None;
+    }
+    let digits = |part: &str| part.chars().all(|ch| ch.is_ascii_digit());
+    if !digits(whole) || !digits(fraction) || fraction.len() > 18 {
+        return None;
+    }
+
+    let whole: u128 = if whole.is_empty() { 0 } else { whole.parse().ok()? };
+    let scale = 10u128.pow(fraction.len() as u32);
+    let fraction: u128 = if fraction.is_empty() { 0 } else { fraction.parse().ok()? };
+    whole
+        .checked_mul(per_unit)?
+        .checked_add(fraction.checked_mul(per_unit)? / scale)
+}
+
+fn to_duration(nanos: u128) -> Option<Duration> {
+    let secs = u64::try_from(nanos / NANOS_PER_SEC).ok()?;
+    Some(Duration::new(secs, (nanos % NANOS_PER_SEC) as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_accept_units_and_bare_seconds() {
+        assert_eq!(parse_duration("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_duration("1h30m"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("1h 30m"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("250ms"), Some(Duration::from_millis(250)));
+        assert_eq!(parse_duration("1.5s"), Some(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("90"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration("0.25"), Some(Duration::from_millis(250)));
+
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("5 parsecs"), None);
+        assert_eq!(parse_duration("-5s"), None);
+        assert_eq!(parse_duration("1h30"), None);
+    }
+
+    #[test]
+    fn durations_round_trip_through_the_compact_form() {
+        for text in ["1h30m", "250ms", "1m500ms", "2h5s", "0s"] {
+            assert_eq!(format_duration(parse_duration(text).unwrap()), text);
+        }
+    }
+
+    #[test]
+    fn byte_sizes_accept_decimal_and_binary_units() {
+        assert_eq!(parse_byte_size("512MiB"), Some(512 << 20));
+        assert_eq!(parse_byte_size("1.5GB"), Some(1_500_000_000));
+        assert_eq!(parse_byte_size("1.5 gib"), Some(3 << 29));
+        assert_eq!(parse_byte_size("64k"), Some(64_000));
+        assert_eq!(parse_byte_size("100"), Some(100));
+
+        assert_eq!(parse_byte_size("12 bananas"), None);
+        assert_eq!(parse_byte_size("MiB"), None);
+        assert_eq!(parse_byte_size("1MiB extra"), None);
+    }
+
+    #[test]
+    fn byte_sizes_display_in_the_largest_exact_unit() {
+        assert_eq!(ByteSize::mib(512).to_string(), "512MiB");
+        assert_eq!(ByteSize(1_500_000_000).to_string(), "1500MB");
+        assert_eq!(ByteSize(1023).to_string(), "1023B");
+        assert_eq!(ByteSize(0).to_string(), "0B");
+    }
+
+    #[test]
+    fn malformed_values_report_the_expected_notation() {
+        let err = Duration::from_node(&ConfigNode::String("soon".into()), "timeout").unwrap_err();
+        let ConfigError::TypeMismatch { field, expected, found, .. } = err else {
+            panic!("expected type mismatch, got {err:?}");
+        };
+        assert_eq!(field, "timeout");
+        assert_eq!(expected, DURATION_EXPECTED);
+        assert_eq!(found, "soon");
+
+        let err = ByteSize::from_node(&ConfigNode::Boolean(true), "buffer").unwrap_err();
+        assert!(
+            matches!(err, ConfigError::TypeMismatch { expected, .. } if expected == BYTE_SIZE_EXPECTED)
+        );
+    }
+}
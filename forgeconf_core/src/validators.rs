@@ -1,11 +1,13 @@
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::fmt::Display;
 use std::hash::Hash;
+use std::time::Duration;
 
 #[cfg(feature = "regex")]
 use regex::Regex;
 
-use crate::ConfigError;
+use crate::units::{format_duration, parse_byte_size, parse_duration};
+use crate::{ByteSize, ConfigError, ConfigNode, ToNode};
 
 /// Trait for values whose length can be queried.
 pub trait HasLen {
@@ -181,6 +183,122 @@ where
     }
 }
 
+/// Ensure a [`Duration`] falls within bounds written like config values,
+/// as in `duration_range("100ms", "30s")`.
+pub fn duration_range(min: &str, max: &str) -> impl Fn(&Duration, &str) -> Result<(), ConfigError> {
+    let bound = |text: &str| {
+        parse_duration(text).unwrap_or_else(|| panic!("invalid duration bound {text:?}"))
+    };
+    let (min, max) = (bound(min), bound(max));
+    assert!(min <= max, "range lower bound must be less than or equal to the upper bound");
+    let expected = format!("between {} and {}", format_duration(min), format_duration(max));
+
+    move |value, key| {
+        if *value < min || *value > max {
+            Err(ConfigError::mismatch(key, expected.clone(), format_duration(*value)))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Ensure a [`ByteSize`] falls within bounds written like config values,
+/// as in `size_range("4KiB", "1GiB")`.
+pub fn size_range(min: &str, max: &str) -> impl Fn(&ByteSize, &str) -> Result<(), ConfigError> {
+    let bound = |text: &str| {
+        parse_byte_size(text)
+            .map(ByteSize)
+            .unwrap_or_else(|| panic!("invalid byte size bound {text:?}"))
+    };
+    range(bound(min), bound(max))
+}
+
+/// Struct-level validator requiring `field` to be set whenever the value at
+/// `condition` equals `value`, as in `required_if("tls_cert", "tls", true)`.
+///
+/// Struct-level validators are registered with
+/// `#[forgeconf(validate = ...)]` and see the whole struct through its
+/// [`ToNode`] form: paths may reach into nested sections (`server.tls`), and
+/// a field counts as set unless it is missing or `None`.
+pub fn required_if<S, V>(
+    field: impl Into<String>,
+    condition: impl Into<String>,
+    value: V,
+) -> impl Fn(&S) -> Result<(), ConfigError>
+where
+    S: ToNode,
+    V: ToNode,
+{
+    let field = field.into();
+    let condition = condition.into();
+    let expected = value.to_node();
+    move |config| {
+        let node = config.to_node();
+        if node.get_path(&condition) != Some(&expected) || is_set(&node, &field) {
+            return Ok(());
+        }
+        Err(ConfigError::constraint(
+            [field.clone(), condition.clone()],
+            format!("`{field}` is required when `{condition}` is {expected}"),
+        ))
+    }
+}
+
+/// Struct-level validator allowing at most one of `fields` to be set; see
+/// [`required_if`].
+pub fn mutually_exclusive<S, I>(fields: I) -> impl Fn(&S) -> Result<(), ConfigError>
+where
+    S: ToNode,
+    I: IntoIterator,
+    I::Item: Into<String>,
+{
+    let fields: Vec<String> = fields.into_iter().map(Into::into).collect();
+    move |config| {
+        let node = config.to_node();
+        let set: Vec<&String> = fields.iter().filter(|field| is_set(&node, field)).collect();
+        if set.len() <= 1 {
+            return Ok(());
+        }
+        Err(ConfigError::constraint(
+            set.iter().map(|field| field.as_str()),
+            format!("only one of {} may be set", quote_list(&fields)),
+        ))
+    }
+}
+
+/// Struct-level validator requiring at least one of `fields` to be set; see
+/// [`required_if`].
+pub fn at_least_one_of<S, I>(fields: I) -> impl Fn(&S) -> Result<(), ConfigError>
+where
+    S: ToNode,
+    I: IntoIterator,
+    I::Item: Into<String>,
+{
+    let fields: Vec<String> = fields.into_iter().map(Into::into).collect();
+    move |config| {
+        let node = config.to_node();
+        if fields.iter().any(|field| is_set(&node, field)) {
+            return Ok(());
+        }
+        Err(ConfigError::constraint(
+            fields.clone(),
+            format!("at least one of {} must be set", quote_list(&fields)),
+        ))
+    }
+}
+
+fn is_set(node: &ConfigNode, path: &str) -> bool {
+    !matches!(node.get_path(path), None | Some(ConfigNode::Null))
+}
+
+fn quote_list(fields: &[String]) -> String {
+    fields
+        .iter()
+        .map(|field| format!("`{field}`"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -213,6 +331,26 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn unit_ranges_report_bounds_in_units() {
+        let timeout = duration_range("100ms", "30s");
+        timeout(&Duration::from_secs(5), "timeout").unwrap();
+        let err = timeout(&Duration::from_secs(90), "timeout").unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::TypeMismatch { expected, found, .. }
+                if expected == "between 100ms and 30s" && found == "1m30s"
+        ));
+
+        let buffer = size_range("4KiB", "1GiB");
+        let err = buffer(&ByteSize(512), "buffer").unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::TypeMismatch { expected, found, .. }
+                if expected == "between 4KiB and 1GiB" && found == "512B"
+        ));
+    }
+
     #[test]
     fn min_len_flags_short_values() {
         let validator = min_len::<String>(3);
@@ -258,6 +396,49 @@ mod tests {
         ));
     }
 
+    fn tls_config(tls: bool, cert: Option<&str>, key: Option<&str>) -> ConfigNode {
+        let mut map = BTreeMap::new();
+        map.insert("tls".to_string(), tls.to_node());
+        map.insert("cert".to_string(), cert.map(str::to_string).to_node());
+        map.insert("key".to_string(), key.map(str::to_string).to_node());
+        ConfigNode::Table(map)
+    }
+
+    #[test]
+    fn required_if_only_applies_when_the_condition_holds() {
+        let validator = required_if("cert", "tls", true);
+        validator(&tls_config(false, None, None)).unwrap();
+        validator(&tls_config(true, Some("cert.pem"), None)).unwrap();
+
+        let err = validator(&tls_config(true, None, None)).unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::Constraint { fields, reason, .. }
+                if fields == ["cert", "tls"] && reason == "`cert` is required when `tls` is true"
+        ));
+    }
+
+    #[test]
+    fn mutually_exclusive_reports_the_fields_that_are_set() {
+        let validator = mutually_exclusive(["cert", "key", "missing"]);
+        validator(&tls_config(true, Some("a"), None)).unwrap();
+
+        let err = validator(&tls_config(true, Some("a"), Some("b"))).unwrap_err();
+        assert!(matches!(err, ConfigError::Constraint { fields, .. } if fields == ["cert", "key"]));
+    }
+
+    #[test]
+    fn at_least_one_of_requires_a_set_field() {
+        let validator = at_least_one_of(["cert", "key"]);
+        validator(&tls_config(false, None, Some("b"))).unwrap();
+
+        let err = validator(&tls_config(false, None, None)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid combination of cert, key: at least one of `cert`, `key` must be set"
+        );
+    }
+
     #[cfg(feature = "regex")]
     #[test]
     fn matches_regex_enforces_pattern() {
@@ -1,12 +1,15 @@
 use proc_macro::TokenStream;
-use syn::{ItemStruct, Result};
+use syn::{Error, Item, Result};
 
 mod model;
 mod render;
 
-use model::{ForgeconfAttr, collect_fields};
+use model::{ForgeconfAttr, collect_fields, collect_variants};
 
 /// Derive loader and parsing logic for a configuration struct.
+///
+/// On enums, derives `FromNode` for unit variants (from strings) and for
+/// externally or internally (`tag = "..."`) tagged data variants.
 #[proc_macro_attribute]
 pub fn forgeconf(attr: TokenStream, item: TokenStream) -> TokenStream {
     match expand(attr, item) {
@@ -17,8 +20,36 @@ pub fn forgeconf(attr: TokenStream, item: TokenStream) -> TokenStream {
 
 fn expand(attr: TokenStream, item: TokenStream) -> Result<TokenStream> {
     let args = syn::parse::<ForgeconfAttr>(attr)?;
-    let mut item = syn::parse::<ItemStruct>(item)?;
-    let fields = collect_fields(&mut item)?;
-    let tokens = render::render(&item, &args, &fields)?;
+    let tokens = match syn::parse::<Item>(item)? {
+        Item::Struct(mut item) => {
+            if args.tag.is_some() || args.insensitive {
+                return Err(Error::new_spanned(
+                    &item.ident,
+                    "`tag` and `insensitive` are only supported on enums",
+                ));
+            }
+            let fields = collect_fields(&mut item.fields)?;
+            render::render(&item, &args, &fields)?
+        },
+        Item::Enum(mut item) => {
+            if !args.files.is_empty() || !args.validators.is_empty() {
+                return Err(Error::new_spanned(
+                    &item.ident,
+                    "`config(...)` and `validate` are not supported on enums",
+                ));
+            }
+            if !item.generics.params.is_empty() {
+                return Err(Error::new_spanned(
+                    &item.generics,
+                    "#[forgeconf] does not support generic enums",
+                ));
+            }
+            let variants = collect_variants(&mut item)?;
+            render::render_enum(&item, &args, &variants)?
+        },
+        other => {
+            return Err(Error::new_spanned(other, "#[forgeconf] supports structs and enums"));
+        },
+    };
     Ok(tokens.into())
 }
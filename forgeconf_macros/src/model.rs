@@ -1,4 +1,4 @@
-use forgeconf_core::FileFormat;
+use forgeconf_core::{FileFormat, MergeStrategy};
 use proc_macro2::Span;
 use syn::parse::{Parse, ParseStream};
 use syn::punctuated::Punctuated;
@@ -7,12 +7,15 @@ use syn::{
     Error,
     Expr,
     Field,
+    Fields,
+    GenericArgument,
     Ident,
-    ItemStruct,
+    ItemEnum,
     LitBool,
     LitChar,
     LitInt,
     LitStr,
+    PathArguments,
     Result,
     Token,
     Type,
@@ -21,12 +24,36 @@ use syn::{
 #[derive(Default)]
 pub struct ForgeconfAttr {
     pub files: Vec<ConfigFile>,
+    /// Enum-only: name of the key holding the variant of internally tagged
+    /// enums.
+    pub tag: Option<String>,
+    /// Enum-only: match variant names case-insensitively.
+    pub insensitive: bool,
+    /// What to do with keys that no field consumed.
+    pub unknown_fields: UnknownFields,
+    /// Struct-only: validators run on the built struct, in order.
+    pub validators: Vec<Expr>,
+    /// Generate `to_node()` and a `ToNode` impl, which need `ToNode` on
+    /// every field type.
+    pub to_node: bool,
+}
+
+/// Handling of leftover keys, set by `deny_unknown_fields` or
+/// `warn_unknown_fields = callback`.
+#[derive(Default)]
+pub enum UnknownFields {
+    #[default]
+    Ignore,
+    Deny,
+    Warn(Expr),
 }
 
 pub struct ConfigFile {
     pub path: Expr,
     pub format: Option<FileFormat>,
     pub priority: Option<u8>,
+    pub optional: bool,
+    pub includes: bool,
 }
 
 #[derive(Clone)]
@@ -36,6 +63,27 @@ pub struct FieldSpec {
     pub options: FieldOptions,
 }
 
+impl FieldSpec {
+    /// Whether values of this field must be kept out of errors and dumps.
+    /// `#[field(secret)]` is only accepted on `Secret<T>` types, which are
+    /// secret with or without it.
+    pub fn is_secret(&self) -> bool {
+        is_secret_type(&self.ty)
+    }
+}
+
+pub struct VariantSpec {
+    pub ident: Ident,
+    pub name: String,
+    pub shape: VariantShape,
+}
+
+pub enum VariantShape {
+    Unit,
+    Newtype(Type),
+    Struct(Vec<FieldSpec>),
+}
+
 #[derive(Clone, Default)]
 pub struct FieldOptions {
     pub rename: Option<String>,
@@ -49,28 +97,63 @@ pub struct FieldOptions {
     pub short: Option<char>,
     pub help: Option<String>,
     pub no_cli: bool,
+    pub strict: bool,
+    pub merge: Option<MergeStrategy>,
+    pub secret: bool,
+    pub relative_to_config: bool,
+    pub expand_path: bool,
+    pub separator: Option<String>,
+    pub count: bool,
 }
 
 impl Parse for ForgeconfAttr {
     fn parse(input: ParseStream) -> Result<Self> {
-        let mut files = Vec::new();
+        let mut attr = ForgeconfAttr::default();
 
         while !input.is_empty() {
             let ident: Ident = input.parse()?;
-            if ident != "config" {
-                return Err(Error::new(ident.span(), "expected `config(...)`"));
+            match ident.to_string().as_str() {
+                "config" => {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    attr.files.push(ConfigFile::parse(&content)?);
+                },
+                "tag" => {
+                    input.parse::<Token![=]>()?;
+                    attr.tag = Some(input.parse::<LitStr>()?.value());
+                },
+                "insensitive" => {
+                    input.parse::<Token![=]>()?;
+                    attr.insensitive = input.parse::<LitBool>()?.value();
+                },
+                "deny_unknown_fields" => attr.unknown_fields = UnknownFields::Deny,
+                "to_node" => attr.to_node = true,
+                "validate" => {
+                    input.parse::<Token![=]>()?;
+                    attr.validators.push(input.parse()?);
+                },
+                "warn_unknown_fields" => {
+                    input.parse::<Token![=]>()?;
+                    attr.unknown_fields = UnknownFields::Warn(input.parse()?);
+                },
+                other => {
+                    return Err(Error::new(
+                        ident.span(),
+                        format!(
+                            "unknown argument `{other}`; expected `config(...)` or `validate` on \
+                             structs, `tag` or `insensitive` on enums, `deny_unknown_fields`, \
+                             `warn_unknown_fields` or `to_node` on either"
+                        ),
+                    ));
+                },
             }
 
-            let content;
-            syn::parenthesized!(content in input);
-            files.push(ConfigFile::parse(&content)?);
-
             if input.peek(Token![,]) {
                 input.parse::<Token![,]>()?;
             }
         }
 
-        Ok(Self { files })
+        Ok(attr)
     }
 }
 
@@ -79,6 +162,8 @@ impl ConfigFile {
         let mut path = None;
         let mut format = None;
         let mut priority = None;
+        let mut optional = false;
+        let mut includes = false;
 
         while !input.is_empty() {
             let ident: Ident = input.parse()?;
@@ -100,6 +185,12 @@ impl ConfigFile {
                     let lit: LitInt = input.parse()?;
                     priority = Some(lit.base10_parse()?);
                 },
+                "optional" => {
+                    optional = input.parse::<LitBool>()?.value();
+                },
+                "includes" => {
+                    includes = input.parse::<LitBool>()?.value();
+                },
                 other => {
                     return Err(Error::new(ident.span(), format!("unknown argument `{other}`")));
                 },
@@ -111,20 +202,72 @@ impl ConfigFile {
         }
 
         let path = path.ok_or(Error::new(Span::call_site(), "missing `path`"))?;
-        Ok(Self { path, format, priority })
+        Ok(Self {
+            path,
+            format,
+            priority,
+            optional,
+            includes,
+        })
     }
 }
 
-pub fn collect_fields(item: &mut ItemStruct) -> Result<Vec<FieldSpec>> {
+pub fn collect_fields(fields: &mut Fields) -> Result<Vec<FieldSpec>> {
     let mut specs = Vec::new();
 
-    for field in item.fields.iter_mut() {
+    for field in fields.iter_mut() {
         specs.push(parse_field(field)?);
     }
 
     Ok(specs)
 }
 
+pub fn collect_variants(item: &mut ItemEnum) -> Result<Vec<VariantSpec>> {
+    let mut specs = Vec::new();
+
+    for variant in item.variants.iter_mut() {
+        let mut name = None;
+        let mut retained = Vec::new();
+        for attr in variant.attrs.drain(..) {
+            if attr.path().is_ident("variant") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("name") {
+                        name = Some(meta.value()?.parse::<LitStr>()?.value());
+                        Ok(())
+                    } else {
+                        Err(meta.error("unknown variant attribute"))
+                    }
+                })?;
+            } else {
+                retained.push(attr);
+            }
+        }
+        variant.attrs = retained;
+
+        let shape = match &mut variant.fields {
+            Fields::Unit => VariantShape::Unit,
+            Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => {
+                VariantShape::Newtype(unnamed.unnamed[0].ty.clone())
+            },
+            Fields::Unnamed(unnamed) => {
+                return Err(Error::new(
+                    unnamed.span(),
+                    "tuple variants must have exactly one field",
+                ));
+            },
+            fields @ Fields::Named(_) => VariantShape::Struct(collect_fields(fields)?),
+        };
+
+        specs.push(VariantSpec {
+            name: name.unwrap_or_else(|| to_snake_case(&variant.ident.to_string())),
+            ident: variant.ident.clone(),
+            shape,
+        });
+    }
+
+    Ok(specs)
+}
+
 fn parse_field(field: &mut Field) -> Result<FieldSpec> {
     let ident = field
         .ident
@@ -169,6 +312,21 @@ impl FieldOptions {
                 MetaEntry::Short(lit) => options.short = Some(lit.value()),
                 MetaEntry::Help(value) => options.help = Some(value.value()),
                 MetaEntry::NoCli => options.no_cli = true,
+                MetaEntry::Strict => options.strict = true,
+                MetaEntry::Secret => options.secret = true,
+                MetaEntry::RelativeToConfig => options.relative_to_config = true,
+                MetaEntry::ExpandPath => options.expand_path = true,
+                MetaEntry::Separator(value) => options.separator = Some(value.value()),
+                MetaEntry::Count => options.count = true,
+                MetaEntry::Merge(lit) => {
+                    let strategy = lit.value().parse::<MergeStrategy>().map_err(|_| {
+                        Error::new(
+                            lit.span(),
+                            "expected `replace`, `append`, `prepend` or `merge_by_key(<key>)`",
+                        )
+                    })?;
+                    options.merge = Some(strategy);
+                },
             }
         }
 
@@ -187,6 +345,13 @@ impl FieldOptions {
         self.short = other.short.or(self.short);
         self.help = other.help.or(self.help.take());
         self.no_cli |= other.no_cli;
+        self.strict |= other.strict;
+        self.merge = other.merge.or(self.merge.take());
+        self.secret |= other.secret;
+        self.relative_to_config |= other.relative_to_config;
+        self.expand_path |= other.expand_path;
+        self.separator = other.separator.or(self.separator.take());
+        self.count |= other.count;
     }
 
     fn validate(&self, ty: &Type, ident: &Ident) -> Result<()> {
@@ -196,6 +361,16 @@ impl FieldOptions {
         if self.optional && self.default.is_some() {
             return Err(Error::new(ident.span(), "an optional field cannot declare a default"));
         }
+        if self.count && scalar_type_name(ty).as_deref() != Some("u8") {
+            return Err(Error::new(ident.span(), "count fields must use u8"));
+        }
+        if self.secret && !is_secret_type(ty) {
+            return Err(Error::new(
+                ident.span(),
+                "secret fields must use Secret<T> (or an Option / Vec of one), which also keeps \
+                 the value out of `Debug` output",
+            ));
+        }
         Ok(())
     }
 }
@@ -212,6 +387,13 @@ enum MetaEntry {
     Short(LitChar),
     Help(LitStr),
     NoCli,
+    Strict,
+    Merge(LitStr),
+    Secret,
+    RelativeToConfig,
+    ExpandPath,
+    Separator(LitStr),
+    Count,
 }
 
 impl Parse for MetaEntry {
@@ -225,6 +407,21 @@ impl Parse for MetaEntry {
         if ident == "no_cli" {
             return Ok(MetaEntry::NoCli);
         }
+        if ident == "strict" {
+            return Ok(MetaEntry::Strict);
+        }
+        if ident == "secret" {
+            return Ok(MetaEntry::Secret);
+        }
+        if ident == "relative_to_config" {
+            return Ok(MetaEntry::RelativeToConfig);
+        }
+        if ident == "expand_path" {
+            return Ok(MetaEntry::ExpandPath);
+        }
+        if ident == "count" {
+            return Ok(MetaEntry::Count);
+        }
 
         input.parse::<Token![=]>()?;
 
@@ -238,6 +435,8 @@ impl Parse for MetaEntry {
             "validate" => Ok(MetaEntry::Validator(input.parse()?)),
             "short" => Ok(MetaEntry::Short(input.parse()?)),
             "help" => Ok(MetaEntry::Help(input.parse()?)),
+            "merge" => Ok(MetaEntry::Merge(input.parse()?)),
+            "separator" => Ok(MetaEntry::Separator(input.parse()?)),
             other => Err(Error::new(ident.span(), format!("unknown field attribute `{other}`"))),
         }
     }
@@ -252,7 +451,7 @@ pub fn is_vec_type(ty: &Type) -> bool {
     false
 }
 
-fn is_option_type(ty: &Type) -> bool {
+pub fn is_option_type(ty: &Type) -> bool {
     if let Type::Path(path) = ty
         && let Some(segment) = path.path.segments.last()
     {
@@ -261,3 +460,81 @@ fn is_option_type(ty: &Type) -> bool {
 
     false
 }
+
+/// Name of a plain, non-generic type such as `u16` or `String`, looking
+/// through an `Option`.
+pub fn scalar_type_name(ty: &Type) -> Option<String> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    match &segment.arguments {
+        PathArguments::None => Some(segment.ident.to_string()),
+        PathArguments::AngleBracketed(args) if segment.ident == "Option" => {
+            match args.args.first()? {
+                GenericArgument::Type(inner) => scalar_type_name(inner),
+                _ => None,
+            }
+        },
+        _ => None,
+    }
+}
+
+/// Name and arguments of a call to one of the built-in validators, written
+/// either bare (`range(1, 10)`) or through the `validators` module.
+pub fn validator_call(expr: &Expr) -> Option<(String, Vec<&Expr>)> {
+    let Expr::Call(call) = expr else { return None };
+    let Expr::Path(path) = &*call.func else { return None };
+    let segments: Vec<String> = path
+        .path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    let (name, parents) = segments.split_last()?;
+    if !parents.is_empty() && parents.last().map(String::as_str) != Some("validators") {
+        return None;
+    }
+    Some((name.clone(), call.args.iter().collect()))
+}
+
+/// `Secret<T>`, or an `Option` / `Vec` of one.
+pub fn is_secret_type(ty: &Type) -> bool {
+    let Type::Path(path) = ty else {
+        return false;
+    };
+    let Some(segment) = path.path.segments.last() else {
+        return false;
+    };
+    if segment.ident == "Secret" {
+        return true;
+    }
+    if segment.ident != "Option" && segment.ident != "Vec" {
+        return false;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => args.args.iter().any(|arg| match arg {
+            GenericArgument::Type(inner) => is_secret_type(inner),
+            _ => false,
+        }),
+        _ => false,
+    }
+}
+
+/// Convert a `CamelCase` identifier into `snake_case`. A run of capitals is
+/// kept as one word, so `HTTPServer` becomes `http_server`.
+fn to_snake_case(ident: &str) -> String {
+    let chars: Vec<char> = ident.chars().collect();
+    let mut out = String::with_capacity(ident.len() + 4);
+    for (index, &ch) in chars.iter().enumerate() {
+        if ch.is_uppercase() && index > 0 {
+            let prev = chars[index - 1];
+            let next_is_lower = chars.get(index + 1).is_some_and(|next| next.is_lowercase());
+            if !prev.is_uppercase() || next_is_lower {
+                out.push('_');
+            }
+        }
+        out.extend(ch.to_lowercase());
+    }
+    out
+}
@@ -1,11 +1,15 @@
+use forgeconf_core::MergeStrategy;
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
-use syn::{ItemStruct, Result};
+use syn::{Error, Expr, GenericParam, Generics, ItemStruct, LitStr, Result, parse_quote};
 
 use crate::model::{ConfigFile, FieldSpec, ForgeconfAttr};
 
 mod clap;
 mod field;
+mod variant;
+
+pub use variant::render_enum;
 
 pub fn render(
     item: &ItemStruct,
@@ -13,15 +17,50 @@ pub fn render(
     fields: &[FieldSpec],
 ) -> Result<TokenStream> {
     let ident = &item.ident;
+    let vis = &item.vis;
     let loader_ident = format_ident!("{}Loader", ident);
+    let generics = bounded_generics(&item.generics, args.to_node)?;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let self_ty = quote! { #ident #ty_generics };
+    let loader_ty = quote! { #loader_ident #ty_generics };
+    // `BuildFn` is a boxed closure, which requires `'static` type parameters.
+    let mut reload_generics = generics.clone();
+    for param in reload_generics.type_params_mut() {
+        param.bounds.push(parse_quote!('static));
+    }
+    let (reload_impl_generics, _, reload_where_clause) = reload_generics.split_for_impl();
 
     let add_config_stmts = args.files.iter().map(render_config_addition);
-    let field_inits = fields.iter().map(field::render_field_init);
+    let struct_init = field::render_struct_init(quote! { Self }, fields, &args.unknown_fields);
+    let struct_validation = render_struct_validators(&args.validators);
+
+    let merge_entries = fields.iter().map(render_merge_entries);
+    let path_entries = fields.iter().map(render_path_entries);
+    let to_node_impl = args.to_node.then(|| {
+        let table_node = field::render_table_node(fields, |ident| quote! { &self.#ident });
+        quote! {
+            impl #impl_generics #self_ty #where_clause {
+                /// Convert the struct back into a tree, the inverse of
+                /// `load_from`. Render it with `ConfigNode::to_string_as` to
+                /// dump the effective configuration.
+                pub fn to_node(&self) -> ::forgeconf::ConfigNode {
+                    #table_node
+                }
+            }
 
+            impl #impl_generics ::forgeconf::ToNode for #self_ty #where_clause {
+                fn to_node(&self) -> ::forgeconf::ConfigNode {
+                    Self::to_node(self)
+                }
+            }
+        }
+    });
+    let object_schema = field::render_object_schema(fields, &args.unknown_fields);
     let parse_methods = generate_parse_methods();
     let clap_methods = clap::generate_clap_methods();
-    let forgeconf_clap_impl = clap::generate_forgeconf_clap_impl(ident, fields);
-    let clap_companion = clap::generate_clap_companion(ident, fields);
+    let clap_generics = clap::clap_generics(&generics, fields);
+    let forgeconf_clap_impl = clap::generate_forgeconf_clap_impl(ident, &clap_generics, fields);
+    let clap_companion = clap::generate_clap_companion(ident, &clap_generics, fields);
 
     let result = quote! {
         // Allow unexpected_cfgs to prevent warnings about parse/toml/yaml/json features
@@ -30,49 +69,234 @@ pub fn render(
         #item
 
         #[allow(unexpected_cfgs)]
-        impl #ident {
-            pub fn loader() -> #loader_ident {
+        impl #impl_generics #self_ty #where_clause {
+            pub fn loader() -> #loader_ty {
                 let mut __builder = ::forgeconf::ConfigBuilder::new();
                 #(#add_config_stmts)*
-                #loader_ident { builder: __builder }
+                for (path, strategy) in Self::merge_strategies() {
+                    __builder = __builder.merge_strategy(path, strategy);
+                }
+                for (path, options) in Self::path_options() {
+                    __builder = __builder.resolve_path(path, options);
+                }
+                #loader_ident {
+                    builder: __builder,
+                    collect_errors: false,
+                    __marker: ::std::marker::PhantomData,
+                }
+            }
+
+            /// Merge strategies declared with `#[field(merge = "...")]`,
+            /// including those of nested sections, keyed by path.
+            pub fn merge_strategies() -> Vec<(String, ::forgeconf::MergeStrategy)> {
+                #[allow(unused_mut)]
+                let mut strategies = Vec::new();
+                #(#merge_entries)*
+                strategies
+            }
+
+            /// Path fields declared with `#[field(relative_to_config)]` or
+            /// `#[field(expand_path)]`, including those of nested sections,
+            /// keyed by path.
+            pub fn path_options() -> Vec<(String, ::forgeconf::PathOptions)> {
+                #[allow(unused_mut)]
+                let mut options = Vec::new();
+                #(#path_entries)*
+                options
+            }
+
+            /// JSON Schema (draft 2020-12) describing the files this struct
+            /// loads from, including nested sections. Render it with
+            /// `ConfigNode::to_string_as(FileFormat::Json)`.
+            pub fn json_schema() -> ::forgeconf::ConfigNode {
+                ::forgeconf::schema::document(
+                    stringify!(#ident),
+                    <Self as ::forgeconf::FromNode>::json_schema(),
+                )
             }
 
+            /// Annotated example file for this struct: `help` text as
+            /// comments, defaults filled in, placeholders for required
+            /// fields, and nested sections. JSON output has no comments.
+            pub fn example_config(
+                format: ::forgeconf::FileFormat,
+            ) -> Result<String, ::forgeconf::ConfigError> {
+                ::forgeconf::schema::example(&<Self as ::forgeconf::FromNode>::json_schema(), format)
+            }
+
+            /// Build the struct from a merged tree, stopping at the first error.
             pub fn load_from(node: &::forgeconf::ConfigNode) -> Result<Self, ::forgeconf::ConfigError> {
+                Self::load_in(node, &mut ::forgeconf::LoadContext::new())
+            }
+
+            /// Build the struct from a merged tree, reporting every missing
+            /// field, type mismatch and validator failure (including those of
+            /// nested sections and collection items) as one
+            /// `ConfigError::Multiple`.
+            pub fn collect_from(node: &::forgeconf::ConfigNode) -> Result<Self, ::forgeconf::ConfigError> {
+                Self::load_in(node, &mut ::forgeconf::LoadContext::collecting())
+            }
+
+            /// Build the struct from a merged tree within a load sharing
+            /// `__cx`, which decides whether to stop at the first error.
+            #[allow(unused_mut)]
+            pub fn load_in(
+                node: &::forgeconf::ConfigNode,
+                __cx: &mut ::forgeconf::LoadContext,
+            ) -> Result<Self, ::forgeconf::ConfigError> {
                 let mut map = node.to_owned_table()?;
-                Ok(Self {
-                    #(#field_inits),*
-                })
+                let __forgeconf_value: Self = #struct_init?;
+                #struct_validation
             }
 
             #parse_methods
             #clap_methods
         }
 
-        pub struct #loader_ident {
+        #vis struct #loader_ident #impl_generics #where_clause {
             builder: ::forgeconf::ConfigBuilder,
+            collect_errors: bool,
+            __marker: ::std::marker::PhantomData<fn() -> #self_ty>,
         }
 
-        impl #loader_ident {
-            pub fn add_source<S>(mut self, source: S) -> Self
-            where
-                S: ::forgeconf::ConfigSource + 'static,
-            {
+        impl #impl_generics #loader_ty #where_clause {
+            pub fn add_source(mut self, source: impl ::forgeconf::ConfigSource + 'static) -> Self {
                 self.builder = self.builder.add_source(source);
                 self
             }
 
-            pub fn load(self) -> Result<#ident, ::forgeconf::ConfigError> {
-                let value = self.builder.load()?;
-                #ident::load_from(&value)
+            /// Choose how arrays at `path` combine across sources; see
+            /// `ConfigBuilder::merge_strategy`.
+            pub fn merge_strategy(
+                mut self,
+                path: impl Into<String>,
+                strategy: ::forgeconf::MergeStrategy,
+            ) -> Self {
+                self.builder = self.builder.merge_strategy(path, strategy);
+                self
+            }
+
+            /// Resolve `${...}` references in values after merging; see
+            /// `ConfigBuilder::interpolate`.
+            pub fn interpolate(mut self) -> Self {
+                self.builder = self.builder.interpolate();
+                self
+            }
+
+            /// Look up environment variables through `lookup`; see
+            /// `ConfigBuilder::env_lookup`.
+            pub fn env_lookup(
+                mut self,
+                lookup: impl Fn(&str) -> Option<String> + Send + Sync + 'static,
+            ) -> Self {
+                self.builder = self.builder.env_lookup(lookup);
+                self
+            }
+
+            /// Load the `[profile.<name>]` sections of each source over their
+            /// `[default]` sections; see `ConfigBuilder::profile`.
+            pub fn profile(mut self, name: impl Into<String>) -> Self {
+                self.builder = self.builder.profile(name);
+                self
+            }
+
+            /// Enable profiles, reading the active one from the environment
+            /// variable `var`; see `ConfigBuilder::profile_env`.
+            pub fn profile_env(mut self, var: impl Into<String>) -> Self {
+                self.builder = self.builder.profile_env(var);
+                self
+            }
+
+            /// Report every error found while loading as one
+            /// `ConfigError::Multiple` instead of stopping at the first.
+            pub fn collect_errors(mut self) -> Self {
+                self.collect_errors = true;
+                self
+            }
+
+            pub fn load(self) -> Result<#self_ty, ::forgeconf::ConfigError> {
+                let (value, sources) = self.builder.load_with_spans()?;
+                Self::finish(self.collect_errors, &value, &sources)
+            }
+
+            /// Like `load`, but also report which override, source, file
+            /// location or default supplied each value, as recorded while
+            /// loading it. The result dereferences to the struct; print
+            /// `explain()` for an "effective config" report.
+            pub fn load_explained(
+                self,
+            ) -> Result<::forgeconf::Explained<#self_ty>, ::forgeconf::ConfigError> {
+                let (value, sources) = self.builder.load_with_spans()?;
+                let mut cx = Self::context(self.collect_errors).recording(&sources);
+                let config = <#self_ty>::load_in(&value, &mut cx).map_err(|err| sources.attach(err))?;
+                Ok(::forgeconf::Explained::new(config, cx.into_provenance()))
+            }
+
+            fn finish(
+                collect_errors: bool,
+                value: &::forgeconf::ConfigNode,
+                sources: &::forgeconf::SourceMap,
+            ) -> Result<#self_ty, ::forgeconf::ConfigError> {
+                <#self_ty>::load_in(value, &mut Self::context(collect_errors))
+                    .map_err(|err| sources.attach(err))
+            }
+
+            fn context<'s>(collect_errors: bool) -> ::forgeconf::LoadContext<'s> {
+                if collect_errors {
+                    ::forgeconf::LoadContext::collecting()
+                } else {
+                    ::forgeconf::LoadContext::new()
+                }
+            }
+        }
+
+        impl #reload_impl_generics ::forgeconf::Reloadable for #loader_ty #reload_where_clause {
+            type Config = #self_ty;
+
+            fn into_parts(self) -> (::forgeconf::ConfigBuilder, ::forgeconf::BuildFn<#self_ty>) {
+                let collect_errors = self.collect_errors;
+                let build = move |value: &::forgeconf::ConfigNode, sources: &::forgeconf::SourceMap| {
+                    Self::finish(collect_errors, value, sources)
+                };
+                (self.builder, Box::new(build))
             }
         }
 
-        impl ::forgeconf::FromNode for #ident {
+        impl #impl_generics ::forgeconf::FromNode for #self_ty #where_clause {
             fn from_node(node: &::forgeconf::ConfigNode, key: &str) -> Result<Self, ::forgeconf::ConfigError> {
-                #ident::load_from(node).map_err(|err| ::forgeconf::ConfigError::nested(key, err))
+                Self::from_node_in(
+                    node,
+                    key,
+                    ::forgeconf::Coercion::Lenient,
+                    &mut ::forgeconf::LoadContext::new(),
+                )
+            }
+
+            fn from_node_in(
+                node: &::forgeconf::ConfigNode,
+                key: &str,
+                _coercion: ::forgeconf::Coercion,
+                cx: &mut ::forgeconf::LoadContext,
+            ) -> Result<Self, ::forgeconf::ConfigError> {
+                cx.scoped(key, |cx| Self::load_in(node, cx))
+                    .map_err(|err| ::forgeconf::ConfigError::nested(key, err))
+            }
+
+            fn json_schema() -> ::forgeconf::ConfigNode {
+                #object_schema
+            }
+
+            fn merge_strategies() -> Vec<(String, ::forgeconf::MergeStrategy)> {
+                Self::merge_strategies()
+            }
+
+            fn path_options() -> Vec<(String, ::forgeconf::PathOptions)> {
+                Self::path_options()
             }
         }
 
+        #to_node_impl
+
         #forgeconf_clap_impl
         #clap_companion
     };
@@ -80,6 +304,68 @@ pub fn render(
     Ok(result)
 }
 
+/// `generics` with every type parameter bound by `FromNode`, plus `ToNode`
+/// when the struct opted into `to_node`, for the generated impls.
+///
+/// Lifetimes are rejected since loaded configuration owns its data.
+fn bounded_generics(generics: &Generics, to_node: bool) -> Result<Generics> {
+    if let Some(lifetime) = generics.lifetimes().next() {
+        return Err(Error::new_spanned(
+            lifetime,
+            "#[forgeconf] does not support lifetime parameters; configuration structs own their \
+             data",
+        ));
+    }
+
+    let mut bounded = generics.clone();
+    let params: Vec<_> = bounded
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(param) => Some(param.ident.clone()),
+            _ => None,
+        })
+        .collect();
+    let where_clause = bounded.make_where_clause();
+    for param in params {
+        let bound = if to_node {
+            quote! { ::forgeconf::FromNode + ::forgeconf::ToNode }
+        } else {
+            quote! { ::forgeconf::FromNode }
+        };
+        where_clause.predicates.push(parse_quote!(#param: #bound));
+    }
+    Ok(bounded)
+}
+
+/// Runs every `#[forgeconf(validate = ...)]` validator on the built struct
+/// and returns it if all pass; struct-level rules only make sense once every
+/// field loaded. Unless `__cx` collects errors, the first failure is
+/// returned without running the rest.
+fn render_struct_validators(validators: &[Expr]) -> TokenStream {
+    if validators.is_empty() {
+        return quote! { Ok(__forgeconf_value) };
+    }
+
+    quote! {
+        if !__cx.collects_errors() {
+            #((#validators)(&__forgeconf_value)?;)*
+            return Ok(__forgeconf_value);
+        }
+        let __errors: Vec<::forgeconf::ConfigError> = [
+            #((#validators)(&__forgeconf_value).err(),)*
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if __errors.is_empty() {
+            Ok(__forgeconf_value)
+        } else {
+            Err(::forgeconf::ConfigError::multiple(__errors))
+        }
+    }
+}
+
 fn generate_parse_methods() -> TokenStream {
     quote! {
         /// Parse TOML text directly into this configuration struct.
@@ -87,8 +373,10 @@ fn generate_parse_methods() -> TokenStream {
         /// Requires the `toml` and `parse` features to be enabled on `forgeconf`.
         #[cfg(all(feature = "parse", feature = "toml"))]
         pub fn parse_toml(input: &str) -> Result<Self, ::forgeconf::ConfigError> {
-            let node = ::forgeconf::parse_toml(input)?;
-            Self::load_from(&node)
+            let document =
+                ::forgeconf::SourceDocument::new("<toml>", ::forgeconf::FileFormat::Toml, input);
+            let node = document.parse()?;
+            Self::load_from(&node).map_err(|err| document.attach(err))
         }
 
         /// Parse YAML text directly into this configuration struct.
@@ -96,8 +384,10 @@ fn generate_parse_methods() -> TokenStream {
         /// Requires the `yaml` and `parse` features to be enabled on `forgeconf`.
         #[cfg(all(feature = "parse", feature = "yaml"))]
         pub fn parse_yaml(input: &str) -> Result<Self, ::forgeconf::ConfigError> {
-            let node = ::forgeconf::parse_yaml(input)?;
-            Self::load_from(&node)
+            let document =
+                ::forgeconf::SourceDocument::new("<yaml>", ::forgeconf::FileFormat::Yaml, input);
+            let node = document.parse()?;
+            Self::load_from(&node).map_err(|err| document.attach(err))
         }
 
         /// Parse JSON text directly into this configuration struct.
@@ -105,12 +395,89 @@ fn generate_parse_methods() -> TokenStream {
         /// Requires the `json` and `parse` features to be enabled on `forgeconf`.
         #[cfg(all(feature = "parse", feature = "json"))]
         pub fn parse_json(input: &str) -> Result<Self, ::forgeconf::ConfigError> {
-            let node = ::forgeconf::parse_json(input)?;
-            Self::load_from(&node)
+            let document =
+                ::forgeconf::SourceDocument::new("<json>", ::forgeconf::FileFormat::Json, input);
+            let node = document.parse()?;
+            Self::load_from(&node).map_err(|err| document.attach(err))
         }
     }
 }
 
+/// Pushes the strategy declared on `field` and, for nested sections, the
+/// strategies of the nested struct under the field's key.
+fn render_merge_entries(field: &FieldSpec) -> TokenStream {
+    let key = field
+        .options
+        .rename
+        .clone()
+        .unwrap_or(field.ident.to_string());
+    let key_lit = LitStr::new(&key, field.ident.span());
+
+    let own = field.options.merge.as_ref().map(|strategy| {
+        let strategy = render_merge_strategy(strategy, &key_lit);
+        quote! { strategies.push((#key_lit.to_string(), #strategy)); }
+    });
+
+    let ty = &field.ty;
+    let nested = field.options.nested.then(|| {
+        quote! {
+            for (path, strategy) in <#ty as ::forgeconf::FromNode>::merge_strategies() {
+                strategies.push((::std::format!("{}.{}", #key_lit, path), strategy));
+            }
+        }
+    });
+
+    quote! { #own #nested }
+}
+
+/// Pushes the path options declared on `field` and, for nested sections,
+/// those of the nested struct under the field's key.
+fn render_path_entries(field: &FieldSpec) -> TokenStream {
+    let key = field
+        .options
+        .rename
+        .clone()
+        .unwrap_or(field.ident.to_string());
+    let key_lit = LitStr::new(&key, field.ident.span());
+
+    let relative_to_config = field.options.relative_to_config;
+    let expand = field.options.expand_path;
+    let own = (relative_to_config || expand).then(|| {
+        quote! {
+            options.push((
+                #key_lit.to_string(),
+                ::forgeconf::PathOptions {
+                    relative_to_config: #relative_to_config,
+                    expand: #expand,
+                },
+            ));
+        }
+    });
+
+    let ty = &field.ty;
+    let nested = field.options.nested.then(|| {
+        quote! {
+            for (path, path_options) in <#ty as ::forgeconf::FromNode>::path_options() {
+                options.push((::std::format!("{}.{}", #key_lit, path), path_options));
+            }
+        }
+    });
+
+    quote! { #own #nested }
+}
+
+fn render_merge_strategy(strategy: &MergeStrategy, span_lit: &LitStr) -> TokenStream {
+    match strategy {
+        MergeStrategy::Replace => quote! { ::forgeconf::MergeStrategy::Replace },
+        MergeStrategy::Append => quote! { ::forgeconf::MergeStrategy::Append },
+        MergeStrategy::Prepend => quote! { ::forgeconf::MergeStrategy::Prepend },
+        MergeStrategy::MergeByKey(key) => {
+            let key_lit = LitStr::new(key, span_lit.span());
+            quote! { ::forgeconf::MergeStrategy::MergeByKey(#key_lit.to_string()) }
+        },
+    }
+}
+
 fn render_config_addition(cfg: &ConfigFile) -> TokenStream {
     let path = &cfg.path;
     let format_chain = cfg
@@ -134,11 +501,25 @@ fn render_config_addition(cfg: &ConfigFile) -> TokenStream {
         .map(|value| quote! { .with_priority(#value) })
         .unwrap_or_default();
 
+    let optional_chain = if cfg.optional {
+        quote! { .optional() }
+    } else {
+        TokenStream::new()
+    };
+
+    let includes_chain = if cfg.includes {
+        quote! { .with_includes() }
+    } else {
+        TokenStream::new()
+    };
+
     quote! {
         __builder = __builder.add_source(
             ::forgeconf::ConfigFile::new(#path)
                 #format_chain
                 #priority_chain
+                #optional_chain
+                #includes_chain
         );
     }
 }
@@ -1,8 +1,8 @@
-use proc_macro2::TokenStream;
-use quote::{format_ident, quote};
-use syn::{LitChar, LitStr};
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{ToTokens, format_ident, quote};
+use syn::{Expr, GenericParam, Generics, Ident, LitChar, LitStr, Type, parse_quote};
 
-use crate::model::{FieldSpec, is_vec_type};
+use crate::model::{FieldSpec, is_vec_type, scalar_type_name, validator_call};
 
 /// Generates `augment_clap` and `from_clap` methods on the config struct.
 /// Both delegate to the `ForgeconfClap` trait impl so nested fields are
@@ -14,7 +14,10 @@ pub(super) fn generate_clap_methods() -> TokenStream {
     quote! {
         /// Augment a [`clap::Command`] with CLI arguments for every config
         /// field, including nested structs (prefixed with `parent-field-`).
-        pub fn augment_clap(cmd: ::forgeconf::clap::Command) -> ::forgeconf::clap::Command {
+        pub fn augment_clap(cmd: ::forgeconf::clap::Command) -> ::forgeconf::clap::Command
+        where
+            Self: ::forgeconf::ForgeconfClap,
+        {
             <Self as ::forgeconf::ForgeconfClap>::augment_clap_with_prefix(cmd, None)
         }
 
@@ -22,7 +25,10 @@ pub(super) fn generate_clap_methods() -> TokenStream {
         ///
         /// Only arguments actually provided on the command line are inserted;
         /// the rest continue to be resolved from files, env, or defaults.
-        pub fn from_clap(matches: &::forgeconf::clap::ArgMatches) -> ::forgeconf::CliArgsSource {
+        pub fn from_clap(matches: &::forgeconf::clap::ArgMatches) -> ::forgeconf::CliArgsSource
+        where
+            Self: ::forgeconf::ForgeconfClap,
+        {
             let mut __map = ::std::collections::BTreeMap::new();
             <Self as ::forgeconf::ForgeconfClap>::extract_clap_with_prefix(matches, None, &mut __map);
             ::forgeconf::CliArgsSource::new(__map)
@@ -30,14 +36,68 @@ pub(super) fn generate_clap_methods() -> TokenStream {
     }
 }
 
+/// `generics` with a `ForgeconfClap` bound on every nested field type that
+/// names a type parameter. Those cannot go through `ClapProbe`, which
+/// resolves before the parameter is known and would silently add no flags.
+pub(super) fn clap_generics(generics: &Generics, fields: &[FieldSpec]) -> Generics {
+    let mut bounded = generics.clone();
+    if !cfg!(feature = "clap") {
+        return bounded;
+    }
+    let params = type_params(generics);
+    let where_clause = bounded.make_where_clause();
+    for field in nested_clap_fields(fields) {
+        if names_param(&field.ty, &params) {
+            let ty = &field.ty;
+            where_clause
+                .predicates
+                .push(parse_quote!(#ty: ::forgeconf::ForgeconfClap));
+        }
+    }
+    bounded
+}
+
+fn type_params(generics: &Generics) -> Vec<Ident> {
+    generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(param) => Some(param.ident.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether any of `params` appears in `ty`, such as `T` in `Option<Pool<T>>`.
+fn names_param(ty: &Type, params: &[Ident]) -> bool {
+    fn walk(tokens: TokenStream, params: &[Ident]) -> bool {
+        tokens.into_iter().any(|token| match token {
+            TokenTree::Ident(ident) => params.contains(&ident),
+            TokenTree::Group(group) => walk(group.stream(), params),
+            _ => false,
+        })
+    }
+    walk(ty.to_token_stream(), params)
+}
+
+/// Nested fields that contribute prefixed flags.
+fn nested_clap_fields(fields: &[FieldSpec]) -> impl Iterator<Item = &FieldSpec> {
+    fields
+        .iter()
+        .filter(|f| f.options.nested && !f.options.no_cli && !is_vec_type(&f.ty))
+}
+
 /// Generates `impl ForgeconfClap for Struct` — the prefix-aware augment/extract
 /// trait that lets parent structs delegate to nested types.
 ///
 /// Flat fields become `--field-name` args. Nested (non-Vec) fields recursively
-/// delegate with a `"parent."` prefix, producing `--parent-field-name` args.
-/// `Vec<T>` nested fields and `no_cli` fields are skipped.
+/// delegate with a `"parent."` prefix, producing `--parent-field-name` args,
+/// when their type implements `ForgeconfClap`; types naming a type parameter
+/// must implement it (see [`clap_generics`]). `Vec<T>` nested fields and
+/// `no_cli` fields are skipped.
 pub(super) fn generate_forgeconf_clap_impl(
     ident: &syn::Ident,
+    generics: &syn::Generics,
     fields: &[FieldSpec],
 ) -> TokenStream {
     if !cfg!(feature = "clap") {
@@ -49,10 +109,8 @@ pub(super) fn generate_forgeconf_clap_impl(
         .filter(|f| !f.options.no_cli && !f.options.nested)
         .collect();
 
-    let nested_fields: Vec<&FieldSpec> = fields
-        .iter()
-        .filter(|f| f.options.nested && !f.options.no_cli && !is_vec_type(&f.ty))
-        .collect();
+    let nested_fields: Vec<&FieldSpec> = nested_clap_fields(fields).collect();
+    let params = type_params(generics);
 
     let flat_augment: Vec<TokenStream> =
         flat_fields.iter().map(|f| render_prefixed_arg(f)).collect();
@@ -63,16 +121,27 @@ pub(super) fn generate_forgeconf_clap_impl(
             let ty = &f.ty;
             let name = f.options.rename.clone().unwrap_or(f.ident.to_string());
             let name_lit = LitStr::new(&name, f.ident.span());
+            let augment = if names_param(ty, &params) {
+                quote! {
+                    <#ty as ::forgeconf::ForgeconfClap>::augment_clap_with_prefix(
+                        cmd,
+                        Some(&__nested_prefix),
+                    )
+                }
+            } else {
+                quote! {
+                    use ::forgeconf::__private::{ViaForgeconfClap as _, WithoutForgeconfClap as _};
+                    (&::forgeconf::__private::ClapProbe::<#ty>::new())
+                        .augment(cmd, &__nested_prefix)
+                }
+            };
             quote! {
                 let cmd = {
                     let __nested_prefix = match prefix {
                         Some(p) => ::std::format!("{}.{}", p, #name_lit),
                         None => #name_lit.to_string(),
                     };
-                    <#ty as ::forgeconf::ForgeconfClap>::augment_clap_with_prefix(
-                        cmd,
-                        Some(&__nested_prefix),
-                    )
+                    #augment
                 };
             }
         })
@@ -89,24 +158,37 @@ pub(super) fn generate_forgeconf_clap_impl(
             let ty = &f.ty;
             let name = f.options.rename.clone().unwrap_or(f.ident.to_string());
             let name_lit = LitStr::new(&name, f.ident.span());
+            let extract = if names_param(ty, &params) {
+                quote! {
+                    <#ty as ::forgeconf::ForgeconfClap>::extract_clap_with_prefix(
+                        matches,
+                        Some(&__nested_prefix),
+                        out,
+                    );
+                }
+            } else {
+                quote! {
+                    use ::forgeconf::__private::{ViaForgeconfClap as _, WithoutForgeconfClap as _};
+                    (&::forgeconf::__private::ClapProbe::<#ty>::new())
+                        .extract(matches, &__nested_prefix, out);
+                }
+            };
             quote! {
                 {
                     let __nested_prefix = match prefix {
                         Some(p) => ::std::format!("{}.{}", p, #name_lit),
                         None => #name_lit.to_string(),
                     };
-                    <#ty as ::forgeconf::ForgeconfClap>::extract_clap_with_prefix(
-                        matches,
-                        Some(&__nested_prefix),
-                        out,
-                    );
+                    #extract
                 }
             }
         })
         .collect();
 
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
     quote! {
-        impl ::forgeconf::ForgeconfClap for #ident {
+        impl #impl_generics ::forgeconf::ForgeconfClap for #ident #ty_generics #where_clause {
             #[allow(unused_variables)]
             fn augment_clap_with_prefix(
                 cmd: ::forgeconf::clap::Command,
@@ -135,44 +217,71 @@ pub(super) fn generate_forgeconf_clap_impl(
 ///
 /// The companion delegates entirely to the struct's `ForgeconfClap` impl,
 /// so nested fields work automatically.
-pub(super) fn generate_clap_companion(ident: &syn::Ident, _fields: &[FieldSpec]) -> TokenStream {
+pub(super) fn generate_clap_companion(
+    ident: &syn::Ident,
+    generics: &syn::Generics,
+    _fields: &[FieldSpec],
+) -> TokenStream {
     if !cfg!(feature = "clap") {
         return TokenStream::new();
     }
 
     let companion_ident = format_ident!("{}CliArgs", ident);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let self_ty = quote! { #ident #ty_generics };
+    let companion_ty = quote! { #companion_ident #ty_generics };
 
     quote! {
-        #[derive(Debug, Clone, Default)]
-        pub struct #companion_ident {
+        pub struct #companion_ident #impl_generics #where_clause {
             __args: ::std::collections::BTreeMap<String, String>,
+            __marker: ::std::marker::PhantomData<fn() -> #self_ty>,
+        }
+
+        impl #impl_generics ::std::fmt::Debug for #companion_ty #where_clause {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.debug_struct(stringify!(#companion_ident))
+                    .field("__args", &self.__args)
+                    .finish()
+            }
+        }
+
+        impl #impl_generics ::std::clone::Clone for #companion_ty #where_clause {
+            fn clone(&self) -> Self {
+                Self { __args: self.__args.clone(), __marker: ::std::marker::PhantomData }
+            }
+        }
+
+        impl #impl_generics ::std::default::Default for #companion_ty #where_clause {
+            fn default() -> Self {
+                Self { __args: ::std::default::Default::default(), __marker: ::std::marker::PhantomData }
+            }
         }
 
-        impl ::forgeconf::clap::FromArgMatches for #companion_ident {
+        impl #impl_generics ::forgeconf::clap::FromArgMatches for #companion_ty #where_clause {
             fn from_arg_matches(
                 matches: &::forgeconf::clap::ArgMatches,
             ) -> Result<Self, ::forgeconf::clap::Error> {
                 let mut __args = ::std::collections::BTreeMap::new();
-                <#ident as ::forgeconf::ForgeconfClap>::extract_clap_with_prefix(
+                <#self_ty as ::forgeconf::ForgeconfClap>::extract_clap_with_prefix(
                     matches, None, &mut __args,
                 );
-                Ok(Self { __args })
+                Ok(Self { __args, __marker: ::std::marker::PhantomData })
             }
 
             fn update_from_arg_matches(
                 &mut self,
                 matches: &::forgeconf::clap::ArgMatches,
             ) -> Result<(), ::forgeconf::clap::Error> {
-                <#ident as ::forgeconf::ForgeconfClap>::extract_clap_with_prefix(
+                <#self_ty as ::forgeconf::ForgeconfClap>::extract_clap_with_prefix(
                     matches, None, &mut self.__args,
                 );
                 Ok(())
             }
         }
 
-        impl ::forgeconf::clap::Args for #companion_ident {
+        impl #impl_generics ::forgeconf::clap::Args for #companion_ty #where_clause {
             fn augment_args(cmd: ::forgeconf::clap::Command) -> ::forgeconf::clap::Command {
-                <#ident as ::forgeconf::ForgeconfClap>::augment_clap_with_prefix(cmd, None)
+                <#self_ty as ::forgeconf::ForgeconfClap>::augment_clap_with_prefix(cmd, None)
             }
 
             fn augment_args_for_update(
@@ -182,7 +291,7 @@ pub(super) fn generate_clap_companion(ident: &syn::Ident, _fields: &[FieldSpec])
             }
         }
 
-        impl ::forgeconf::ConfigSource for #companion_ident {
+        impl #impl_generics ::forgeconf::ConfigSource for #companion_ty #where_clause {
             fn priority(&self) -> u8 {
                 u8::MAX
             }
@@ -194,6 +303,103 @@ pub(super) fn generate_clap_companion(ident: &syn::Ident, _fields: &[FieldSpec])
     }
 }
 
+/// How a field is exposed on the command line.
+enum ArgKind {
+    /// `bool`: a `--name` switch with a `--no-name` counterpart.
+    Flag,
+    /// `#[field(count)]`: a repeatable switch counting its occurrences.
+    Count,
+    /// Anything else: a `--name <value>` option.
+    Value,
+}
+
+impl ArgKind {
+    fn of(field: &FieldSpec) -> Self {
+        if field.options.count {
+            ArgKind::Count
+        } else if scalar_type_name(&field.ty).as_deref() == Some("bool") {
+            ArgKind::Flag
+        } else {
+            ArgKind::Value
+        }
+    }
+}
+
+/// Builds the value parser of a `--name <value>` arg, so clap rejects bad
+/// input with a usage message instead of leaving it to `FromNode`.
+///
+/// `one_of` validators become possible values and integers get clap's
+/// ranged parsers, bounded by a `range` validator if present. Other types
+/// are checked by converting the text through their `FromNode` impl. Every
+/// parser yields the original text, which `extract_clap_with_prefix`
+/// stores for `CliArgsSource`.
+fn render_value_parser(field: &FieldSpec, key_lit: &LitStr) -> TokenStream {
+    let ty = &field.ty;
+    let find = |wanted: &str, arity: usize| -> Option<Vec<&Expr>> {
+        field.options.validators.iter().find_map(|expr| {
+            validator_call(expr)
+                .and_then(|(name, args)| (name == wanted && args.len() == arity).then_some(args))
+        })
+    };
+
+    if let Some(args) = find("one_of", 1) {
+        let values = args[0];
+        return quote! {
+            ::forgeconf::clap::builder::PossibleValuesParser::new(
+                ::std::iter::IntoIterator::into_iter(#values).map(|__value| {
+                    let __value: &'static str = ::std::boxed::Box::leak(
+                        ::std::string::ToString::to_string(&__value).into_boxed_str(),
+                    );
+                    ::forgeconf::clap::builder::PossibleValue::new(__value)
+                }),
+            )
+        };
+    }
+
+    let type_name = scalar_type_name(ty);
+    if let Some(name @ ("i8" | "i16" | "i32" | "i64" | "u8" | "u16" | "u32" | "u64")) =
+        type_name.as_deref()
+    {
+        let int = format_ident!("{}", name);
+        let bound = if name == "u64" {
+            quote! { u64 }
+        } else {
+            quote! { i64 }
+        };
+        let range = find("range", 2).map(|args| {
+            let (min, max) = (args[0], args[1]);
+            quote! { .range((#min) as #bound..=(#max) as #bound) }
+        });
+        return quote! {
+            ::forgeconf::clap::builder::TypedValueParser::map(
+                ::forgeconf::clap::value_parser!(#int) #range,
+                |__value| __value.to_string(),
+            )
+        };
+    }
+
+    // Lists are split the same way `load_in` splits them before conversion.
+    let split = field.options.separator.as_ref().map(|separator| {
+        let separator_lit = LitStr::new(separator, key_lit.span());
+        quote! { let __node = __node.split_list(#separator_lit); }
+    });
+
+    // A fn pointer rather than a closure, so the parser stays `'static` when
+    // the field type is a generic parameter.
+    quote! {
+        {
+            let __parse: fn(&str) -> ::std::result::Result<String, String> = |__value| {
+                let __node = ::forgeconf::ConfigNode::Scalar(__value.to_string());
+                #split
+                <#ty as ::forgeconf::FromNode>::from_node(&__node, #key_lit)
+                    .map(|_| __value.to_string())
+                    .map_err(|err| err.to_string())
+            };
+            __parse
+        }
+    }
+}
+
 /// Generates a `let cmd = { ... };` binding that adds a single prefixed arg.
 ///
 /// Arg ID uses dot notation (`watcher.watch_path`) so it matches the dotted
@@ -237,6 +443,31 @@ fn render_prefixed_arg(field: &FieldSpec) -> TokenStream {
         })
         .unwrap_or_default();
 
+    let kind_stmt = match ArgKind::of(field) {
+        ArgKind::Flag => quote! {
+            let __no_id: &'static str =
+                ::std::boxed::Box::leak(::std::format!("no-{}", __id).into_boxed_str());
+            let __no_long: &'static str =
+                ::std::boxed::Box::leak(::std::format!("no-{}", __long).into_boxed_str());
+            let cmd = cmd.arg(
+                ::forgeconf::clap::Arg::new(__no_id)
+                    .long(__no_long)
+                    .action(::forgeconf::clap::ArgAction::SetTrue)
+                    .overrides_with(__id),
+            );
+            let arg = arg
+                .action(::forgeconf::clap::ArgAction::SetTrue)
+                .overrides_with(__no_id);
+        },
+        ArgKind::Count => quote! {
+            let arg = arg.action(::forgeconf::clap::ArgAction::Count);
+        },
+        ArgKind::Value => {
+            let parser = render_value_parser(field, &id_base_lit);
+            quote! { let arg = arg.value_parser(#parser); }
+        },
+    };
+
     quote! {
         let cmd = {
             let __id: &'static str = match prefix {
@@ -253,6 +484,7 @@ fn render_prefixed_arg(field: &FieldSpec) -> TokenStream {
                 ),
             };
             let arg = ::forgeconf::clap::Arg::new(__id).long(__long);
+            #kind_stmt
             #short_stmt
             #help_stmt
             cmd.arg(arg)
@@ -272,6 +504,27 @@ fn render_prefixed_extract(field: &FieldSpec) -> TokenStream {
     let id_base_lit = LitStr::new(&config_key, field.ident.span());
     let key_base_lit = LitStr::new(&config_key, field.ident.span());
 
+    let insert = match ArgKind::of(field) {
+        ArgKind::Flag => quote! {
+            if matches.get_flag(__id.as_str()) {
+                out.insert(__key, "true".to_string());
+            } else if matches.get_flag(::std::format!("no-{}", __id).as_str()) {
+                out.insert(__key, "false".to_string());
+            }
+        },
+        ArgKind::Count => quote! {
+            let __count = matches.get_count(__id.as_str());
+            if __count > 0 {
+                out.insert(__key, __count.to_string());
+            }
+        },
+        ArgKind::Value => quote! {
+            if let Some(__v) = matches.get_one::<String>(__id.as_str()) {
+                out.insert(__key, __v.clone());
+            }
+        },
+    };
+
     quote! {
         {
             let __id = match prefix {
@@ -282,9 +535,7 @@ fn render_prefixed_extract(field: &FieldSpec) -> TokenStream {
                 None => #key_base_lit.to_string(),
                 Some(p) => ::std::format!("{}.{}", p, #key_base_lit),
             };
-            if let Some(__v) = matches.get_one::<String>(__id.as_str()) {
-                out.insert(__key, __v.clone());
-            }
+            #insert
         }
     }
 }
@@ -2,11 +2,12 @@ use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 use syn::{Expr, LitStr};
 
-use crate::model::FieldSpec;
+use crate::model::{FieldSpec, UnknownFields, validator_call};
 
-pub(super) fn render_field_init(field: &FieldSpec) -> TokenStream {
+/// Renders the lookup, conversion and validation of a single field as an
+/// expression of type `Result<T, ConfigError>`.
+fn render_field_init(field: &FieldSpec) -> TokenStream {
     let ident = &field.ident;
-    let ty = &field.ty;
     let key = field.options.rename.clone().unwrap_or(ident.to_string());
     let key_lit = LitStr::new(&key, ident.span());
     let value_ident = format_ident!("__forgeconf_value");
@@ -26,106 +27,256 @@ pub(super) fn render_field_init(field: &FieldSpec) -> TokenStream {
         quote! { map.remove(#key_lit) }
     };
 
-    let override_expr = match (field.options.cli.as_ref(), field.options.env.as_ref()) {
-        (Some(cli), Some(env)) => {
-            let cli_lit = LitStr::new(cli, ident.span());
-            let env_lit = LitStr::new(env, ident.span());
-            quote! {
-                std::env::args()
-                    .skip(1)
-                    .find_map(|arg| {
-                        if let Some(value) = arg.strip_prefix(concat!("--", #cli_lit, "=")) {
-                            Some(::forgeconf::ConfigNode::Scalar(value.to_string()))
-                        } else {
-                            None
-                        }
-                    })
-                    .or(std::env::var(#env_lit).ok().map(::forgeconf::ConfigNode::Scalar))
-            }
-        },
-        (Some(cli), None) => {
-            let cli_lit = LitStr::new(cli, ident.span());
-            quote! {
-                std::env::args()
-                    .skip(1)
-                    .find_map(|arg| {
-                        if let Some(value) = arg.strip_prefix(concat!("--", #cli_lit, "=")) {
-                            Some(::forgeconf::ConfigNode::Scalar(value.to_string()))
-                        } else {
-                            None
-                        }
-                    })
-            }
-        },
-        (None, Some(env)) => {
-            let env_lit = LitStr::new(env, ident.span());
-            quote! {
-                std::env::var(#env_lit)
-                    .ok()
-                    .map(::forgeconf::ConfigNode::Scalar)
-            }
-        },
+    let cli_override = field.options.cli.as_ref().map(|cli| {
+        let cli_lit = LitStr::new(cli, ident.span());
+        quote! {
+            std::env::args().skip(1).find_map(|arg| {
+                arg.strip_prefix(concat!("--", #cli_lit, "=")).map(|value| {
+                    (
+                        ::forgeconf::ConfigNode::Scalar(value.to_string()),
+                        ::forgeconf::Origin::cli(#cli_lit).with_value(value),
+                    )
+                })
+            })
+        }
+    });
+    let env_override = field.options.env.as_ref().map(|env| {
+        let env_lit = LitStr::new(env, ident.span());
+        quote! {
+            std::env::var(#env_lit).ok().map(|value| {
+                let origin = ::forgeconf::Origin::env(#env_lit).with_value(value.as_str());
+                (::forgeconf::ConfigNode::Scalar(value), origin)
+            })
+        }
+    });
+    let override_expr = match (cli_override, env_override) {
+        (Some(cli), Some(env)) => quote! { #cli.or_else(|| #env) },
+        (Some(cli), None) => cli,
+        (None, Some(env)) => env,
         (None, None) => quote! { None },
     };
+    let redact_origin = field.is_secret().then(|| quote! { .redacted() });
+
+    let split = field.options.separator.as_ref().map(|separator| {
+        let separator_lit = LitStr::new(separator, ident.span());
+        quote! { .map(|node| node.split_list(#separator_lit)) }
+    });
 
     let fetch_value = quote! {
         {
-            let mut value: Option<::forgeconf::ConfigNode> = #override_expr;
-            if value.is_none() {
-                value = #lookup_expr;
-            }
-            value
+            // Always take the key out of `map`, even when overridden, so it
+            // is not reported as unknown.
+            let from_sources: Option<::forgeconf::ConfigNode> = #lookup_expr;
+            let overridden: Option<(::forgeconf::ConfigNode, ::forgeconf::Origin)> =
+                #override_expr;
+            match overridden {
+                Some((node, origin)) => {
+                    __cx.record(#key_lit, || origin #redact_origin);
+                    Some(node)
+                },
+                None => from_sources,
+            } #split
         }
     };
 
+    let convert = |node: TokenStream| render_from_node(field, node, &key_lit);
+    let convert_node = convert(quote! { &node });
+    let convert_fallback = convert(quote! { &fallback });
+
     let base_expr = match field_kind(field) {
         FieldKind::Optional => {
             quote! {
-                let node = #fetch_value.unwrap_or(::forgeconf::ConfigNode::Null);
-                <#ty as ::forgeconf::FromNode>::from_node(&node, #key_lit)?
+                {
+                    let node = #fetch_value.unwrap_or(::forgeconf::ConfigNode::Null);
+                    #convert_node
+                }
             }
         },
         FieldKind::Default(expr) => {
             quote! {
-                if let Some(node) = #fetch_value {
-                    <#ty as ::forgeconf::FromNode>::from_node(&node, #key_lit)?
-                } else {
-                    #expr
+                match #fetch_value {
+                    Some(node) => #convert_node,
+                    None => {
+                        let value = #expr;
+                        __cx.record(#key_lit, || {
+                            use ::forgeconf::__private::{ViaToNode as _, WithoutToNode as _};
+                            let origin = ::forgeconf::Origin::default_value();
+                            match (&::forgeconf::__private::NodeProbe(&value)).probe_node() {
+                                Some(node) => origin.with_value(node.to_string()),
+                                None => origin,
+                            } #redact_origin
+                        });
+                        Ok(value)
+                    },
                 }
             }
         },
         FieldKind::Scalar => {
             quote! {
-                if let Some(node) = #fetch_value {
-                    <#ty as ::forgeconf::FromNode>::from_node(&node, #key_lit)?
-                } else {
-                    return Err(::forgeconf::ConfigError::missing(#key_lit));
+                match #fetch_value {
+                    Some(node) => #convert_node,
+                    None => Err(::forgeconf::ConfigError::missing(#key_lit)),
                 }
             }
         },
         FieldKind::Nested => {
             quote! {
-                if let Some(node) = #fetch_value {
-                    <#ty as ::forgeconf::FromNode>::from_node(&node, #key_lit)?
-                } else {
-                    let fallback = ::forgeconf::ConfigNode::Table(map.clone());
-                    <#ty as ::forgeconf::FromNode>::from_node(&fallback, #key_lit)?
+                match #fetch_value {
+                    Some(node) => #convert_node,
+                    None => {
+                        let fallback = ::forgeconf::ConfigNode::Table(map.clone());
+                        #convert_fallback
+                    },
                 }
             }
         },
     };
 
     let validator_calls = render_validator_calls(field, &key_lit, &value_ident);
+    let redact = field
+        .is_secret()
+        .then(|| quote! { .map_err(::forgeconf::ConfigError::redact) });
+
+    // Overrides and defaults record their origin above, and nested sections
+    // record their own fields; anything else came from the merged sources.
+    quote! {
+        {
+            let __recorded = __cx.recorded();
+            let __result = #base_expr;
+            if __cx.recorded() == __recorded
+                && let Some(origin) = __cx.source_origin(#key_lit)
+            {
+                __cx.record(#key_lit, || origin #redact_origin);
+            }
+            __result
+        } #validator_calls #redact
+    }
+}
+
+/// Builds `path { .. }` out of `map`. Returns the first error from the
+/// enclosing function, unless the `LoadContext` in `__cx` collects errors:
+/// then every field is evaluated and all failures are reported together
+/// through [`ConfigError::multiple`](forgeconf_core::ConfigError::multiple).
+/// Keys left in `map` afterwards are handled according to `unknown`.
+///
+/// The generated expression has type `Result<path, ConfigError>`.
+pub(super) fn render_struct_init(
+    path: TokenStream,
+    fields: &[FieldSpec],
+    unknown: &UnknownFields,
+) -> TokenStream {
+    if fields.is_empty() && matches!(unknown, UnknownFields::Ignore) {
+        return quote! { Ok(#path {}) };
+    }
+
+    let idents: Vec<_> = fields.iter().map(|field| &field.ident).collect();
+    let slots: Vec<_> = fields
+        .iter()
+        .map(|field| format_ident!("__forgeconf_{}", field.ident))
+        .collect();
+    let evaluations = fields.iter().zip(&slots).map(|(field, slot)| {
+        let ty = &field.ty;
+        let init = render_field_init(field);
+        quote! {
+            let #slot: Option<#ty> = match #init {
+                Ok(value) => Some(value),
+                Err(err) if __cx.collects_errors() => {
+                    __errors.push(err);
+                    None
+                },
+                Err(err) => return Err(err),
+            };
+        }
+    });
+    let unknown_check = render_unknown_check(fields, unknown);
+
+    quote! {
+        {
+            #[allow(unused_mut)]
+            let mut __errors: Vec<::forgeconf::ConfigError> = Vec::new();
+            #(#evaluations)*
+            #unknown_check
+            match (#(#slots,)*) {
+                (#(Some(#slots),)*) if __errors.is_empty() => Ok(#path { #(#idents: #slots),* }),
+                _ => Err(::forgeconf::ConfigError::multiple(__errors)),
+            }
+        }
+    }
+}
+
+/// Builds a `ConfigNode::Table` holding every field under its key, reading
+/// each value through `access(ident)`, an expression of type `&T`.
+pub(super) fn render_table_node(
+    fields: &[FieldSpec],
+    access: impl Fn(&syn::Ident) -> TokenStream,
+) -> TokenStream {
+    let entries = fields.iter().map(|field| {
+        let key = field
+            .options
+            .rename
+            .clone()
+            .unwrap_or(field.ident.to_string());
+        let key_lit = LitStr::new(&key, field.ident.span());
+        let value = access(&field.ident);
+        let redact = field.is_secret().then(|| quote! { .redacted() });
+        quote! {
+            __table.insert(#key_lit.to_string(), ::forgeconf::ToNode::to_node(#value) #redact);
+        }
+    });
 
     quote! {
-        #ident: {
-            let #value_ident = { #base_expr };
-            #validator_calls
-            #value_ident
+        {
+            #[allow(unused_mut)]
+            let mut __table = ::std::collections::BTreeMap::new();
+            #(#entries)*
+            ::forgeconf::ConfigNode::Table(__table)
         }
     }
 }
 
+fn render_unknown_check(fields: &[FieldSpec], unknown: &UnknownFields) -> TokenStream {
+    let known = fields.iter().map(|field| {
+        let key = field
+            .options
+            .rename
+            .clone()
+            .unwrap_or(field.ident.to_string());
+        LitStr::new(&key, field.ident.span())
+    });
+    let error = quote! { ::forgeconf::ConfigError::unknown_field(__key, &[#(#known),*]) };
+
+    match unknown {
+        UnknownFields::Ignore => TokenStream::new(),
+        UnknownFields::Deny => quote! {
+            for __key in map.keys() {
+                if !__cx.collects_errors() {
+                    return Err(#error);
+                }
+                __errors.push(#error);
+            }
+        },
+        UnknownFields::Warn(callback) => quote! {
+            for __key in map.keys() {
+                (#callback)(&#error);
+            }
+        },
+    }
+}
+
+/// Renders the `FromNode` conversion of `node` within the load's context,
+/// honouring `#[field(strict)]`.
+fn render_from_node(field: &FieldSpec, node: TokenStream, key_lit: &LitStr) -> TokenStream {
+    let ty = &field.ty;
+    let coercion = if field.options.strict {
+        quote! { ::forgeconf::Coercion::Strict }
+    } else {
+        quote! { ::forgeconf::Coercion::Lenient }
+    };
+    quote! {
+        <#ty as ::forgeconf::FromNode>::from_node_in(#node, #key_lit, #coercion, __cx)
+    }
+}
+
 fn field_kind(field: &FieldSpec) -> FieldKind<'_> {
     if field.options.optional {
         FieldKind::Optional
@@ -158,5 +309,121 @@ fn render_validator_calls(
         quote! { (#expr)(&#value_ident, #key_lit)?; }
     });
 
-    quote! { #(#validators)* }
+    quote! {
+        .and_then(|#value_ident| {
+            #(#validators)*
+            Ok(#value_ident)
+        })
+    }
+}
+
+/// Builds the JSON Schema of a table holding `fields`: one property per
+/// field, with `help` as its description, its default, and the keywords of
+/// the validators forgeconf knows about. Fields without a default that are
+/// neither optional, nested nor overridable from env/CLI are required.
+pub(super) fn render_object_schema(fields: &[FieldSpec], unknown: &UnknownFields) -> TokenStream {
+    let entries = fields.iter().map(|field| {
+        let ty = &field.ty;
+        let key = field
+            .options
+            .rename
+            .clone()
+            .unwrap_or(field.ident.to_string());
+        let key_lit = LitStr::new(&key, field.ident.span());
+
+        let keywords = field
+            .options
+            .validators
+            .iter()
+            .filter_map(render_validator_schema);
+        let description = field.options.help.as_ref().map(|help| {
+            let help_lit = LitStr::new(help, field.ident.span());
+            quote! {
+                __schema = ::forgeconf::schema::with(
+                    __schema,
+                    "description",
+                    ::forgeconf::ConfigNode::String(#help_lit.to_string()),
+                );
+            }
+        });
+        let default = match field_kind(field) {
+            FieldKind::Default(expr) if !field.is_secret() => quote! {
+                let __default: #ty = #expr;
+                let __default = {
+                    use ::forgeconf::__private::{ViaToNode as _, WithoutToNode as _};
+                    (&::forgeconf::__private::NodeProbe(&__default)).probe_node()
+                };
+                if let Some(__default) = __default {
+                    __schema = ::forgeconf::schema::with(__schema, "default", __default);
+                }
+            },
+            _ => TokenStream::new(),
+        };
+        let required = (matches!(field_kind(field), FieldKind::Scalar)
+            && field.options.env.is_none()
+            && field.options.cli.is_none())
+        .then(|| quote! { __required.push(#key_lit); });
+
+        quote! {
+            {
+                let mut __schema = <#ty as ::forgeconf::FromNode>::json_schema();
+                #(#keywords)*
+                #description
+                #default
+                __properties.push((#key_lit.to_string(), __schema));
+                #required
+            }
+        }
+    });
+    let additional = !matches!(unknown, UnknownFields::Deny);
+
+    quote! {
+        {
+            #[allow(unused_mut)]
+            let mut __properties: Vec<(String, ::forgeconf::ConfigNode)> = Vec::new();
+            #[allow(unused_mut)]
+            let mut __required: Vec<&str> = Vec::new();
+            #(#entries)*
+            ::forgeconf::schema::object(__properties, __required, #additional)
+        }
+    }
+}
+
+/// Maps calls to the `forgeconf::validators` helpers that have a JSON
+/// Schema equivalent onto `__schema`. Other validators are skipped.
+fn render_validator_schema(expr: &Expr) -> Option<TokenStream> {
+    let (name, args) = validator_call(expr)?;
+    let length = |min: TokenStream, max: TokenStream| {
+        quote! { __schema = ::forgeconf::schema::length(__schema, #min, #max); }
+    };
+    Some(match (name.as_str(), args.as_slice()) {
+        ("range", [min, max]) => quote! {
+            __schema = ::forgeconf::schema::range(
+                __schema,
+                ::forgeconf::ToNode::to_node(&(#min)),
+                ::forgeconf::ToNode::to_node(&(#max)),
+            );
+        },
+        ("non_empty", []) => length(quote! { Some(1) }, quote! { None }),
+        ("min_len", [min]) => length(quote! { Some(#min) }, quote! { None }),
+        ("max_len", [max]) => length(quote! { None }, quote! { Some(#max) }),
+        ("len_range", [min, max]) => length(quote! { Some(#min) }, quote! { Some(#max) }),
+        ("one_of", [values]) => quote! {
+            __schema = {
+                use ::forgeconf::ToNode as _;
+                ::forgeconf::schema::enumerated(
+                    __schema,
+                    ::std::iter::IntoIterator::into_iter(#values).map(|__value| __value.to_node()),
+                )
+            };
+        },
+        ("matches_regex", [pattern]) => quote! {
+            __schema = ::forgeconf::schema::with(
+                __schema,
+                "pattern",
+                ::forgeconf::ConfigNode::String((#pattern).as_str().to_string()),
+            );
+        },
+        _ => return None,
+    })
 }
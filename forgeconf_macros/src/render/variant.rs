@@ -0,0 +1,341 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{ItemEnum, LitStr, Result};
+
+use super::field::{render_object_schema, render_struct_init, render_table_node};
+use crate::model::{ForgeconfAttr, UnknownFields, VariantShape, VariantSpec};
+
+/// Generates `impl FromNode for Enum` and, with `to_node`, the matching
+/// `ToNode` impl.
+///
+/// Strings select unit variants. Tables select data variants either through
+/// a single `{ variant = payload }` entry (externally tagged) or through the
+/// key named by `tag = "..."` (internally tagged), in which case the rest of
+/// the table is the payload.
+pub fn render_enum(
+    item: &ItemEnum,
+    args: &ForgeconfAttr,
+    variants: &[VariantSpec],
+) -> Result<TokenStream> {
+    let ident = &item.ident;
+    let expected = format!(
+        "one of [{}]",
+        variants
+            .iter()
+            .map(|variant| variant.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let expected_lit = LitStr::new(&expected, ident.span());
+
+    let unit_arms = variants
+        .iter()
+        .filter(|variant| matches!(variant.shape, VariantShape::Unit))
+        .map(|variant| {
+            let variant_ident = &variant.ident;
+            let matches = render_name_match(variant, args.insensitive);
+            quote! {
+                if #matches {
+                    return Ok(#ident::#variant_ident);
+                }
+            }
+        });
+
+    let table_arms: Vec<TokenStream> = variants
+        .iter()
+        .map(|variant| render_table_arm(ident, variant, args))
+        .collect();
+
+    let table_branch = match &args.tag {
+        Some(tag) => {
+            let tag_lit = LitStr::new(tag, ident.span());
+            quote! {
+                let mut __table = __table.clone();
+                let __tag_key = ::std::format!("{}.{}", key, #tag_lit);
+                let __name = match __table.remove(#tag_lit) {
+                    Some(
+                        ::forgeconf::ConfigNode::Scalar(__name)
+                        | ::forgeconf::ConfigNode::String(__name),
+                    ) => __name,
+                    Some(other) => {
+                        return Err(::forgeconf::ConfigError::mismatch(
+                            __tag_key,
+                            #expected_lit,
+                            other.kind(),
+                        ));
+                    },
+                    None => return Err(::forgeconf::ConfigError::missing(__tag_key)),
+                };
+                let __name = __name.trim();
+                let __payload_key = key.to_string();
+                let __payload = ::forgeconf::ConfigNode::Table(__table);
+                let __payload = &__payload;
+                #(#table_arms)*
+                Err(::forgeconf::ConfigError::mismatch(key, #expected_lit, __name))
+            }
+        },
+        None => quote! {
+            let mut __entries = __table.iter();
+            let (__name, __payload) = match (__entries.next(), __entries.next()) {
+                (Some(entry), None) => entry,
+                _ => {
+                    return Err(::forgeconf::ConfigError::mismatch(
+                        key,
+                        #expected_lit,
+                        ::std::format!("table with {} keys", __table.len()),
+                    ));
+                },
+            };
+            let __name = __name.as_str();
+            let __payload_key = ::std::format!("{}.{}", key, __name);
+            #(#table_arms)*
+            Err(::forgeconf::ConfigError::mismatch(key, #expected_lit, __name))
+        },
+    };
+
+    let to_node_impl = args.to_node.then(|| {
+        let to_node_arms = variants
+            .iter()
+            .map(|variant| render_to_node_arm(ident, variant, args));
+        quote! {
+            impl #ident {
+                /// Convert the value back into a tree, the inverse of `from_node`.
+                pub fn to_node(&self) -> ::forgeconf::ConfigNode {
+                    match self {
+                        #(#to_node_arms)*
+                    }
+                }
+            }
+
+            impl ::forgeconf::ToNode for #ident {
+                fn to_node(&self) -> ::forgeconf::ConfigNode {
+                    #ident::to_node(self)
+                }
+            }
+        }
+    });
+    let schema = render_enum_schema(variants, args);
+
+    Ok(quote! {
+        #item
+
+        #to_node_impl
+
+        impl ::forgeconf::FromNode for #ident {
+            fn from_node(
+                node: &::forgeconf::ConfigNode,
+                key: &str,
+            ) -> Result<Self, ::forgeconf::ConfigError> {
+                Self::from_node_in(
+                    node,
+                    key,
+                    ::forgeconf::Coercion::Lenient,
+                    &mut ::forgeconf::LoadContext::new(),
+                )
+            }
+
+            #[allow(unused_variables)]
+            fn from_node_in(
+                node: &::forgeconf::ConfigNode,
+                key: &str,
+                coercion: ::forgeconf::Coercion,
+                __cx: &mut ::forgeconf::LoadContext,
+            ) -> Result<Self, ::forgeconf::ConfigError> {
+                match node {
+                    ::forgeconf::ConfigNode::Scalar(__name)
+                    | ::forgeconf::ConfigNode::String(__name) => {
+                        let __name = __name.trim();
+                        #(#unit_arms)*
+                        Err(::forgeconf::ConfigError::mismatch(key, #expected_lit, __name))
+                    },
+                    ::forgeconf::ConfigNode::Table(__table) => {
+                        #table_branch
+                    },
+                    ::forgeconf::ConfigNode::Null => Err(::forgeconf::ConfigError::missing(key)),
+                    other => Err(::forgeconf::ConfigError::mismatch(
+                        key,
+                        #expected_lit,
+                        other.kind(),
+                    )),
+                }
+            }
+
+            fn json_schema() -> ::forgeconf::ConfigNode {
+                #schema
+            }
+        }
+    })
+}
+
+/// Describes the forms `from_node` accepts: unit variant names as a string
+/// `enum`, plus one object schema per data variant in the enum's tagging.
+fn render_enum_schema(variants: &[VariantSpec], args: &ForgeconfAttr) -> TokenStream {
+    let unit_names: Vec<LitStr> = variants
+        .iter()
+        .filter(|variant| matches!(variant.shape, VariantShape::Unit))
+        .map(|variant| LitStr::new(&variant.name, variant.ident.span()))
+        .collect();
+    let units = (!unit_names.is_empty()).then(|| {
+        quote! {
+            ::forgeconf::schema::enumerated(
+                ::forgeconf::schema::of_type("string"),
+                [#(::forgeconf::ConfigNode::String(#unit_names.to_string())),*],
+            ),
+        }
+    });
+
+    let tables = variants.iter().filter_map(|variant| {
+        let name_lit = LitStr::new(&variant.name, variant.ident.span());
+        let payload = match &variant.shape {
+            VariantShape::Unit if args.tag.is_none() => return None,
+            VariantShape::Unit => render_object_schema(&[], &args.unknown_fields),
+            VariantShape::Newtype(ty) => quote! { <#ty as ::forgeconf::FromNode>::json_schema() },
+            VariantShape::Struct(fields) => render_object_schema(fields, &args.unknown_fields),
+        };
+        Some(match &args.tag {
+            Some(tag) => {
+                let tag_lit = LitStr::new(tag, variant.ident.span());
+                quote! { ::forgeconf::schema::tagged(#payload, #tag_lit, #name_lit), }
+            },
+            None => quote! {
+                ::forgeconf::schema::object(
+                    [(#name_lit.to_string(), #payload)],
+                    [#name_lit],
+                    false,
+                ),
+            },
+        })
+    });
+
+    quote! {
+        ::forgeconf::schema::one_of([#units #(#tables)*])
+    }
+}
+
+/// Renders unit variants as their name and data variants in the same tagged
+/// form `from_node` reads.
+fn render_to_node_arm(
+    ident: &syn::Ident,
+    variant: &VariantSpec,
+    args: &ForgeconfAttr,
+) -> TokenStream {
+    let variant_ident = &variant.ident;
+    let name_lit = LitStr::new(&variant.name, variant.ident.span());
+
+    let (pattern, payload) = match &variant.shape {
+        VariantShape::Unit => {
+            return quote! {
+                #ident::#variant_ident => ::forgeconf::ConfigNode::String(#name_lit.to_string()),
+            };
+        },
+        VariantShape::Newtype(_) => (
+            quote! { #ident::#variant_ident(__inner) },
+            quote! { ::forgeconf::ToNode::to_node(__inner) },
+        ),
+        VariantShape::Struct(fields) => {
+            let idents = fields.iter().map(|field| &field.ident);
+            (
+                quote! { #ident::#variant_ident { #(#idents),* } },
+                render_table_node(fields, |ident| quote! { #ident }),
+            )
+        },
+    };
+
+    let node = match &args.tag {
+        Some(tag) => {
+            let tag_lit = LitStr::new(tag, ident.span());
+            quote! {
+                {
+                    let mut __node = #payload;
+                    if let ::forgeconf::ConfigNode::Table(__table) = &mut __node {
+                        __table.insert(
+                            #tag_lit.to_string(),
+                            ::forgeconf::ConfigNode::String(#name_lit.to_string()),
+                        );
+                    }
+                    __node
+                }
+            }
+        },
+        None => quote! {
+            {
+                let mut __outer = ::std::collections::BTreeMap::new();
+                __outer.insert(#name_lit.to_string(), #payload);
+                ::forgeconf::ConfigNode::Table(__outer)
+            }
+        },
+    };
+
+    quote! { #pattern => #node, }
+}
+
+fn render_name_match(variant: &VariantSpec, insensitive: bool) -> TokenStream {
+    let name_lit = LitStr::new(&variant.name, variant.ident.span());
+    if insensitive {
+        quote! { __name.eq_ignore_ascii_case(#name_lit) }
+    } else {
+        quote! { __name == #name_lit }
+    }
+}
+
+/// Builds the variant selected by `__name` out of `__payload`, reporting
+/// errors under `__payload_key`.
+fn render_table_arm(
+    ident: &syn::Ident,
+    variant: &VariantSpec,
+    args: &ForgeconfAttr,
+) -> TokenStream {
+    let variant_ident = &variant.ident;
+    let matches = render_name_match(variant, args.insensitive);
+
+    // With a tag, a unit variant's payload is the rest of the table, so it
+    // goes through the unknown-key check like a struct variant without fields.
+    let checks_unit = args.tag.is_some() && !matches!(args.unknown_fields, UnknownFields::Ignore);
+    let build = match &variant.shape {
+        VariantShape::Unit if !checks_unit => quote! { Ok(#ident::#variant_ident) },
+        VariantShape::Newtype(ty) => quote! {
+            <#ty as ::forgeconf::FromNode>::from_node_in(__payload, &__payload_key, coercion, __cx)
+                .map(#ident::#variant_ident)
+        },
+        VariantShape::Unit | VariantShape::Struct(_) => {
+            let fields = match &variant.shape {
+                VariantShape::Struct(fields) => fields.as_slice(),
+                _ => &[],
+            };
+            let loader_ident = format_ident!("__forgeconf_load_{}", variant.ident);
+            let struct_init =
+                render_struct_init(quote! { #ident::#variant_ident }, fields, &args.unknown_fields);
+            quote! {
+                {
+                    #[allow(non_snake_case, unused_mut)]
+                    fn #loader_ident(
+                        mut map: ::std::collections::BTreeMap<String, ::forgeconf::ConfigNode>,
+                        __cx: &mut ::forgeconf::LoadContext,
+                    ) -> Result<#ident, ::forgeconf::ConfigError> {
+                        #struct_init
+                    }
+
+                    match __payload {
+                        ::forgeconf::ConfigNode::Table(__map) => __cx
+                            .scoped(&__payload_key, |__cx| #loader_ident(__map.clone(), __cx))
+                            .map_err(|err| ::forgeconf::ConfigError::nested(&__payload_key, err)),
+                        ::forgeconf::ConfigNode::Null => __cx
+                            .scoped(&__payload_key, |__cx| #loader_ident(Default::default(), __cx))
+                            .map_err(|err| ::forgeconf::ConfigError::nested(&__payload_key, err)),
+                        other => Err(::forgeconf::ConfigError::mismatch(
+                            &__payload_key,
+                            "table",
+                            other.kind(),
+                        )),
+                    }
+                }
+            }
+        },
+    };
+
+    quote! {
+        if #matches {
+            return #build;
+        }
+    }
+}